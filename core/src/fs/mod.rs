@@ -1,16 +1,65 @@
+mod source;
+mod watcher;
+
 use std::path::{Path, PathBuf};
 
+use anyhow::anyhow;
+
+pub use source::*;
+pub use watcher::*;
+
 pub fn to_absolute_path<P>(filepath: P) -> anyhow::Result<PathBuf>
 where
     P: AsRef<Path>,
+{
+    to_absolute_path_with_root(filepath, std::env::current_dir()?)
+}
+
+pub fn to_absolute_path_with_root<P, R>(filepath: P, root: R) -> anyhow::Result<PathBuf>
+where
+    P: AsRef<Path>,
+    R: AsRef<Path>,
 {
     let filepath = filepath.as_ref();
     Ok(if filepath.is_absolute() {
         filepath.to_path_buf()
     } else {
         let mut scratch = PathBuf::new();
-        scratch.push(std::env::current_dir()?);
+        scratch.push(root);
         scratch.push(filepath);
         scratch
     })
 }
+
+// canonicalizes the path and verifies that it exists
+pub fn resolve_asset<P>(filepath: P) -> anyhow::Result<PathBuf>
+where
+    P: AsRef<Path>,
+{
+    let filepath = to_absolute_path(filepath)?;
+    filepath
+        .canonicalize()
+        .map_err(|e| anyhow!("Asset not found: {} ({})", filepath.display(), e))
+}
+
+// overrides the asset root for packaged builds, relative paths are from the current directory
+pub const ASSET_ROOT_ENV: &str = "ENGINE_ASSET_ROOT";
+
+// ENGINE_ASSET_ROOT if it's set, then the executable directory if assets
+// were deployed next to it, otherwise the current directory (running from the source tree)
+pub fn default_asset_root() -> anyhow::Result<PathBuf> {
+    if let Some(root) = std::env::var_os(ASSET_ROOT_ENV) {
+        if !root.is_empty() {
+            return to_absolute_path(root);
+        }
+    }
+
+    let exe = std::env::current_exe()?;
+    if let Some(exe_dir) = exe.parent() {
+        if exe_dir.join("assets").is_dir() {
+            return Ok(exe_dir.to_path_buf());
+        }
+    }
+
+    Ok(std::env::current_dir()?)
+}
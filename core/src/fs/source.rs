@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::anyhow;
+use zip::ZipArchive;
+
+use super::{resolve_asset, to_absolute_path_with_root};
+
+pub trait AssetSource: Send + Sync {
+    fn read(&self, filepath: &Path) -> anyhow::Result<Vec<u8>>;
+
+    fn exists(&self, filepath: &Path) -> bool;
+
+    // every file in the source, relative to the source root
+    fn files(&self) -> anyhow::Result<Vec<PathBuf>>;
+}
+
+// loose files on disk under a root directory
+pub struct DirectorySource {
+    root: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new<P>(root: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self { root: root.into() }
+    }
+
+    pub fn get_root(&self) -> &Path {
+        &self.root
+    }
+
+    // symlinked directories are followed, but each real directory is only visited once
+    fn collect_files(
+        &self,
+        dir: &Path,
+        visited: &mut HashSet<PathBuf>,
+        files: &mut Vec<PathBuf>,
+    ) -> anyhow::Result<()> {
+        if !visited.insert(std::fs::canonicalize(dir)?) {
+            return Ok(());
+        }
+
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.collect_files(&path, visited, files)?;
+            } else if let Ok(relative) = path.strip_prefix(&self.root) {
+                files.push(relative.to_path_buf());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl AssetSource for DirectorySource {
+    fn read(&self, filepath: &Path) -> anyhow::Result<Vec<u8>> {
+        let filepath = resolve_asset(to_absolute_path_with_root(filepath, &self.root)?)?;
+        Ok(std::fs::read(filepath)?)
+    }
+
+    fn exists(&self, filepath: &Path) -> bool {
+        to_absolute_path_with_root(filepath, &self.root)
+            .map(|x| x.is_file())
+            .unwrap_or(false)
+    }
+
+    fn files(&self) -> anyhow::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        self.collect_files(&self.root, &mut HashSet::new(), &mut files)?;
+        Ok(files)
+    }
+}
+
+// assets bundled into a single zip archive
+pub struct ZipSource<R>
+where
+    R: Read + Seek,
+{
+    archive: Mutex<ZipArchive<R>>,
+}
+
+impl ZipSource<File> {
+    pub fn open<P>(filepath: P) -> anyhow::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::new(File::open(resolve_asset(filepath)?)?)
+    }
+}
+
+impl<R> ZipSource<R>
+where
+    R: Read + Seek,
+{
+    pub fn new(reader: R) -> anyhow::Result<Self> {
+        Ok(Self {
+            archive: Mutex::new(ZipArchive::new(reader)?),
+        })
+    }
+
+    // zip entries always use / as the separator
+    fn entry_name(filepath: &Path) -> String {
+        filepath
+            .components()
+            .map(|x| x.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+impl<R> AssetSource for ZipSource<R>
+where
+    R: Read + Seek + Send,
+{
+    fn read(&self, filepath: &Path) -> anyhow::Result<Vec<u8>> {
+        let name = Self::entry_name(filepath);
+
+        let mut archive = self
+            .archive
+            .lock()
+            .map_err(|_| anyhow!("Asset archive lock poisoned"))?;
+        let mut file = archive
+            .by_name(&name)
+            .map_err(|e| anyhow!("Asset not found: {} ({})", name, e))?;
+
+        let mut data = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut data)?;
+
+        Ok(data)
+    }
+
+    fn exists(&self, filepath: &Path) -> bool {
+        let name = Self::entry_name(filepath);
+
+        match self.archive.lock() {
+            Ok(mut archive) => archive.by_name(&name).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    fn files(&self) -> anyhow::Result<Vec<PathBuf>> {
+        let mut archive = self
+            .archive
+            .lock()
+            .map_err(|_| anyhow!("Asset archive lock poisoned"))?;
+
+        let mut files = Vec::with_capacity(archive.len());
+        for i in 0..archive.len() {
+            let file = archive.by_index(i)?;
+            if !file.is_dir() {
+                files.push(PathBuf::from(file.name()));
+            }
+        }
+
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use super::*;
+
+    fn sorted(mut files: Vec<PathBuf>) -> Vec<PathBuf> {
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn zip_source_reads_nested_entries() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in [("scene.json", "{}"), ("meshes/cube.obj", "v 0 0 0")].iter() {
+            writer
+                .start_file(*name, zip::write::FileOptions::default())
+                .unwrap();
+            writer.write_all(data.as_bytes()).unwrap();
+        }
+        let source = ZipSource::new(writer.finish().unwrap()).unwrap();
+
+        let mesh = Path::new("meshes").join("cube.obj");
+        assert!(source.exists(&mesh));
+        assert_eq!(source.read(&mesh).unwrap(), b"v 0 0 0");
+        assert!(!source.exists(Path::new("missing.json")));
+        assert!(source.read(Path::new("missing.json")).is_err());
+
+        assert_eq!(
+            sorted(source.files().unwrap()),
+            vec![
+                PathBuf::from("meshes/cube.obj"),
+                PathBuf::from("scene.json")
+            ]
+        );
+    }
+
+    #[test]
+    fn directory_source_lists_files_relative_to_the_root() {
+        let root = std::env::temp_dir().join(format!(
+            "directory_source_lists_files_relative_to_the_root-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("meshes")).unwrap();
+        std::fs::write(root.join("scene.json"), "{}").unwrap();
+        std::fs::write(root.join("meshes").join("cube.obj"), "v 0 0 0").unwrap();
+
+        let source = DirectorySource::new(root.clone());
+
+        let mesh = Path::new("meshes").join("cube.obj");
+        assert!(source.exists(&mesh));
+        assert_eq!(source.read(&mesh).unwrap(), b"v 0 0 0");
+        assert!(!source.exists(Path::new("meshes")));
+
+        assert_eq!(
+            sorted(source.files().unwrap()),
+            vec![mesh, PathBuf::from("scene.json")]
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
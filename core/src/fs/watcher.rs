@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// polls file modification times, no OS notification support
+#[derive(Debug, Default)]
+pub struct FileWatcher {
+    files: HashMap<PathBuf, Option<SystemTime>>,
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl FileWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn watch<P>(&mut self, path: P)
+    where
+        P: Into<PathBuf>,
+    {
+        let path = path.into();
+        let modified = modified_time(&path);
+        self.files.insert(path, modified);
+    }
+
+    pub fn unwatch(&mut self, path: &Path) {
+        self.files.remove(path);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    // files modified (or created / deleted) since the last poll
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for (path, last_modified) in self.files.iter_mut() {
+            let modified = modified_time(path);
+            if modified != *last_modified {
+                *last_modified = modified;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn creating_and_deleting_a_file_are_changes() {
+        let path = std::env::temp_dir().join(format!(
+            "creating_and_deleting_a_file_are_changes-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut watcher = FileWatcher::new();
+        watcher.watch(path.clone());
+        assert!(watcher.poll().is_empty());
+
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(watcher.poll(), vec![path.clone()]);
+        assert!(watcher.poll().is_empty());
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(watcher.poll(), vec![path.clone()]);
+
+        watcher.unwatch(&path);
+        assert!(watcher.is_empty());
+    }
+}
@@ -0,0 +1,253 @@
+use std::any::Any;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use log::{debug, error};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_owned()
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct LoadProgress {
+    pub loaded: usize,
+    pub total: usize,
+}
+
+impl LoadProgress {
+    pub fn is_complete(&self) -> bool {
+        self.loaded >= self.total
+    }
+
+    // 0..1, an empty batch is complete
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return 1.0;
+        }
+
+        self.loaded as f32 / self.total as f32
+    }
+}
+
+// results of a batch of jobs, in submission order
+// jobs that panicked report the panic message instead of a result
+pub struct JobHandle<T> {
+    results: Receiver<(usize, Result<T, String>)>,
+    loaded: Arc<AtomicUsize>,
+    total: usize,
+}
+
+impl<T> JobHandle<T> {
+    pub fn progress(&self) -> LoadProgress {
+        LoadProgress {
+            loaded: self.loaded.load(Ordering::Acquire),
+            total: self.total,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.progress().is_complete()
+    }
+
+    // blocks until every job has finished, fails if any job panicked
+    pub fn wait(self) -> anyhow::Result<Vec<T>> {
+        let mut results = Vec::with_capacity(self.total);
+        for _ in 0..self.total {
+            results.push(
+                self.results
+                    .recv()
+                    .map_err(|_| anyhow::anyhow!("Job pool shut down before the jobs completed"))?,
+            );
+        }
+
+        results.sort_by_key(|(idx, _)| *idx);
+        results
+            .into_iter()
+            .map(|(idx, result)| result.map_err(|e| anyhow::anyhow!("Job {} panicked: {}", idx, e)))
+            .collect()
+    }
+}
+
+// fixed size thread pool, workers exit when the pool is dropped
+pub struct JobPool {
+    sender: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl JobPool {
+    // 0 uses one worker per CPU
+    pub fn new(worker_count: usize) -> anyhow::Result<Self> {
+        let worker_count = if worker_count == 0 {
+            num_cpus::get()
+        } else {
+            worker_count
+        };
+
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let mut workers = Vec::with_capacity(worker_count);
+        for idx in 0..worker_count {
+            let receiver = receiver.clone();
+            workers.push(
+                std::thread::Builder::new()
+                    .name(format!("job-worker-{}", idx))
+                    .spawn(move || loop {
+                        let job = match receiver.lock() {
+                            Ok(receiver) => receiver.recv(),
+                            Err(_) => break,
+                        };
+
+                        // keep the worker alive if a job panics
+                        match job {
+                            Ok(job) => {
+                                if let Err(e) = catch_unwind(AssertUnwindSafe(job)) {
+                                    error!("Job panicked: {}", panic_message(e.as_ref()));
+                                }
+                            }
+                            Err(_) => break,
+                        }
+                    })?,
+            );
+        }
+
+        debug!("Started {} job workers", worker_count);
+
+        Ok(Self {
+            sender: Some(sender),
+            workers,
+        })
+    }
+
+    pub fn worker_count(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn spawn<F>(&self, job: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        if let Some(sender) = &self.sender {
+            if sender.send(Box::new(job)).is_err() {
+                error!("Job pool workers have shut down");
+            }
+        }
+    }
+
+    pub fn spawn_all<T, F>(&self, jobs: Vec<F>) -> JobHandle<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (sender, results) = channel();
+        let loaded = Arc::new(AtomicUsize::new(0));
+        let total = jobs.len();
+
+        for (idx, job) in jobs.into_iter().enumerate() {
+            let sender = sender.clone();
+            let loaded = loaded.clone();
+            self.spawn(move || {
+                // a panicking job still counts as loaded so progress completes
+                let result =
+                    catch_unwind(AssertUnwindSafe(job)).map_err(|e| panic_message(e.as_ref()));
+                loaded.fetch_add(1, Ordering::AcqRel);
+
+                // the handle may have been dropped
+                let _ = sender.send((idx, result));
+            });
+        }
+
+        JobHandle {
+            results,
+            loaded,
+            total,
+        }
+    }
+}
+
+impl Drop for JobPool {
+    fn drop(&mut self) {
+        // closing the channel stops the workers
+        self.sender.take();
+
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_reaches_the_total() {
+        let pool = JobPool::new(2).unwrap();
+
+        let jobs = (0..3).map(|i| move || i * 10).collect::<Vec<_>>();
+        let handle = pool.spawn_all(jobs);
+        assert_eq!(handle.progress().total, 3);
+
+        while !handle.is_complete() {
+            std::thread::yield_now();
+        }
+        assert_eq!(
+            handle.progress(),
+            LoadProgress {
+                loaded: 3,
+                total: 3,
+            }
+        );
+
+        // results are in submission order regardless of completion order
+        assert_eq!(handle.wait().unwrap(), vec![0, 10, 20]);
+    }
+
+    #[test]
+    fn panicking_job_fails_the_batch_and_completes_progress() {
+        let pool = JobPool::new(1).unwrap();
+
+        let jobs: Vec<Box<dyn FnOnce() -> u32 + Send>> = vec![
+            Box::new(|| 1),
+            Box::new(|| panic!("bad asset")),
+            Box::new(|| 3),
+        ];
+        let handle = pool.spawn_all(jobs);
+
+        while !handle.is_complete() {
+            std::thread::yield_now();
+        }
+        assert_eq!(
+            handle.progress(),
+            LoadProgress {
+                loaded: 3,
+                total: 3,
+            }
+        );
+
+        let err = handle.wait().unwrap_err();
+        assert_eq!(err.to_string(), "Job 1 panicked: bad asset");
+
+        // the worker survived the panic
+        assert_eq!(pool.spawn_all(vec![|| 4]).wait().unwrap(), vec![4]);
+    }
+
+    #[test]
+    fn empty_batch_is_complete() {
+        let progress = LoadProgress::default();
+
+        assert!(progress.is_complete());
+        assert_eq!(progress.fraction(), 1.0);
+    }
+}
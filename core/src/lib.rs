@@ -1,2 +1,4 @@
 pub mod fs;
+pub mod jobs;
 pub mod math;
+pub mod spatial;
@@ -0,0 +1,46 @@
+use super::matrix::Matrix4;
+use super::vector::{Vector2, Vector3, Vector4};
+
+// float comparison with an explicit tolerance
+pub trait ApproxEq {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool;
+}
+
+impl ApproxEq for f32 {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        (self - other).abs() <= epsilon
+    }
+}
+
+impl ApproxEq for Vector2 {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.x.approx_eq(&other.x, epsilon) && self.y.approx_eq(&other.y, epsilon)
+    }
+}
+
+impl ApproxEq for Vector3 {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.x.approx_eq(&other.x, epsilon)
+            && self.y.approx_eq(&other.y, epsilon)
+            && self.z.approx_eq(&other.z, epsilon)
+    }
+}
+
+impl ApproxEq for Vector4 {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.x.approx_eq(&other.x, epsilon)
+            && self.y.approx_eq(&other.y, epsilon)
+            && self.z.approx_eq(&other.z, epsilon)
+            && self.w.approx_eq(&other.w, epsilon)
+    }
+}
+
+impl ApproxEq for Matrix4 {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.m
+            .iter()
+            .flatten()
+            .zip(other.m.iter().flatten())
+            .all(|(a, b)| a.approx_eq(b, epsilon))
+    }
+}
@@ -0,0 +1,117 @@
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+use super::approx::ApproxEq;
+
+// RGBA, components in 0..1
+// colors are assumed to be sRGB unless converted with to_linear()
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+
+    #[serde(default = "default_alpha")]
+    pub a: f32,
+}
+
+fn default_alpha() -> f32 {
+    1.0
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::BLACK
+    }
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0.0, 0.0, 0.0);
+    pub const WHITE: Color = Color::rgb(1.0, 1.0, 1.0);
+    pub const RED: Color = Color::rgb(1.0, 0.0, 0.0);
+    pub const GREEN: Color = Color::rgb(0.0, 1.0, 0.0);
+    pub const BLUE: Color = Color::rgb(0.0, 0.0, 1.0);
+
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self { r, g, b, a: 1.0 }
+    }
+
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    // #RRGGBB or #RRGGBBAA, the # is optional
+    pub fn from_hex(hex: &str) -> anyhow::Result<Self> {
+        let hex = hex.trim_start_matches('#');
+        if (hex.len() != 6 && hex.len() != 8) || !hex.is_ascii() {
+            bail!("Invalid hex color: {}", hex);
+        }
+
+        let component = |i: usize| -> anyhow::Result<f32> {
+            Ok(f32::from(u8::from_str_radix(&hex[i..i + 2], 16)?) / 255.0)
+        };
+
+        Ok(Self {
+            r: component(0)?,
+            g: component(2)?,
+            b: component(4)?,
+            a: if hex.len() == 8 { component(6)? } else { 1.0 },
+        })
+    }
+
+    // alpha is always linear
+    pub fn to_linear(self) -> Self {
+        fn convert(c: f32) -> f32 {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        }
+
+        Self {
+            r: convert(self.r),
+            g: convert(self.g),
+            b: convert(self.b),
+            a: self.a,
+        }
+    }
+
+    pub fn to_srgb(self) -> Self {
+        fn convert(c: f32) -> f32 {
+            if c <= 0.003_130_8 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            }
+        }
+
+        Self {
+            r: convert(self.r),
+            g: convert(self.g),
+            b: convert(self.b),
+            a: self.a,
+        }
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from(c: [f32; 4]) -> Self {
+        Self::rgba(c[0], c[1], c[2], c[3])
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(c: Color) -> Self {
+        [c.r, c.g, c.b, c.a]
+    }
+}
+
+impl ApproxEq for Color {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.r.approx_eq(&other.r, epsilon)
+            && self.g.approx_eq(&other.g, epsilon)
+            && self.b.approx_eq(&other.b, epsilon)
+            && self.a.approx_eq(&other.a, epsilon)
+    }
+}
@@ -0,0 +1,229 @@
+use std::ops::*;
+
+use serde::{Deserialize, Serialize};
+
+use super::vector::{Vector2, Vector3};
+
+//#region IVector2
+
+// tile and pixel coordinates
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct IVector2 {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl IVector2 {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+}
+
+impl Neg for IVector2 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
+impl Add<IVector2> for IVector2 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        Self {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+impl AddAssign<IVector2> for IVector2 {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub<IVector2> for IVector2 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        Self {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+}
+
+impl SubAssign<IVector2> for IVector2 {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<i32> for IVector2 {
+    type Output = Self;
+
+    fn mul(self, rhs: i32) -> Self::Output {
+        Self {
+            x: self.x * rhs,
+            y: self.y * rhs,
+        }
+    }
+}
+
+impl MulAssign<i32> for IVector2 {
+    fn mul_assign(&mut self, rhs: i32) {
+        *self = *self * rhs;
+    }
+}
+
+//#endregion
+
+//#region IVector3
+
+// voxel and grid cell coordinates
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct IVector3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl IVector3 {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl Neg for IVector3 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+impl Add<IVector3> for IVector3 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        Self {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        }
+    }
+}
+
+impl AddAssign<IVector3> for IVector3 {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub<IVector3> for IVector3 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        Self {
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
+        }
+    }
+}
+
+impl SubAssign<IVector3> for IVector3 {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<i32> for IVector3 {
+    type Output = Self;
+
+    fn mul(self, rhs: i32) -> Self::Output {
+        Self {
+            x: self.x * rhs,
+            y: self.y * rhs,
+            z: self.z * rhs,
+        }
+    }
+}
+
+impl MulAssign<i32> for IVector3 {
+    fn mul_assign(&mut self, rhs: i32) {
+        *self = *self * rhs;
+    }
+}
+
+//#endregion
+
+//#region Conversions
+
+// float to integer conversions floor, so negative coordinates
+// land in the cell below rather than collapsing onto 0
+
+impl From<Vector2> for IVector2 {
+    fn from(v: Vector2) -> Self {
+        Self {
+            x: v.x.floor() as i32,
+            y: v.y.floor() as i32,
+        }
+    }
+}
+
+impl From<IVector2> for Vector2 {
+    fn from(v: IVector2) -> Self {
+        Self {
+            x: v.x as f32,
+            y: v.y as f32,
+        }
+    }
+}
+
+impl From<Vector3> for IVector3 {
+    fn from(v: Vector3) -> Self {
+        Self {
+            x: v.x.floor() as i32,
+            y: v.y.floor() as i32,
+            z: v.z.floor() as i32,
+        }
+    }
+}
+
+impl From<IVector3> for Vector3 {
+    fn from(v: IVector3) -> Self {
+        Self {
+            x: v.x as f32,
+            y: v.y as f32,
+            z: v.z as f32,
+        }
+    }
+}
+
+impl From<[i32; 2]> for IVector2 {
+    fn from(v: [i32; 2]) -> Self {
+        Self { x: v[0], y: v[1] }
+    }
+}
+
+impl From<[i32; 3]> for IVector3 {
+    fn from(v: [i32; 3]) -> Self {
+        Self {
+            x: v[0],
+            y: v[1],
+            z: v[2],
+        }
+    }
+}
+
+//#endregion
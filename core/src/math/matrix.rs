@@ -0,0 +1,351 @@
+use std::ops::*;
+
+use serde::{Deserialize, Serialize};
+
+use super::vector::{Vector3, Vector4};
+
+// row-major, transforms column vectors (M * v)
+// serialized as a flat array of 16 values in row-major order
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "[f32; 16]", into = "[f32; 16]")]
+pub struct Matrix4 {
+    pub m: [[f32; 4]; 4],
+}
+
+impl Default for Matrix4 {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Matrix4 {
+    pub fn identity() -> Self {
+        Self {
+            m: [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn from_translation(translation: Vector3) -> Self {
+        let mut matrix = Self::identity();
+        matrix.m[0][3] = translation.x;
+        matrix.m[1][3] = translation.y;
+        matrix.m[2][3] = translation.z;
+        matrix
+    }
+
+    pub fn from_scale(scale: Vector3) -> Self {
+        let mut matrix = Self::identity();
+        matrix.m[0][0] = scale.x;
+        matrix.m[1][1] = scale.y;
+        matrix.m[2][2] = scale.z;
+        matrix
+    }
+
+    // right-handed, vulkan clip space (y down, depth 0..1)
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let f = 1.0 / (fov_y / 2.0).tan();
+
+        let mut matrix = Self { m: [[0.0; 4]; 4] };
+        matrix.m[0][0] = f / aspect;
+        matrix.m[1][1] = -f;
+        matrix.m[2][2] = far / (near - far);
+        matrix.m[2][3] = (near * far) / (near - far);
+        matrix.m[3][2] = -1.0;
+        matrix
+    }
+
+    // same as perspective() but maps near to depth 1 and far to depth 0,
+    // for better depth precision with a Greater depth test
+    pub fn perspective_reversed(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let f = 1.0 / (fov_y / 2.0).tan();
+
+        let mut matrix = Self { m: [[0.0; 4]; 4] };
+        matrix.m[0][0] = f / aspect;
+        matrix.m[1][1] = -f;
+        matrix.m[2][2] = near / (far - near);
+        matrix.m[2][3] = (near * far) / (far - near);
+        matrix.m[3][2] = -1.0;
+        matrix
+    }
+
+    // right-handed, vulkan clip space (y down, depth 0..1)
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        let mut matrix = Self::identity();
+        matrix.m[0][0] = 2.0 / (right - left);
+        matrix.m[1][1] = -2.0 / (top - bottom);
+        matrix.m[2][2] = 1.0 / (near - far);
+        matrix.m[0][3] = -(right + left) / (right - left);
+        matrix.m[1][3] = (top + bottom) / (top - bottom);
+        matrix.m[2][3] = near / (near - far);
+        matrix
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut matrix = *self;
+        for row in 0..4 {
+            for col in 0..4 {
+                matrix.m[row][col] = self.m[col][row];
+            }
+        }
+        matrix
+    }
+
+    // w = 1, includes translation
+    pub fn transform_point(&self, point: Vector3) -> Vector3 {
+        let m = &self.m;
+        Vector3 {
+            x: m[0][0] * point.x + m[0][1] * point.y + m[0][2] * point.z + m[0][3],
+            y: m[1][0] * point.x + m[1][1] * point.y + m[1][2] * point.z + m[1][3],
+            z: m[2][0] * point.x + m[2][1] * point.y + m[2][2] * point.z + m[2][3],
+        }
+    }
+
+    pub fn transform(&self, v: Vector4) -> Vector4 {
+        let m = &self.m;
+        Vector4 {
+            x: m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z + m[0][3] * v.w,
+            y: m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z + m[1][3] * v.w,
+            z: m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z + m[2][3] * v.w,
+            w: m[3][0] * v.x + m[3][1] * v.y + m[3][2] * v.z + m[3][3] * v.w,
+        }
+    }
+
+    // full w divide, for projection matrices
+    pub fn transform_point_projected(&self, point: Vector3) -> Vector3 {
+        self.transform(point.into()).to_vector3()
+    }
+
+    // w = 0, ignores translation
+    pub fn transform_vector(&self, vector: Vector3) -> Vector3 {
+        let m = &self.m;
+        Vector3 {
+            x: m[0][0] * vector.x + m[0][1] * vector.y + m[0][2] * vector.z,
+            y: m[1][0] * vector.x + m[1][1] * vector.y + m[1][2] * vector.z,
+            z: m[2][0] * vector.x + m[2][1] * vector.y + m[2][2] * vector.z,
+        }
+    }
+
+    // None if the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        // flatten row-major and invert via cofactor expansion
+        let mut a = [0.0; 16];
+        for row in 0..4 {
+            for col in 0..4 {
+                a[row * 4 + col] = self.m[row][col];
+            }
+        }
+
+        let mut inv = [0.0; 16];
+        inv[0] = a[5] * a[10] * a[15] - a[5] * a[11] * a[14] - a[9] * a[6] * a[15]
+            + a[9] * a[7] * a[14]
+            + a[13] * a[6] * a[11]
+            - a[13] * a[7] * a[10];
+        inv[4] = -a[4] * a[10] * a[15] + a[4] * a[11] * a[14] + a[8] * a[6] * a[15]
+            - a[8] * a[7] * a[14]
+            - a[12] * a[6] * a[11]
+            + a[12] * a[7] * a[10];
+        inv[8] = a[4] * a[9] * a[15] - a[4] * a[11] * a[13] - a[8] * a[5] * a[15]
+            + a[8] * a[7] * a[13]
+            + a[12] * a[5] * a[11]
+            - a[12] * a[7] * a[9];
+        inv[12] = -a[4] * a[9] * a[14] + a[4] * a[10] * a[13] + a[8] * a[5] * a[14]
+            - a[8] * a[6] * a[13]
+            - a[12] * a[5] * a[10]
+            + a[12] * a[6] * a[9];
+        inv[1] = -a[1] * a[10] * a[15] + a[1] * a[11] * a[14] + a[9] * a[2] * a[15]
+            - a[9] * a[3] * a[14]
+            - a[13] * a[2] * a[11]
+            + a[13] * a[3] * a[10];
+        inv[5] = a[0] * a[10] * a[15] - a[0] * a[11] * a[14] - a[8] * a[2] * a[15]
+            + a[8] * a[3] * a[14]
+            + a[12] * a[2] * a[11]
+            - a[12] * a[3] * a[10];
+        inv[9] = -a[0] * a[9] * a[15] + a[0] * a[11] * a[13] + a[8] * a[1] * a[15]
+            - a[8] * a[3] * a[13]
+            - a[12] * a[1] * a[11]
+            + a[12] * a[3] * a[9];
+        inv[13] = a[0] * a[9] * a[14] - a[0] * a[10] * a[13] - a[8] * a[1] * a[14]
+            + a[8] * a[2] * a[13]
+            + a[12] * a[1] * a[10]
+            - a[12] * a[2] * a[9];
+        inv[2] = a[1] * a[6] * a[15] - a[1] * a[7] * a[14] - a[5] * a[2] * a[15]
+            + a[5] * a[3] * a[14]
+            + a[13] * a[2] * a[7]
+            - a[13] * a[3] * a[6];
+        inv[6] = -a[0] * a[6] * a[15] + a[0] * a[7] * a[14] + a[4] * a[2] * a[15]
+            - a[4] * a[3] * a[14]
+            - a[12] * a[2] * a[7]
+            + a[12] * a[3] * a[6];
+        inv[10] = a[0] * a[5] * a[15] - a[0] * a[7] * a[13] - a[4] * a[1] * a[15]
+            + a[4] * a[3] * a[13]
+            + a[12] * a[1] * a[7]
+            - a[12] * a[3] * a[5];
+        inv[14] = -a[0] * a[5] * a[14] + a[0] * a[6] * a[13] + a[4] * a[1] * a[14]
+            - a[4] * a[2] * a[13]
+            - a[12] * a[1] * a[6]
+            + a[12] * a[2] * a[5];
+        inv[3] = -a[1] * a[6] * a[11] + a[1] * a[7] * a[10] + a[5] * a[2] * a[11]
+            - a[5] * a[3] * a[10]
+            - a[9] * a[2] * a[7]
+            + a[9] * a[3] * a[6];
+        inv[7] = a[0] * a[6] * a[11] - a[0] * a[7] * a[10] - a[4] * a[2] * a[11]
+            + a[4] * a[3] * a[10]
+            + a[8] * a[2] * a[7]
+            - a[8] * a[3] * a[6];
+        inv[11] = -a[0] * a[5] * a[11] + a[0] * a[7] * a[9] + a[4] * a[1] * a[11]
+            - a[4] * a[3] * a[9]
+            - a[8] * a[1] * a[7]
+            + a[8] * a[3] * a[5];
+        inv[15] = a[0] * a[5] * a[10] - a[0] * a[6] * a[9] - a[4] * a[1] * a[10]
+            + a[4] * a[2] * a[9]
+            + a[8] * a[1] * a[6]
+            - a[8] * a[2] * a[5];
+
+        let det = a[0] * inv[0] + a[1] * inv[4] + a[2] * inv[8] + a[3] * inv[12];
+        // no absolute epsilon, a valid projection can have a tiny determinant
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+
+        let mut matrix = Self { m: [[0.0; 4]; 4] };
+        for row in 0..4 {
+            for col in 0..4 {
+                let value = inv[row * 4 + col] / det;
+                if !value.is_finite() {
+                    return None;
+                }
+                matrix.m[row][col] = value;
+            }
+        }
+        Some(matrix)
+    }
+}
+
+impl Mul<Matrix4> for Matrix4 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        let mut matrix = Self { m: [[0.0; 4]; 4] };
+        for row in 0..4 {
+            for col in 0..4 {
+                matrix.m[row][col] = (0..4).map(|i| self.m[row][i] * other.m[i][col]).sum();
+            }
+        }
+        matrix
+    }
+}
+
+impl MulAssign<Matrix4> for Matrix4 {
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+
+impl From<[f32; 16]> for Matrix4 {
+    fn from(v: [f32; 16]) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (idx, value) in v.iter().enumerate() {
+            m[idx / 4][idx % 4] = *value;
+        }
+
+        Self { m }
+    }
+}
+
+impl From<Matrix4> for [f32; 16] {
+    fn from(matrix: Matrix4) -> Self {
+        let mut v = [0.0; 16];
+        for (idx, value) in v.iter_mut().enumerate() {
+            *value = matrix.m[idx / 4][idx % 4];
+        }
+
+        v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::ApproxEq;
+
+    // depth of a point on the view axis, the camera looks down -z
+    fn depth_at(projection: &Matrix4, distance: f32) -> f32 {
+        projection
+            .transform_point_projected(Vector3 {
+                x: 0.0,
+                y: 0.0,
+                z: -distance,
+            })
+            .z
+    }
+
+    fn transform() -> Matrix4 {
+        Matrix4::from_translation(Vector3 {
+            x: 1.0,
+            y: -2.0,
+            z: 3.0,
+        }) * Matrix4::from_scale(Vector3 {
+            x: 2.0,
+            y: 4.0,
+            z: 0.5,
+        })
+    }
+
+    #[test]
+    fn serializes_as_a_flat_row_major_array() {
+        let matrix = transform();
+
+        let json = serde_json::to_string(&matrix).unwrap();
+        assert_eq!(
+            json,
+            "[2.0,0.0,0.0,1.0,0.0,4.0,0.0,-2.0,0.0,0.0,0.5,3.0,0.0,0.0,0.0,1.0]"
+        );
+
+        let deserialized: Matrix4 = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, matrix);
+    }
+
+    #[test]
+    fn inverse_round_trips_to_identity() {
+        let matrix = transform();
+        let inverse = matrix.inverse().unwrap();
+
+        assert!((matrix * inverse).approx_eq(&Matrix4::identity(), 1e-5));
+        assert!((inverse * matrix).approx_eq(&Matrix4::identity(), 1e-5));
+    }
+
+    #[test]
+    fn singular_matrix_has_no_inverse() {
+        let matrix = Matrix4::from_scale(Vector3 {
+            x: 1.0,
+            y: 0.0,
+            z: 1.0,
+        });
+
+        assert!(matrix.inverse().is_none());
+    }
+
+    #[test]
+    fn perspective_maps_near_to_0_and_far_to_1() {
+        let projection = Matrix4::perspective(60.0_f32.to_radians(), 1.0, 0.1, 100.0);
+
+        assert!(depth_at(&projection, 0.1).approx_eq(&0.0, 1e-5));
+        assert!(depth_at(&projection, 100.0).approx_eq(&1.0, 1e-5));
+    }
+
+    #[test]
+    fn perspective_reversed_maps_near_to_1_and_far_to_0() {
+        let projection = Matrix4::perspective_reversed(60.0_f32.to_radians(), 1.0, 0.1, 100.0);
+
+        assert!(depth_at(&projection, 0.1).approx_eq(&1.0, 1e-5));
+        assert!(depth_at(&projection, 100.0).approx_eq(&0.0, 1e-5));
+
+        // closer is greater
+        assert!(depth_at(&projection, 1.0) > depth_at(&projection, 10.0));
+    }
+}
@@ -1,5 +1,17 @@
+mod aabb;
+mod approx;
+mod color;
+mod ivector;
+mod matrix;
 mod quaternion;
+mod ray;
 mod vector;
 
+pub use aabb::*;
+pub use approx::*;
+pub use color::*;
+pub use ivector::*;
+pub use matrix::*;
 pub use quaternion::*;
+pub use ray::*;
 pub use vector::*;
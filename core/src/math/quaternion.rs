@@ -1,6 +1,12 @@
+use std::ops::*;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+use super::approx::ApproxEq;
+use super::matrix::Matrix4;
+use super::vector::Vector3;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Quaternion {
     x: f32,
     y: f32,
@@ -8,4 +14,170 @@ pub struct Quaternion {
     w: f32,
 }
 
-impl Quaternion {}
+// a zero quaternion doesn't rotate vectors correctly
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Quaternion {
+    pub fn identity() -> Self {
+        Self::new(0.0, 0.0, 0.0, 1.0)
+    }
+
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    // from the upper 3x3 of a pure rotation matrix
+    pub fn from_matrix(matrix: &Matrix4) -> Self {
+        let m = &matrix.m;
+        let trace = m[0][0] + m[1][1] + m[2][2];
+
+        if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Self::new(
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+                0.25 * s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            Self::new(
+                0.25 * s,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[2][1] - m[1][2]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            Self::new(
+                (m[0][1] + m[1][0]) / s,
+                0.25 * s,
+                (m[1][2] + m[2][1]) / s,
+                (m[0][2] - m[2][0]) / s,
+            )
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            Self::new(
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                0.25 * s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        }
+    }
+
+    fn dot(&self, other: &Self) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    // assumes unit quaternions, takes the shortest path
+    pub fn slerp(self, other: Self, t: f32) -> Self {
+        let mut other = other;
+        let mut cos_theta = self.dot(&other);
+        if cos_theta < 0.0 {
+            other = Self::new(-other.x, -other.y, -other.z, -other.w);
+            cos_theta = -cos_theta;
+        }
+
+        // nearly parallel, fall back to a normalized lerp
+        let (a, b) = if cos_theta > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let theta = cos_theta.acos();
+            let sin_theta = theta.sin();
+            (
+                ((1.0 - t) * theta).sin() / sin_theta,
+                (t * theta).sin() / sin_theta,
+            )
+        };
+
+        let result = Self::new(
+            a * self.x + b * other.x,
+            a * self.y + b * other.y,
+            a * self.z + b * other.z,
+            a * self.w + b * other.w,
+        );
+
+        let mag = result.dot(&result).sqrt();
+        if mag == 0.0 {
+            return Self::default();
+        }
+
+        Self::new(
+            result.x / mag,
+            result.y / mag,
+            result.z / mag,
+            result.w / mag,
+        )
+    }
+
+    // the inverse for unit quaternions
+    pub fn conjugate(&self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    // q * v * q^-1, assumes a unit quaternion
+    pub fn rotate_vector(&self, v: Vector3) -> Vector3 {
+        let p = Self::new(v.x, v.y, v.z, 0.0);
+        let r = *self * p * self.conjugate();
+
+        Vector3 {
+            x: r.x,
+            y: r.y,
+            z: r.z,
+        }
+    }
+
+    // assumes a unit quaternion
+    pub fn to_matrix(&self) -> Matrix4 {
+        let (x, y, z, w) = (self.x, self.y, self.z, self.w);
+
+        let mut matrix = Matrix4::identity();
+        matrix.m[0][0] = 1.0 - 2.0 * (y * y + z * z);
+        matrix.m[0][1] = 2.0 * (x * y - z * w);
+        matrix.m[0][2] = 2.0 * (x * z + y * w);
+
+        matrix.m[1][0] = 2.0 * (x * y + z * w);
+        matrix.m[1][1] = 1.0 - 2.0 * (x * x + z * z);
+        matrix.m[1][2] = 2.0 * (y * z - x * w);
+
+        matrix.m[2][0] = 2.0 * (x * z - y * w);
+        matrix.m[2][1] = 2.0 * (y * z + x * w);
+        matrix.m[2][2] = 1.0 - 2.0 * (x * x + y * y);
+        matrix
+    }
+}
+
+// applies other first, then self
+impl Mul<Quaternion> for Quaternion {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        Self {
+            x: self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            y: self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            z: self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+            w: self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+        }
+    }
+}
+
+impl MulAssign<Quaternion> for Quaternion {
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+
+// component-wise, q and -q are not considered equal
+impl ApproxEq for Quaternion {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.x.approx_eq(&other.x, epsilon)
+            && self.y.approx_eq(&other.y, epsilon)
+            && self.z.approx_eq(&other.z, epsilon)
+            && self.w.approx_eq(&other.w, epsilon)
+    }
+}
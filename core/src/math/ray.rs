@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+use super::vector::{Vector, Vector3};
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ray {
+    pub origin: Vector3,
+
+    // expected to be normalized
+    pub direction: Vector3,
+}
+
+impl Ray {
+    pub fn new(origin: Vector3, direction: Vector3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalized(),
+        }
+    }
+
+    pub fn point_at(&self, t: f32) -> Vector3 {
+        self.origin + self.direction * t
+    }
+
+    // slab test, returns the distance along the ray to the nearest hit
+    pub fn intersect_aabb(&self, min: Vector3, max: Vector3) -> Option<f32> {
+        let origin: [f32; 3] = self.origin.into();
+        let direction: [f32; 3] = self.direction.into();
+        let min: [f32; 3] = min.into();
+        let max: [f32; 3] = max.into();
+
+        let mut tmin = 0.0_f32;
+        let mut tmax = f32::MAX;
+        for i in 0..3 {
+            if direction[i].abs() < f32::EPSILON {
+                // parallel to the slab, must start inside it
+                if origin[i] < min[i] || origin[i] > max[i] {
+                    return None;
+                }
+                continue;
+            }
+
+            let inv = 1.0 / direction[i];
+            let mut t0 = (min[i] - origin[i]) * inv;
+            let mut t1 = (max[i] - origin[i]) * inv;
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            tmin = tmin.max(t0);
+            tmax = tmax.min(t1);
+            if tmin > tmax {
+                return None;
+            }
+        }
+
+        Some(tmin)
+    }
+
+    // the plane is defined by a point on it and its normal
+    pub fn intersect_plane(&self, point: Vector3, normal: Vector3) -> Option<f32> {
+        let denom = normal.dot(&self.direction);
+        if denom.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let t = normal.dot(&(point - self.origin)) / denom;
+        if t < 0.0 {
+            return None;
+        }
+
+        Some(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::ApproxEq;
+
+    fn v(x: f32, y: f32, z: f32) -> Vector3 {
+        Vector3 { x, y, z }
+    }
+
+    #[test]
+    fn hits_aabb_at_the_near_face() {
+        let ray = Ray::new(v(0.0, 0.0, -5.0), v(0.0, 0.0, 1.0));
+
+        let t = ray.intersect_aabb(v(-1.0, -1.0, -1.0), v(1.0, 1.0, 1.0));
+        assert!(t.unwrap().approx_eq(&4.0, 1e-5));
+        assert!(ray.point_at(4.0).approx_eq(&v(0.0, 0.0, -1.0), 1e-5));
+    }
+
+    #[test]
+    fn misses_aabb() {
+        let min = v(-1.0, -1.0, -1.0);
+        let max = v(1.0, 1.0, 1.0);
+
+        // passes beside it
+        let ray = Ray::new(v(2.0, 0.0, -5.0), v(0.0, 0.0, 1.0));
+        assert!(ray.intersect_aabb(min, max).is_none());
+
+        // pointing away from it
+        let ray = Ray::new(v(0.0, 0.0, -5.0), v(0.0, 0.0, -1.0));
+        assert!(ray.intersect_aabb(min, max).is_none());
+    }
+
+    #[test]
+    fn hits_plane_at_the_expected_distance() {
+        let ray = Ray::new(v(0.0, 10.0, 0.0), v(0.0, -1.0, 0.0));
+
+        let t = ray.intersect_plane(v(0.0, 2.0, 0.0), v(0.0, 1.0, 0.0));
+        assert!(t.unwrap().approx_eq(&8.0, 1e-5));
+
+        // parallel
+        let ray = Ray::new(v(0.0, 10.0, 0.0), v(1.0, 0.0, 0.0));
+        assert!(ray
+            .intersect_plane(v(0.0, 2.0, 0.0), v(0.0, 1.0, 0.0))
+            .is_none());
+    }
+}
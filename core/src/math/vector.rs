@@ -3,7 +3,15 @@ use std::ops::*;
 use serde::{Deserialize, Serialize};
 
 // TODO: be cleaner to have a derive macro for this
-pub trait Vector: Sub<Output = Self> + Div<f32, Output = Self> + DivAssign<f32> + Sized {
+pub trait Vector:
+    Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<f32, Output = Self>
+    + Div<f32, Output = Self>
+    + DivAssign<f32>
+    + Copy
+    + Sized
+{
     fn magnitude(&self) -> f32 {
         self.magnitude_squared().sqrt()
     }
@@ -19,6 +27,16 @@ pub trait Vector: Sub<Output = Self> + Div<f32, Output = Self> + DivAssign<f32>
         self / mag
     }
 
+    // None for zero, near-zero and non-finite vectors
+    fn try_normalized(self) -> Option<Self> {
+        let mag = self.magnitude();
+        if !mag.is_finite() || mag <= f32::EPSILON {
+            return None;
+        }
+
+        Some(self / mag)
+    }
+
     fn distance(self, other: Self) -> f32 {
         (other - self).magnitude()
     }
@@ -29,7 +47,10 @@ pub trait Vector: Sub<Output = Self> + Div<f32, Output = Self> + DivAssign<f32>
 
     fn dot(&self, other: &Self) -> f32;
 
-    // TODO: fn lerp(&mut self, other: &Self, t: f32);
+    // t is not clamped
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
 }
 
 //#region Vector2
@@ -218,7 +239,13 @@ impl Vector3 {
         }
     }
 
-    // TODO: fn cross(&self, other: &Self) -> Self;
+    pub fn cross(&self, other: &Self) -> Self {
+        Self {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
 }
 
 impl Vector for Vector3 {
@@ -455,3 +482,186 @@ impl DivAssign<f32> for Vector4 {
 }
 
 //#endregion
+
+//#region Swizzles
+
+// named by the source components in order, 0 is a zero component
+
+impl Vector2 {
+    pub fn yx(self) -> Vector2 {
+        Vector2 {
+            x: self.y,
+            y: self.x,
+        }
+    }
+
+    // 2D to the xy plane
+    pub fn xy0(self) -> Vector3 {
+        self.to_vector3(0.0)
+    }
+
+    // 2D to the xz ground plane
+    pub fn x0y(self) -> Vector3 {
+        Vector3 {
+            x: self.x,
+            y: 0.0,
+            z: self.y,
+        }
+    }
+}
+
+impl Vector3 {
+    // position on the ground plane
+    pub fn xz(self) -> Vector2 {
+        Vector2 {
+            x: self.x,
+            y: self.z,
+        }
+    }
+
+    pub fn yz(self) -> Vector2 {
+        Vector2 {
+            x: self.y,
+            y: self.z,
+        }
+    }
+
+    // flattened onto the xy plane
+    pub fn xy0(self) -> Vector3 {
+        Vector3 { z: 0.0, ..self }
+    }
+
+    // flattened onto the ground plane
+    pub fn x0z(self) -> Vector3 {
+        Vector3 { y: 0.0, ..self }
+    }
+}
+
+//#endregion
+
+//#region Conversions
+
+impl Vector2 {
+    pub fn to_vector3(self, z: f32) -> Vector3 {
+        Vector3 {
+            x: self.x,
+            y: self.y,
+            z,
+        }
+    }
+}
+
+impl Vector3 {
+    pub fn xy(self) -> Vector2 {
+        Vector2 {
+            x: self.x,
+            y: self.y,
+        }
+    }
+
+    pub fn to_vector4(self, w: f32) -> Vector4 {
+        Vector4 {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+            w,
+        }
+    }
+}
+
+impl Vector4 {
+    pub fn xy(self) -> Vector2 {
+        Vector2 {
+            x: self.x,
+            y: self.y,
+        }
+    }
+
+    pub fn xyz(self) -> Vector3 {
+        Vector3 {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+        }
+    }
+
+    // perspective divide
+    pub fn to_vector3(self) -> Vector3 {
+        self.xyz() / self.w
+    }
+}
+
+// z = 0
+impl From<Vector2> for Vector3 {
+    fn from(v: Vector2) -> Self {
+        v.to_vector3(0.0)
+    }
+}
+
+// drops z
+impl From<Vector3> for Vector2 {
+    fn from(v: Vector3) -> Self {
+        v.xy()
+    }
+}
+
+// w = 1, a point
+impl From<Vector3> for Vector4 {
+    fn from(v: Vector3) -> Self {
+        v.to_vector4(1.0)
+    }
+}
+
+// drops w
+impl From<Vector4> for Vector3 {
+    fn from(v: Vector4) -> Self {
+        v.xyz()
+    }
+}
+
+impl From<[f32; 2]> for Vector2 {
+    fn from(v: [f32; 2]) -> Self {
+        Self { x: v[0], y: v[1] }
+    }
+}
+
+impl From<Vector2> for [f32; 2] {
+    fn from(v: Vector2) -> Self {
+        [v.x, v.y]
+    }
+}
+
+impl From<[f32; 3]> for Vector3 {
+    fn from(v: [f32; 3]) -> Self {
+        Self {
+            x: v[0],
+            y: v[1],
+            z: v[2],
+        }
+    }
+}
+
+impl From<Vector3> for [f32; 3] {
+    fn from(v: Vector3) -> Self {
+        [v.x, v.y, v.z]
+    }
+}
+
+impl From<[f32; 4]> for Vector4 {
+    fn from(v: [f32; 4]) -> Self {
+        Self {
+            x: v[0],
+            y: v[1],
+            z: v[2],
+            w: v[3],
+        }
+    }
+}
+
+impl From<Vector4> for [f32; 4] {
+    fn from(v: Vector4) -> Self {
+        [v.x, v.y, v.z, v.w]
+    }
+}
+
+//#endregion
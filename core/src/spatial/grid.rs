@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+
+use crate::math::{Aabb, IVector3, Vector3};
+
+type Cell = IVector3;
+
+// cell coordinates are clamped to this so huge boxes don't overflow i32
+const MAX_CELL: f32 = 1_000_000.0;
+
+// boxes covering more cells than this go in the overflow list instead
+const MAX_ITEM_CELLS: i64 = 64;
+
+fn is_finite(aabb: &Aabb) -> bool {
+    [aabb.min, aabb.max]
+        .iter()
+        .all(|v| v.x.is_finite() && v.y.is_finite() && v.z.is_finite())
+}
+
+fn cell_count(min: Cell, max: Cell) -> i64 {
+    let extent = |min: i32, max: i32| (i64::from(max) - i64::from(min) + 1).max(0);
+    extent(min.x, max.x) * extent(min.y, max.y) * extent(min.z, max.z)
+}
+
+// buckets items by every cell their box overlaps
+// items too large to bucket are checked by every query
+#[derive(Debug)]
+pub struct UniformGrid<T> {
+    cell_size: f32,
+    cells: HashMap<Cell, Vec<usize>>,
+    overflow: Vec<usize>,
+    items: Vec<(T, Aabb)>,
+}
+
+impl<T> UniformGrid<T>
+where
+    T: Copy,
+{
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0.0, "Grid cell size must be positive");
+
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            overflow: Vec::new(),
+            items: Vec::new(),
+        }
+    }
+
+    pub fn get_cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.overflow.clear();
+        self.items.clear();
+    }
+
+    fn cell(&self, v: Vector3) -> Cell {
+        let v = v / self.cell_size;
+        IVector3::new(
+            v.x.floor().clamp(-MAX_CELL, MAX_CELL) as i32,
+            v.y.floor().clamp(-MAX_CELL, MAX_CELL) as i32,
+            v.z.floor().clamp(-MAX_CELL, MAX_CELL) as i32,
+        )
+    }
+
+    fn cell_range(&self, aabb: &Aabb) -> (Cell, Cell) {
+        (self.cell(aabb.min), self.cell(aabb.max))
+    }
+
+    // returns false without inserting if the box isn't finite
+    pub fn insert(&mut self, item: T, aabb: Aabb) -> bool {
+        if !is_finite(&aabb) {
+            return false;
+        }
+
+        let idx = self.items.len();
+        self.items.push((item, aabb));
+
+        let (min, max) = self.cell_range(&aabb);
+        if cell_count(min, max) > MAX_ITEM_CELLS {
+            self.overflow.push(idx);
+            return true;
+        }
+
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    self.cells
+                        .entry(IVector3::new(x, y, z))
+                        .or_default()
+                        .push(idx);
+                }
+            }
+        }
+
+        true
+    }
+
+    // items whose box intersects the query box, each returned once
+    pub fn query(&self, aabb: &Aabb) -> Vec<T> {
+        if !is_finite(aabb) {
+            return Vec::new();
+        }
+
+        // large queries are cheaper to check against every item
+        let (min, max) = self.cell_range(aabb);
+        if cell_count(min, max) > self.cells.len() as i64 {
+            return self
+                .items
+                .iter()
+                .filter(|(_, item)| item.intersects(aabb))
+                .map(|(item, _)| *item)
+                .collect();
+        }
+
+        let mut found = self
+            .overflow
+            .iter()
+            .copied()
+            .filter(|idx| self.items[*idx].1.intersects(aabb))
+            .collect::<Vec<_>>();
+
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    if let Some(cell) = self.cells.get(&IVector3::new(x, y, z)) {
+                        found.extend(
+                            cell.iter()
+                                .filter(|idx| self.items[**idx].1.intersects(aabb)),
+                        );
+                    }
+                }
+            }
+        }
+
+        found.sort_unstable();
+        found.dedup();
+        found.into_iter().map(|idx| self.items[idx].0).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // unit box with its min corner at (x, y, z)
+    fn unit_box(x: f32, y: f32, z: f32) -> Aabb {
+        Aabb::new(
+            Vector3 { x, y, z },
+            Vector3 {
+                x: x + 1.0,
+                y: y + 1.0,
+                z: z + 1.0,
+            },
+        )
+    }
+
+    fn sorted(mut items: Vec<u32>) -> Vec<u32> {
+        items.sort_unstable();
+        items
+    }
+
+    #[test]
+    fn query_returns_only_overlapping_items() {
+        let mut grid = UniformGrid::new(4.0);
+        grid.insert(1, unit_box(0.5, 0.5, 0.5));
+        grid.insert(2, unit_box(2.0, 0.5, 0.5));
+        grid.insert(3, unit_box(20.0, 0.5, 0.5));
+        grid.insert(4, unit_box(-10.0, -10.0, -10.0));
+        assert_eq!(grid.len(), 4);
+
+        let found = grid.query(&Aabb::new(
+            Vector3::default(),
+            Vector3 {
+                x: 5.0,
+                y: 5.0,
+                z: 5.0,
+            },
+        ));
+        assert_eq!(sorted(found), vec![1, 2]);
+
+        assert_eq!(grid.query(&unit_box(19.5, 0.0, 0.0)), vec![3]);
+        assert!(grid.query(&unit_box(100.0, 0.0, 0.0)).is_empty());
+    }
+
+    #[test]
+    fn items_spanning_cells_are_returned_once() {
+        let mut grid = UniformGrid::new(1.0);
+        grid.insert(1, unit_box(0.5, 0.5, 0.5));
+
+        // overlaps all eight cells the item is in
+        let found = grid.query(&Aabb::new(
+            Vector3::default(),
+            Vector3 {
+                x: 2.0,
+                y: 2.0,
+                z: 2.0,
+            },
+        ));
+        assert_eq!(found, vec![1]);
+    }
+
+    #[test]
+    fn huge_and_non_finite_boxes() {
+        let mut grid = UniformGrid::new(1.0);
+        let huge = Aabb::new(
+            Vector3 {
+                x: -1.0e9,
+                y: -1.0e9,
+                z: -1.0e9,
+            },
+            Vector3 {
+                x: 1.0e9,
+                y: 1.0e9,
+                z: 1.0e9,
+            },
+        );
+        assert!(grid.insert(1, huge));
+
+        let infinite = Aabb::new(
+            Vector3::default(),
+            Vector3 {
+                x: f32::INFINITY,
+                y: 0.0,
+                z: 0.0,
+            },
+        );
+        assert!(!grid.insert(2, infinite));
+        assert_eq!(grid.len(), 1);
+
+        // overflowed items are still found
+        assert_eq!(grid.query(&unit_box(5.0, 5.0, 5.0)), vec![1]);
+        assert!(grid.query(&infinite).is_empty());
+    }
+}
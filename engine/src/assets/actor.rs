@@ -1,19 +1,70 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::component::ComponentAsset;
+use super::component::{deserialize_components, ComponentAsset};
 
-#[derive(Default, Serialize, Deserialize)]
-pub(crate) struct ActorAsset {
+// patches the fields of a prefab component, fields that aren't listed are inherited
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentOverride {
+    // component type name, as in the components list
+    #[serde(rename = "type")]
+    pub component_type: String,
+
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ActorAsset {
     pub id: Uuid,
 
-    // TODO: need to be able to override the prefab values
+    // disabled actors (and their children) aren't spawned
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
     #[serde(default)]
     pub prefab: Option<Uuid>,
 
-    #[serde(default)]
+    // on prefab instances these replace the prefab's component of the same type,
+    // or are added if the prefab doesn't have one
+    #[serde(default, deserialize_with = "deserialize_components")]
     pub components: Vec<Box<dyn ComponentAsset>>,
 
+    // only valid on prefab instances
+    #[serde(default)]
+    pub overrides: Vec<ComponentOverride>,
+
     #[serde(default)]
     pub children: Vec<ActorAsset>,
 }
+
+impl ActorAsset {
+    // the first unregistered component type on the actor or its children
+    pub(crate) fn unknown_component(&self) -> Option<&str> {
+        self.components
+            .iter()
+            .find_map(|component| component.unknown_type())
+            .or_else(|| {
+                self.children
+                    .iter()
+                    .find_map(|child| child.unknown_component())
+            })
+    }
+}
+
+impl Default for ActorAsset {
+    fn default() -> Self {
+        Self {
+            id: Uuid::default(),
+            enabled: true,
+            prefab: None,
+            components: Vec::new(),
+            overrides: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+}
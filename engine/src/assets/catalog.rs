@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use log::{debug, error, info, warn};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use core::fs::AssetSource;
+
+use super::mesh::MeshAsset;
+use super::prefab::PrefabAsset;
+use super::resource::{read_resource, Resource, ResourceFormat};
+use super::scene::SceneAsset;
+
+// just enough of a resource to catalog it
+#[derive(Deserialize)]
+struct ResourceId {
+    id: Uuid,
+}
+
+fn is_resource(filepath: &Path) -> bool {
+    // strip the format extension (camera.prefab.ron => camera.prefab)
+    let filepath = if ResourceFormat::from_path(filepath).is_some() {
+        match filepath.file_stem() {
+            Some(stem) => Path::new(stem),
+            None => return false,
+        }
+    } else {
+        filepath
+    };
+
+    match filepath.extension().and_then(|x| x.to_str()) {
+        Some(extension) => [
+            SceneAsset::EXTENSION,
+            PrefabAsset::EXTENSION,
+            MeshAsset::EXTENSION,
+        ]
+        .contains(&extension),
+        None => false,
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct AssetCatalog {
+    resources: HashMap<Uuid, PathBuf>,
+}
+
+impl AssetCatalog {
+    pub fn scan(source: &dyn AssetSource) -> anyhow::Result<Self> {
+        info!("Scanning assets...");
+
+        let mut catalog = Self::default();
+        for filepath in source.files()? {
+            if !is_resource(&filepath) {
+                continue;
+            }
+
+            let id = match read_resource::<ResourceId, _>(source, &filepath) {
+                Ok(resource) => resource.id,
+                Err(e) => {
+                    warn!("Unable to catalog {}: {}", filepath.display(), e);
+                    continue;
+                }
+            };
+
+            debug!("Cataloged {} => {}", id, filepath.display());
+            if let Some(existing) = catalog.resources.insert(id, filepath) {
+                warn!(
+                    "Duplicate resource id {} (replacing {})",
+                    id,
+                    existing.display()
+                );
+            }
+        }
+
+        info!("Cataloged {} resources", catalog.resources.len());
+
+        Ok(catalog)
+    }
+
+    // an empty catalog if the scan fails
+    pub fn scan_or_default(source: &dyn AssetSource) -> Self {
+        Self::scan(source).unwrap_or_else(|e| {
+            error!("Error scanning assets: {}", e);
+            Self::default()
+        })
+    }
+
+    pub fn resolve(&self, id: &Uuid) -> Option<&PathBuf> {
+        self.resources.get(id)
+    }
+}
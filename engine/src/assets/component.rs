@@ -1,2 +1,75 @@
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
 #[typetag::serde(tag = "type", content = "data")]
-pub(crate) trait ComponentAsset: std::fmt::Debug {}
+pub trait ComponentAsset: std::fmt::Debug {
+    // inserts a copy of the component into the entity being built
+    fn add_to<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a>;
+
+    fn is_transform(&self) -> bool {
+        false
+    }
+
+    // a default transform is added to actors missing one
+    fn requires_transform(&self) -> bool {
+        false
+    }
+
+    // the type name of a component that isn't registered, see UnknownComponentAsset
+    fn unknown_type(&self) -> Option<&str> {
+        None
+    }
+}
+
+// stands in for a component whose type isn't registered so the resource
+// can still be parsed and the type reported as AssetError::UnknownComponent
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct UnknownComponentAsset {
+    component: String,
+}
+
+#[typetag::serde]
+impl ComponentAsset for UnknownComponentAsset {
+    fn add_to<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a> {
+        builder
+    }
+
+    fn unknown_type(&self) -> Option<&str> {
+        Some(&self.component)
+    }
+}
+
+// unregistered types become an UnknownComponentAsset rather than an error
+pub(crate) fn component_from_value(
+    value: serde_json::Value,
+) -> serde_json::Result<Box<dyn ComponentAsset>> {
+    let component = value
+        .get("type")
+        .and_then(|t| t.as_str())
+        .map(str::to_owned);
+
+    serde_json::from_value::<Box<dyn ComponentAsset>>(value).or_else(|e| match component {
+        Some(component)
+            if e.to_string()
+                .starts_with(&format!("unknown variant `{}`", component)) =>
+        {
+            Ok(Box::new(UnknownComponentAsset { component }))
+        }
+        _ => Err(e),
+    })
+}
+
+// deserializes components one at a time so an unregistered type
+// doesn't fail the whole resource, see component_from_value()
+pub(crate) fn deserialize_components<'de, D>(
+    deserializer: D,
+) -> Result<Vec<Box<dyn ComponentAsset>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<serde_json::Value>::deserialize(deserializer)?
+        .into_iter()
+        .map(|value| component_from_value(value).map_err(de::Error::custom))
+        .collect()
+}
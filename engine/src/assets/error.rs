@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+// returned (wrapped in anyhow) by resource loading,
+// callers can downcast_ref::<AssetError>() to tell failures apart
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssetError {
+    NotFound(PathBuf),
+
+    // invalid UTF-8, or invalid JSON / TOML / RON for the resource type
+    Parse {
+        filepath: PathBuf,
+        message: String,
+    },
+
+    // a component type that isn't registered with the engine
+    UnknownComponent {
+        filepath: PathBuf,
+        component: String,
+    },
+
+    // the resource is newer than the engine supports
+    VersionMismatch {
+        filepath: PathBuf,
+        version: u32,
+        supported: u32,
+    },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetError::NotFound(filepath) => write!(f, "Asset not found: {}", filepath.display()),
+            AssetError::Parse { filepath, message } => {
+                write!(f, "Unable to parse {}: {}", filepath.display(), message)
+            }
+            AssetError::UnknownComponent {
+                filepath,
+                component,
+            } => write!(
+                f,
+                "Unknown component {} in {}",
+                component,
+                filepath.display()
+            ),
+            AssetError::VersionMismatch {
+                filepath,
+                version,
+                supported,
+            } => write!(
+                f,
+                "{} has version {}, newer than the supported version {}",
+                filepath.display(),
+                version,
+                supported
+            ),
+        }
+    }
+}
+
+impl Error for AssetError {}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::anyhow;
+use log::{debug, info};
+use uuid::Uuid;
+
+use core::fs::AssetSource;
+use core::math::Quaternion;
+
+use crate::components::{MeshComponent, TransformComponent};
+
+use super::actor::ActorAsset;
+use super::component::ComponentAsset;
+use super::mesh::MeshAsset;
+use super::scene::SceneAsset;
+
+pub(crate) const GLTF_EXTENSIONS: [&str; 2] = ["gltf", "glb"];
+
+#[derive(Default)]
+pub(crate) struct GltfImport {
+    pub scene: SceneAsset,
+    pub meshes: Vec<MeshAsset>,
+}
+
+struct GltfImporter {
+    buffers: Vec<gltf::buffer::Data>,
+
+    // gltf mesh index => imported mesh id
+    meshes: HashMap<usize, Uuid>,
+
+    import: GltfImport,
+}
+
+impl GltfImporter {
+    fn import_mesh(&mut self, mesh: gltf::Mesh) -> Uuid {
+        if let Some(id) = self.meshes.get(&mesh.index()) {
+            return *id;
+        }
+
+        let mut asset = MeshAsset {
+            id: Uuid::new_v4(),
+            ..Default::default()
+        };
+
+        // primitives are merged into a single mesh
+        for primitive in mesh.primitives() {
+            let buffers = &self.buffers;
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+            let offset = asset.vertices.len() as u32;
+            if let Some(positions) = reader.read_positions() {
+                asset.vertices.extend(positions);
+            }
+
+            if let Some(indices) = reader.read_indices() {
+                asset
+                    .indices
+                    .extend(indices.into_u32().map(|index| index + offset));
+            }
+        }
+
+        debug!(
+            "Imported mesh {} with {} vertices, {} indices",
+            asset.id,
+            asset.vertices.len(),
+            asset.indices.len()
+        );
+
+        let id = asset.id;
+        self.meshes.insert(mesh.index(), id);
+        self.import.meshes.push(asset);
+
+        id
+    }
+
+    fn import_node(&mut self, node: gltf::Node) -> ActorAsset {
+        let (translation, rotation, scale) = node.transform().decomposed();
+
+        let mut components: Vec<Box<dyn ComponentAsset>> = vec![Box::new(TransformComponent::new(
+            translation.into(),
+            Quaternion::new(rotation[0], rotation[1], rotation[2], rotation[3]),
+            scale.into(),
+        ))];
+
+        if let Some(mesh) = node.mesh() {
+            components.push(Box::new(MeshComponent {
+                mesh: self.import_mesh(mesh),
+            }));
+        }
+
+        ActorAsset {
+            id: Uuid::new_v4(),
+            enabled: true,
+            prefab: None,
+            components,
+            overrides: Vec::new(),
+            children: node.children().map(|x| self.import_node(x)).collect(),
+        }
+    }
+}
+
+// TODO: external buffer uris aren't resolved through the asset source
+pub(crate) fn import_gltf<P>(source: &dyn AssetSource, filepath: P) -> anyhow::Result<GltfImport>
+where
+    P: AsRef<Path>,
+{
+    let filepath = filepath.as_ref();
+    info!("Importing glTF from {}...", filepath.display());
+
+    // the file may be in a zip so it has to be read through the source,
+    // gltf::import_slice() is why this needs gltf 0.15
+    let (document, buffers, _) = gltf::import_slice(&source.read(filepath)?)?;
+
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .ok_or_else(|| anyhow!("glTF file has no scenes"))?;
+
+    let mut importer = GltfImporter {
+        buffers,
+        meshes: HashMap::new(),
+        import: GltfImport::default(),
+    };
+
+    importer.import.scene.id = Uuid::new_v4();
+    for node in scene.nodes() {
+        let actor = importer.import_node(node);
+        importer.import.scene.actors.push(actor);
+    }
+
+    Ok(importer.import)
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::resource::{default_version, Resource};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct MeshAsset {
+    pub id: Uuid,
+
+    #[serde(default = "default_version")]
+    pub version: u32,
+
+    #[serde(default)]
+    pub vertices: Vec<[f32; 3]>,
+
+    #[serde(default)]
+    pub indices: Vec<u32>,
+}
+
+impl Default for MeshAsset {
+    fn default() -> Self {
+        Self {
+            id: Uuid::default(),
+            version: Self::VERSION,
+            vertices: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+}
+
+impl Resource for MeshAsset {
+    const EXTENSION: &'static str = "mesh";
+    const VERSION: u32 = 1;
+
+    fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u32) {
+        self.version = version;
+    }
+}
@@ -1,11 +1,22 @@
 mod actor;
+mod catalog;
 mod component;
+mod error;
+mod gltf_import;
+mod mesh;
 mod prefab;
 mod resource;
 mod scene;
+mod scene_diff;
 
 pub(crate) use actor::*;
+pub(crate) use catalog::*;
 pub(crate) use component::*;
+pub use error::*;
+pub(crate) use gltf_import::*;
+pub(crate) use mesh::*;
 pub(crate) use prefab::*;
 pub use resource::*;
 pub(crate) use scene::*;
+pub use scene::{SceneAsset, SceneBuilder};
+pub use scene_diff::SceneDiff;
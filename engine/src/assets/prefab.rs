@@ -1,16 +1,378 @@
+use anyhow::{anyhow, bail};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use core::fs::AssetSource;
+
 use super::actor::ActorAsset;
-use super::resource::Resource;
+use super::catalog::AssetCatalog;
+use super::component::component_from_value;
+use super::resource::{default_version, load_resource, Resource};
 
 #[derive(Serialize, Deserialize)]
 pub(crate) struct PrefabAsset {
     pub id: Uuid,
 
+    #[serde(default = "default_version")]
+    pub version: u32,
+
     pub actor: ActorAsset,
 }
 
+// components serialize as { "type": ..., "data": ... }
+fn component_type(component: &serde_json::Value) -> anyhow::Result<&str> {
+    match component.get("type").and_then(|t| t.as_str()) {
+        Some(component_type) => Ok(component_type),
+        None => bail!("Component has no type"),
+    }
+}
+
+fn find_component(components: &[serde_json::Value], ty: &str) -> anyhow::Result<Option<usize>> {
+    for (idx, component) in components.iter().enumerate() {
+        if component_type(component)? == ty {
+            return Ok(Some(idx));
+        }
+    }
+
+    Ok(None)
+}
+
+// objects are merged key by key, anything else is replaced
+fn merge_value(base: &mut serde_json::Value, patch: &serde_json::Value) {
+    match (base, patch) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(patch)) => {
+            for (key, value) in patch.iter() {
+                match base.get_mut(key) {
+                    Some(base) => merge_value(base, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, patch) => *base = patch.clone(),
+    }
+}
+
+// every instance of a prefab would otherwise spawn its children with the same ids
+fn remap_child_ids(instance: Uuid, children: &mut [ActorAsset]) {
+    for child in children.iter_mut() {
+        child.id = Uuid::from_u128(instance.as_u128() ^ child.id.as_u128());
+        remap_child_ids(instance, &mut child.children);
+    }
+}
+
+impl PrefabAsset {
+    // the instance keeps its own id and enabled state, and its children follow the prefab's
+    // its components and overrides are applied over the prefab's components
+    pub fn instantiate(&self, instance: &ActorAsset) -> anyhow::Result<ActorAsset> {
+        let mut components = self
+            .actor
+            .components
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for component in instance.components.iter() {
+            let component = serde_json::to_value(component)?;
+            let ty = component_type(&component)?.to_owned();
+            match find_component(&components, &ty)? {
+                Some(idx) => components[idx] = component,
+                None => components.push(component),
+            }
+        }
+
+        for component_override in instance.overrides.iter() {
+            match find_component(&components, &component_override.component_type)? {
+                Some(idx) => match components[idx].get_mut("data") {
+                    Some(data) => merge_value(data, &component_override.data),
+                    None => bail!(
+                        "Actor {} overrides {} but the prefab {} component has no data",
+                        instance.id,
+                        component_override.component_type,
+                        self.id
+                    ),
+                },
+                None => bail!(
+                    "Actor {} overrides {} but prefab {} doesn't have one",
+                    instance.id,
+                    component_override.component_type,
+                    self.id
+                ),
+            }
+        }
+
+        let components = components
+            .into_iter()
+            .map(|component| {
+                component_from_value(component).map_err(|e| {
+                    anyhow!(
+                        "Actor {} has an invalid component after applying prefab {}: {}",
+                        instance.id,
+                        self.id,
+                        e
+                    )
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut children: Vec<ActorAsset> =
+            serde_json::from_value(serde_json::to_value(&self.actor.children)?)?;
+        remap_child_ids(instance.id, &mut children);
+        children.extend(serde_json::from_value::<Vec<ActorAsset>>(
+            serde_json::to_value(&instance.children)?,
+        )?);
+
+        Ok(ActorAsset {
+            id: instance.id,
+            enabled: instance.enabled,
+            prefab: None,
+            components,
+            overrides: Vec::new(),
+            children,
+        })
+    }
+}
+
+// expands an actor's prefab, any prefabs that prefab is built on,
+// and any prefab instances among the children
+pub(crate) fn load_prefab_instance(
+    source: &dyn AssetSource,
+    catalog: &AssetCatalog,
+    instance: &ActorAsset,
+) -> anyhow::Result<ActorAsset> {
+    expand_prefab(source, catalog, instance, &mut Vec::new())
+}
+
+// path is the chain of prefabs currently being expanded
+fn expand_prefab(
+    source: &dyn AssetSource,
+    catalog: &AssetCatalog,
+    instance: &ActorAsset,
+    path: &mut Vec<Uuid>,
+) -> anyhow::Result<ActorAsset> {
+    let id = match instance.prefab {
+        Some(id) => id,
+        None => bail!("Actor {} is not a prefab instance", instance.id),
+    };
+
+    if path.contains(&id) {
+        let cycle = path
+            .iter()
+            .chain(std::iter::once(&id))
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>();
+        bail!("Prefab cycle: {}", cycle.join(" -> "));
+    }
+
+    let filepath = match catalog.resolve(&id) {
+        Some(filepath) => filepath,
+        None => bail!("Prefab {} not found in the asset catalog", id),
+    };
+
+    let mut prefab = load_resource::<PrefabAsset, _>(source, filepath)?;
+    path.push(id);
+    if prefab.actor.prefab.is_some() {
+        prefab.actor = expand_prefab(source, catalog, &prefab.actor, path)?;
+    } else {
+        expand_children(source, catalog, &mut prefab.actor.children, path)?;
+    }
+    path.pop();
+
+    let mut actor = prefab.instantiate(instance)?;
+    expand_children(source, catalog, &mut actor.children, path)?;
+
+    Ok(actor)
+}
+
+// children that are prefab instances are replaced with their expansion
+fn expand_children(
+    source: &dyn AssetSource,
+    catalog: &AssetCatalog,
+    children: &mut [ActorAsset],
+    path: &mut Vec<Uuid>,
+) -> anyhow::Result<()> {
+    for child in children.iter_mut() {
+        if child.prefab.is_some() {
+            *child = expand_prefab(source, catalog, child, path)?;
+        } else {
+            expand_children(source, catalog, &mut child.children, path)?;
+        }
+    }
+
+    Ok(())
+}
+
 impl Resource for PrefabAsset {
     const EXTENSION: &'static str = "prefab";
+    const VERSION: u32 = 1;
+
+    fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u32) {
+        self.version = version;
+    }
+
+    fn unknown_component(&self) -> Option<&str> {
+        self.actor.unknown_component()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    use serde_json::json;
+
+    use super::*;
+
+    // prefab files by path
+    struct MemorySource(HashMap<PathBuf, String>);
+
+    impl AssetSource for MemorySource {
+        fn read(&self, filepath: &Path) -> anyhow::Result<Vec<u8>> {
+            match self.0.get(filepath) {
+                Some(data) => Ok(data.clone().into_bytes()),
+                None => bail!("Asset not found: {}", filepath.display()),
+            }
+        }
+
+        fn exists(&self, filepath: &Path) -> bool {
+            self.0.contains_key(filepath)
+        }
+
+        fn files(&self) -> anyhow::Result<Vec<PathBuf>> {
+            Ok(self.0.keys().cloned().collect())
+        }
+    }
+
+    fn source(prefabs: &[(&str, serde_json::Value)]) -> MemorySource {
+        MemorySource(
+            prefabs
+                .iter()
+                .map(|(name, prefab)| {
+                    (
+                        PathBuf::from(format!("{}.prefab.json", name)),
+                        prefab.to_string(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    fn instance(prefab: Uuid, overrides: serde_json::Value) -> ActorAsset {
+        serde_json::from_value(json!({
+            "id": Uuid::from_u128(100),
+            "prefab": prefab,
+            "overrides": overrides,
+        }))
+        .unwrap()
+    }
+
+    fn transform_data(actor: &ActorAsset) -> serde_json::Value {
+        let component = actor
+            .components
+            .iter()
+            .map(|component| serde_json::to_value(component).unwrap())
+            .find(|component| component["type"] == "TransformComponent")
+            .unwrap();
+        component["data"].clone()
+    }
+
+    #[test]
+    fn overrides_replace_only_the_fields_they_set() {
+        let prefab_id = Uuid::from_u128(1);
+        let source = source(&[(
+            "crate",
+            json!({
+                "id": prefab_id,
+                "actor": {
+                    "id": Uuid::from_u128(10),
+                    "components": [{
+                        "type": "TransformComponent",
+                        "data": {
+                            "position": { "x": 1.0, "y": 2.0, "z": 3.0 },
+                            "scale": { "x": 2.0, "y": 2.0, "z": 2.0 },
+                        },
+                    }],
+                },
+            }),
+        )]);
+        let catalog = AssetCatalog::scan(&source).unwrap();
+
+        let actor = load_prefab_instance(
+            &source,
+            &catalog,
+            &instance(
+                prefab_id,
+                json!([{
+                    "type": "TransformComponent",
+                    "data": { "position": { "x": 5.0 } },
+                }]),
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(actor.id, Uuid::from_u128(100));
+        assert!(actor.prefab.is_none());
+
+        let transform = transform_data(&actor);
+        assert_eq!(
+            transform["position"],
+            json!({ "x": 5.0, "y": 2.0, "z": 3.0 })
+        );
+        assert_eq!(transform["scale"], json!({ "x": 2.0, "y": 2.0, "z": 2.0 }));
+    }
+
+    #[test]
+    fn overriding_a_missing_component_is_an_error() {
+        let prefab_id = Uuid::from_u128(1);
+        let source = source(&[(
+            "empty",
+            json!({
+                "id": prefab_id,
+                "actor": { "id": Uuid::from_u128(10) },
+            }),
+        )]);
+        let catalog = AssetCatalog::scan(&source).unwrap();
+
+        let result = load_prefab_instance(
+            &source,
+            &catalog,
+            &instance(
+                prefab_id,
+                json!([{ "type": "TransformComponent", "data": {} }]),
+            ),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn prefab_cycle_is_reported_with_its_path() {
+        let a = Uuid::from_u128(1);
+        let b = Uuid::from_u128(2);
+        let source = source(&[
+            (
+                "a",
+                json!({ "id": a, "actor": { "id": Uuid::from_u128(10), "prefab": b } }),
+            ),
+            (
+                "b",
+                json!({ "id": b, "actor": { "id": Uuid::from_u128(20), "prefab": a } }),
+            ),
+        ]);
+        let catalog = AssetCatalog::scan(&source).unwrap();
+
+        let err = match load_prefab_instance(&source, &catalog, &instance(a, json!([]))) {
+            Ok(_) => panic!("Expanded a prefab cycle"),
+            Err(e) => e,
+        };
+        assert_eq!(
+            err.to_string(),
+            format!("Prefab cycle: {} -> {} -> {}", a, b, a)
+        );
+    }
 }
@@ -1,3 +1,152 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::bail;
+use log::info;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use core::fs::AssetSource;
+
+use super::error::AssetError;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ResourceFormat {
+    Json,
+    Toml,
+    Ron,
+}
+
+impl ResourceFormat {
+    // None if the extension isn't .json, .toml, or .ron, callers fall back to JSON
+    pub fn from_path<P>(filepath: P) -> Option<Self>
+    where
+        P: AsRef<Path>,
+    {
+        match filepath.as_ref().extension().and_then(|x| x.to_str()) {
+            Some("json") => Some(ResourceFormat::Json),
+            Some("toml") => Some(ResourceFormat::Toml),
+            Some("ron") => Some(ResourceFormat::Ron),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn default_version() -> u32 {
+    1
+}
+
 pub trait Resource {
     const EXTENSION: &'static str;
+
+    // the newest version of the resource format
+    const VERSION: u32;
+
+    fn version(&self) -> u32;
+
+    fn set_version(&mut self, version: u32);
+
+    // upgrades a resource loaded from an older version of the format
+    fn migrate(&mut self, _from_version: u32) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    // the first component type that isn't registered, if any
+    fn unknown_component(&self) -> Option<&str> {
+        None
+    }
+}
+
+// deserializes without any version handling
+pub(crate) fn read_resource<R, P>(source: &dyn AssetSource, filepath: P) -> anyhow::Result<R>
+where
+    R: DeserializeOwned,
+    P: AsRef<Path>,
+{
+    let filepath = filepath.as_ref();
+
+    if !source.exists(filepath) {
+        bail!(AssetError::NotFound(filepath.to_path_buf()));
+    }
+
+    let parse_error = |message: String| AssetError::Parse {
+        filepath: filepath.to_path_buf(),
+        message,
+    };
+
+    let data = String::from_utf8(source.read(filepath)?).map_err(|e| parse_error(e.to_string()))?;
+    Ok(
+        match ResourceFormat::from_path(filepath).unwrap_or(ResourceFormat::Json) {
+            ResourceFormat::Json => {
+                serde_json::from_str::<R>(&data).map_err(|e| parse_error(e.to_string()))?
+            }
+            ResourceFormat::Toml => {
+                toml::from_str::<R>(&data).map_err(|e| parse_error(e.to_string()))?
+            }
+            ResourceFormat::Ron => {
+                ron::de::from_str::<R>(&data).map_err(|e| parse_error(e.to_string()))?
+            }
+        },
+    )
+}
+
+pub(crate) fn load_resource<R, P>(source: &dyn AssetSource, filepath: P) -> anyhow::Result<R>
+where
+    R: Resource + DeserializeOwned,
+    P: AsRef<Path>,
+{
+    let filepath = filepath.as_ref();
+
+    let mut resource = read_resource::<R, _>(source, filepath)?;
+
+    if let Some(component) = resource.unknown_component() {
+        bail!(AssetError::UnknownComponent {
+            filepath: filepath.to_path_buf(),
+            component: component.to_owned(),
+        });
+    }
+
+    let version = resource.version();
+    if version > R::VERSION {
+        bail!(AssetError::VersionMismatch {
+            filepath: filepath.to_path_buf(),
+            version,
+            supported: R::VERSION,
+        });
+    }
+
+    if version < R::VERSION {
+        info!(
+            "Migrating {} from version {} to {}...",
+            filepath.display(),
+            version,
+            R::VERSION
+        );
+        resource.migrate(version)?;
+        resource.set_version(R::VERSION);
+    }
+
+    Ok(resource)
+}
+
+pub(crate) fn save_resource<R, P>(resource: &R, filepath: P) -> anyhow::Result<()>
+where
+    R: Resource + Serialize,
+    P: AsRef<Path>,
+{
+    let filepath = filepath.as_ref();
+
+    let data = match ResourceFormat::from_path(filepath).unwrap_or(ResourceFormat::Json) {
+        ResourceFormat::Json => serde_json::to_string_pretty(resource)?,
+        ResourceFormat::Toml => toml::to_string_pretty(resource)?,
+        ResourceFormat::Ron => {
+            ron::ser::to_string_pretty(resource, ron::ser::PrettyConfig::default())?
+        }
+    };
+
+    let mut file = File::create(filepath)?;
+    file.write_all(data.as_bytes())?;
+
+    Ok(())
 }
@@ -1,17 +1,209 @@
+use std::path::Path;
+
+use anyhow::bail;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use core::math::Color;
+
 use super::actor::ActorAsset;
-use super::resource::Resource;
+use super::catalog::AssetCatalog;
+use super::component::ComponentAsset;
+use super::resource::{default_version, save_resource, Resource};
+
+pub(crate) const DEFAULT_CLEAR_COLOR: Color = Color::BLUE;
+
+fn default_clear_color() -> Color {
+    DEFAULT_CLEAR_COLOR
+}
 
-#[derive(Default, Serialize, Deserialize)]
-pub(crate) struct SceneAsset {
+#[derive(Serialize, Deserialize)]
+pub struct SceneAsset {
     pub id: Uuid,
 
+    #[serde(default = "default_version")]
+    pub version: u32,
+
+    #[serde(default = "default_clear_color")]
+    pub clear_color: Color,
+
+    // cubemap face images in +X, -X, +Y, -Y, +Z, -Z order
+    #[serde(default)]
+    pub skybox: Vec<String>,
+
     #[serde(default)]
     pub actors: Vec<ActorAsset>,
 }
 
+impl Default for SceneAsset {
+    fn default() -> Self {
+        Self {
+            id: Uuid::default(),
+            version: Self::VERSION,
+            clear_color: DEFAULT_CLEAR_COLOR,
+            skybox: Vec::new(),
+            actors: Vec::new(),
+        }
+    }
+}
+
+impl SceneAsset {
+    // collects every problem rather than stopping at the first
+    pub(crate) fn validate(&self, catalog: &AssetCatalog) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.id.is_nil() {
+            errors.push("scene: id is nil".to_owned());
+        }
+
+        for (idx, actor) in self.actors.iter().enumerate() {
+            validate_actor(actor, &format!("actors[{}]", idx), catalog, &mut errors);
+        }
+
+        errors
+    }
+
+    // the format is picked from the extension, defaulting to JSON
+    pub fn save<P>(&self, filepath: P) -> anyhow::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        save_resource(self, filepath)
+    }
+}
+
+fn validate_actor(
+    actor: &ActorAsset,
+    context: &str,
+    catalog: &AssetCatalog,
+    errors: &mut Vec<String>,
+) {
+    if actor.id.is_nil() {
+        errors.push(format!("{}: actor id is nil", context));
+    }
+
+    match &actor.prefab {
+        Some(prefab) => {
+            if catalog.resolve(prefab).is_none() {
+                errors.push(format!(
+                    "{} ({}): prefab {} not found in the asset catalog",
+                    context, actor.id, prefab
+                ));
+            }
+        }
+        None => {
+            if !actor.overrides.is_empty() {
+                errors.push(format!(
+                    "{} ({}): overrides require a prefab",
+                    context, actor.id
+                ));
+            }
+
+            // prefab actors get their components from the prefab
+            if actor.components.is_empty() {
+                errors.push(format!(
+                    "{} ({}): actor has no components",
+                    context, actor.id
+                ));
+            }
+        }
+    }
+
+    for (idx, child) in actor.children.iter().enumerate() {
+        validate_actor(
+            child,
+            &format!("{}.children[{}]", context, idx),
+            catalog,
+            errors,
+        );
+    }
+}
+
+// components are added to the most recently added actor
+pub struct SceneBuilder {
+    scene: SceneAsset,
+
+    // reported by build() so calls can still be chained
+    error: Option<String>,
+}
+
+impl SceneBuilder {
+    pub fn new(id: Uuid) -> Self {
+        Self {
+            scene: SceneAsset {
+                id,
+                ..Default::default()
+            },
+            error: None,
+        }
+    }
+
+    pub fn clear_color(mut self, clear_color: Color) -> Self {
+        self.scene.clear_color = clear_color;
+        self
+    }
+
+    pub fn skybox(mut self, faces: Vec<String>) -> Self {
+        self.scene.skybox = faces;
+        self
+    }
+
+    pub fn add_actor(mut self, id: Uuid) -> Self {
+        self.scene.actors.push(ActorAsset {
+            id,
+            ..Default::default()
+        });
+        self
+    }
+
+    pub fn with_prefab(mut self, prefab: Uuid) -> Self {
+        if let Some(actor) = self.current_actor("with_prefab") {
+            actor.prefab = Some(prefab);
+        }
+        self
+    }
+
+    pub fn with_component<C>(mut self, component: C) -> Self
+    where
+        C: ComponentAsset + 'static,
+    {
+        if let Some(actor) = self.current_actor("with_component") {
+            actor.components.push(Box::new(component));
+        }
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<SceneAsset> {
+        if let Some(error) = self.error {
+            bail!("Unable to build scene {}: {}", self.scene.id, error);
+        }
+
+        Ok(self.scene)
+    }
+
+    fn current_actor(&mut self, method: &str) -> Option<&mut ActorAsset> {
+        let actor = self.scene.actors.last_mut();
+        if actor.is_none() && self.error.is_none() {
+            self.error = Some(format!("{}() called before add_actor()", method));
+        }
+        actor
+    }
+}
+
 impl Resource for SceneAsset {
     const EXTENSION: &'static str = "scene";
+    const VERSION: u32 = 1;
+
+    fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u32) {
+        self.version = version;
+    }
+
+    fn unknown_component(&self) -> Option<&str> {
+        self.actors
+            .iter()
+            .find_map(|actor| actor.unknown_component())
+    }
 }
@@ -0,0 +1,142 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::actor::ActorAsset;
+use super::scene::SceneAsset;
+
+// top level actor changes between two versions of a scene, keyed by actor id
+// children are part of their parent's content, so changing one modifies the parent
+// actors are stored serialized so diffs can be cloned, compared, and saved
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneDiff {
+    // in the order they appear in the new scene
+    #[serde(default)]
+    pub added: Vec<serde_json::Value>,
+
+    // in the order they appeared in the old scene
+    #[serde(default)]
+    pub removed: Vec<Uuid>,
+
+    // new content, in the order they appear in the new scene
+    #[serde(default)]
+    pub modified: Vec<serde_json::Value>,
+}
+
+fn actor_id(actor: &serde_json::Value) -> anyhow::Result<Uuid> {
+    match actor.get("id").and_then(|id| id.as_str()) {
+        Some(id) => Ok(Uuid::parse_str(id)?),
+        None => bail!("Actor diff entry has no id"),
+    }
+}
+
+impl SceneDiff {
+    // scene settings (clear color, skybox) aren't diffed
+    pub fn new(from: &SceneAsset, to: &SceneAsset) -> anyhow::Result<Self> {
+        let old = from
+            .actors
+            .iter()
+            .map(|actor| Ok((actor.id, serde_json::to_value(actor)?)))
+            .collect::<anyhow::Result<HashMap<_, _>>>()?;
+
+        let mut diff = Self::default();
+        let mut seen = HashSet::new();
+        for actor in to.actors.iter() {
+            seen.insert(actor.id);
+
+            let value = serde_json::to_value(actor)?;
+            match old.get(&actor.id) {
+                Some(old) if *old == value => (),
+                Some(_) => diff.modified.push(value),
+                None => diff.added.push(value),
+            }
+        }
+
+        diff.removed = from
+            .actors
+            .iter()
+            .map(|actor| actor.id)
+            .filter(|id| !seen.contains(id))
+            .collect();
+
+        Ok(diff)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+    // actor ids touched by both diffs, these can't be merged automatically
+    pub fn conflicts(&self, other: &SceneDiff) -> anyhow::Result<Vec<Uuid>> {
+        let ours = self.touched()?;
+        let mut conflicts = other
+            .touched()?
+            .into_iter()
+            .filter(|id| ours.contains(id))
+            .collect::<Vec<_>>();
+        conflicts.sort();
+
+        Ok(conflicts)
+    }
+
+    fn touched(&self) -> anyhow::Result<HashSet<Uuid>> {
+        let mut ids = self.removed.iter().copied().collect::<HashSet<_>>();
+        for actor in self.added.iter().chain(self.modified.iter()) {
+            ids.insert(actor_id(actor)?);
+        }
+
+        Ok(ids)
+    }
+
+    // fails without changing the scene if the diff doesn't match it
+    pub fn apply(&self, scene: &mut SceneAsset) -> anyhow::Result<()> {
+        let existing = scene
+            .actors
+            .iter()
+            .map(|actor| actor.id)
+            .collect::<HashSet<_>>();
+
+        for id in self.removed.iter() {
+            if !existing.contains(id) {
+                bail!("Removed actor {} not found in scene {}", id, scene.id);
+            }
+        }
+
+        let mut modified = HashMap::new();
+        for value in self.modified.iter() {
+            let id = actor_id(value)?;
+            if !existing.contains(&id) {
+                bail!("Modified actor {} not found in scene {}", id, scene.id);
+            }
+            modified.insert(id, serde_json::from_value::<ActorAsset>(value.clone())?);
+        }
+
+        let mut added = Vec::with_capacity(self.added.len());
+        for value in self.added.iter() {
+            let actor = serde_json::from_value::<ActorAsset>(value.clone())?;
+            if existing.contains(&actor.id) {
+                bail!(
+                    "Added actor {} already exists in scene {}",
+                    actor.id,
+                    scene.id
+                );
+            }
+            added.push(actor);
+        }
+
+        // modified actors keep their position in the scene
+        scene
+            .actors
+            .retain(|actor| !self.removed.contains(&actor.id));
+        for actor in scene.actors.iter_mut() {
+            if let Some(new) = modified.remove(&actor.id) {
+                *actor = new;
+            }
+        }
+        scene.actors.extend(added);
+
+        Ok(())
+    }
+}
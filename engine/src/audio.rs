@@ -0,0 +1,234 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{error, info};
+use rodio::source::UniformSourceIterator;
+use rodio::{Decoder, Device, DeviceTrait, Sink, Source};
+use specs::prelude::*;
+
+use core::fs::AssetSource;
+use core::math::{Vector, Vector3};
+
+use crate::components::{
+    AudioListenerComponent, AudioSourceComponent, DisabledComponent, TransformComponent,
+};
+
+//#region Spatialization
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct AudioMix {
+    // 0..1
+    pub volume: f32,
+
+    // -1 (left) .. 1 (right)
+    pub pan: f32,
+}
+
+impl Default for AudioMix {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            pan: 0.0,
+        }
+    }
+}
+
+pub(crate) fn spatialize(
+    listener: &AudioListenerComponent,
+    listener_transform: &TransformComponent,
+    source_position: Vector3,
+) -> AudioMix {
+    let offset = source_position - listener_transform.get_position();
+    let distance = offset.magnitude();
+
+    let pan = match offset.try_normalized() {
+        Some(direction) => direction.dot(&listener_transform.right()).clamp(-1.0, 1.0),
+        // on top of the listener
+        None => 0.0,
+    };
+
+    AudioMix {
+        volume: listener.attenuate(distance),
+        pan,
+    }
+}
+
+//#endregion
+
+// equal power stereo panning, pan is shared with the player as f32 bits
+struct PannedSource<S>
+where
+    S: Source<Item = f32>,
+{
+    input: S,
+    pan: Arc<AtomicU32>,
+    channel: u16,
+}
+
+impl<S> Iterator for PannedSource<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.input.next()?;
+
+        let pan = f32::from_bits(self.pan.load(Ordering::Relaxed));
+        let angle = (pan + 1.0) * std::f32::consts::FRAC_PI_4;
+        let gain = if self.channel == 0 {
+            angle.cos()
+        } else {
+            angle.sin()
+        };
+        self.channel = (self.channel + 1) % 2;
+
+        Some(sample * gain * std::f32::consts::SQRT_2)
+    }
+}
+
+impl<S> Source for PannedSource<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
+
+struct PlayingSource {
+    sink: Sink,
+    pan: Arc<AtomicU32>,
+}
+
+// plays audio sources through the default output device
+pub(crate) struct AudioPlayer {
+    device: Device,
+
+    playing: HashMap<Entity, PlayingSource>,
+
+    // sources that failed to load aren't retried
+    failed: HashSet<Entity>,
+}
+
+impl AudioPlayer {
+    // None if there's no output device
+    pub fn new() -> Option<Self> {
+        let device = rodio::default_output_device()?;
+        info!(
+            "Using audio device {}",
+            device.name().unwrap_or_else(|_| "unknown".to_owned())
+        );
+
+        Some(Self {
+            device,
+            playing: HashMap::new(),
+            failed: HashSet::new(),
+        })
+    }
+
+    // starts new sources, stops any that were removed or disabled,
+    // and mixes everything relative to the listener
+    pub fn update(&mut self, world: &World, source: &dyn AssetSource) {
+        let entities = world.entities();
+        let sources = world.read_storage::<AudioSourceComponent>();
+        let listeners = world.read_storage::<AudioListenerComponent>();
+        let transforms = world.read_storage::<TransformComponent>();
+        let disabled = world.read_storage::<DisabledComponent>();
+
+        let is_active = |entity: Entity| {
+            entities.is_alive(entity) && sources.contains(entity) && !disabled.contains(entity)
+        };
+
+        self.playing.retain(|entity, playing| {
+            if is_active(*entity) {
+                return true;
+            }
+
+            playing.sink.stop();
+            false
+        });
+        self.failed.retain(|entity| is_active(*entity));
+
+        let listener = (&listeners, &transforms, !&disabled)
+            .join()
+            .next()
+            .map(|(listener, transform, _)| (listener, transform));
+
+        for (entity, audio, transform, _) in
+            (&entities, &sources, transforms.maybe(), !&disabled).join()
+        {
+            let mix = match (listener, transform) {
+                (Some((listener, listener_transform)), Some(transform)) => {
+                    spatialize(listener, listener_transform, transform.get_position())
+                }
+                _ => AudioMix::default(),
+            };
+
+            if !self.playing.contains_key(&entity) {
+                if self.failed.contains(&entity) {
+                    continue;
+                }
+
+                match self.play(source, audio) {
+                    Ok(playing) => {
+                        self.playing.insert(entity, playing);
+                    }
+                    Err(e) => {
+                        error!("Error playing audio clip {}: {}", audio.clip, e);
+                        self.failed.insert(entity);
+                        continue;
+                    }
+                }
+            }
+
+            if let Some(playing) = self.playing.get(&entity) {
+                playing.sink.set_volume(audio.volume * mix.volume);
+                playing.pan.store(mix.pan.to_bits(), Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn play(
+        &self,
+        source: &dyn AssetSource,
+        audio: &AudioSourceComponent,
+    ) -> anyhow::Result<PlayingSource> {
+        let data = source.read(audio.get_clip_path())?;
+        let decoder = Decoder::new(Cursor::new(data))?;
+        let sample_rate = decoder.sample_rate();
+
+        let pan = Arc::new(AtomicU32::new(0.0f32.to_bits()));
+        let sink = Sink::new(&self.device);
+        if audio.looping {
+            sink.append(PannedSource {
+                input: UniformSourceIterator::new(decoder.repeat_infinite(), 2, sample_rate),
+                pan: pan.clone(),
+                channel: 0,
+            });
+        } else {
+            sink.append(PannedSource {
+                input: UniformSourceIterator::new(decoder, 2, sample_rate),
+                pan: pan.clone(),
+                channel: 0,
+            });
+        }
+
+        Ok(PlayingSource { sink, pan })
+    }
+}
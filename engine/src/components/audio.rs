@@ -0,0 +1,41 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use crate::assets::ComponentAsset;
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+// starts playing when the entity is created
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct AudioSourceComponent {
+    // relative to the asset root
+    pub clip: String,
+
+    // 0..1 linear gain
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+
+    #[serde(default)]
+    pub looping: bool,
+}
+
+impl AudioSourceComponent {
+    pub fn get_clip_path(&self) -> &Path {
+        Path::new(&self.clip)
+    }
+}
+
+#[typetag::serde]
+impl ComponentAsset for AudioSourceComponent {
+    fn add_to<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a> {
+        builder.with(self.clone())
+    }
+
+    fn requires_transform(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use crate::assets::ComponentAsset;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Attenuation {
+    // full volume at min_distance, silent at max_distance
+    Linear,
+
+    // min_distance / distance, clamped at max_distance
+    #[default]
+    Inverse,
+}
+
+fn default_min_distance() -> f32 {
+    1.0
+}
+
+fn default_max_distance() -> f32 {
+    100.0
+}
+
+// usually paired with the camera, the first enabled listener is used
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct AudioListenerComponent {
+    #[serde(default)]
+    pub attenuation: Attenuation,
+
+    #[serde(default = "default_min_distance")]
+    pub min_distance: f32,
+
+    #[serde(default = "default_max_distance")]
+    pub max_distance: f32,
+}
+
+impl Default for AudioListenerComponent {
+    fn default() -> Self {
+        Self {
+            attenuation: Attenuation::default(),
+            min_distance: default_min_distance(),
+            max_distance: default_max_distance(),
+        }
+    }
+}
+
+impl AudioListenerComponent {
+    // 0..1 gain at the given distance from the listener
+    pub fn attenuate(&self, distance: f32) -> f32 {
+        let min = self.min_distance.max(0.0);
+        let max = self.max_distance.max(min);
+        if distance <= min {
+            return 1.0;
+        }
+
+        match self.attenuation {
+            Attenuation::Linear => {
+                if max <= min {
+                    return 0.0;
+                }
+                1.0 - ((distance - min) / (max - min)).min(1.0)
+            }
+            Attenuation::Inverse => {
+                if min == 0.0 {
+                    return 0.0;
+                }
+                min / distance.min(max)
+            }
+        }
+    }
+}
+
+#[typetag::serde]
+impl ComponentAsset for AudioListenerComponent {
+    fn add_to<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a> {
+        builder.with(self.clone())
+    }
+
+    fn requires_transform(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use core::math::{Aabb, Matrix4, Vector3};
+
+use crate::assets::ComponentAsset;
+
+// local space axis-aligned bounding box
+#[derive(Component, Default, Debug, Clone, Serialize, Deserialize)]
+pub struct BoundsComponent {
+    pub min: Vector3,
+    pub max: Vector3,
+}
+
+impl BoundsComponent {
+    // the world space box enclosing all 8 transformed corners
+    pub fn transformed(&self, matrix: &Matrix4) -> Aabb {
+        let mut min = Vector3 {
+            x: f32::MAX,
+            y: f32::MAX,
+            z: f32::MAX,
+        };
+        let mut max = Vector3 {
+            x: f32::MIN,
+            y: f32::MIN,
+            z: f32::MIN,
+        };
+
+        for i in 0..8 {
+            let corner = matrix.transform_point(Vector3 {
+                x: if i & 1 == 0 { self.min.x } else { self.max.x },
+                y: if i & 2 == 0 { self.min.y } else { self.max.y },
+                z: if i & 4 == 0 { self.min.z } else { self.max.z },
+            });
+
+            min.x = min.x.min(corner.x);
+            min.y = min.y.min(corner.y);
+            min.z = min.z.min(corner.z);
+            max.x = max.x.max(corner.x);
+            max.y = max.y.max(corner.y);
+            max.z = max.z.max(corner.z);
+        }
+
+        Aabb::new(min, max)
+    }
+}
+
+#[typetag::serde]
+impl ComponentAsset for BoundsComponent {
+    fn add_to<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a> {
+        builder.with(self.clone())
+    }
+}
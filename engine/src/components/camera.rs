@@ -1,11 +1,133 @@
 use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 
+use core::math::Matrix4;
+
 use crate::assets::ComponentAsset;
 
-// TODO: requires a transform component
-#[derive(Component, Debug, Serialize, Deserialize)]
-pub struct CameraComponent {}
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Projection {
+    #[default]
+    Perspective,
+
+    // for 2D scenes
+    Orthographic,
+}
+
+fn default_fov() -> f32 {
+    60.0
+}
+
+fn default_ortho_size() -> f32 {
+    10.0
+}
+
+fn default_near() -> f32 {
+    0.1
+}
+
+fn default_far() -> f32 {
+    1000.0
+}
+
+// scenes add a default transform if the actor doesn't have one
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct CameraComponent {
+    #[serde(default)]
+    pub projection: Projection,
+
+    // vertical field of view in degrees
+    #[serde(default = "default_fov")]
+    pub fov: f32,
+
+    // vertical extent in world units for orthographic projection
+    #[serde(default = "default_ortho_size")]
+    pub ortho_size: f32,
+
+    #[serde(default = "default_near")]
+    pub near: f32,
+
+    #[serde(default = "default_far")]
+    pub far: f32,
+
+    // near maps to depth 1 and far to 0, needs a Reversed depth pipeline
+    #[serde(default)]
+    pub reversed_z: bool,
+}
+
+impl Default for CameraComponent {
+    fn default() -> Self {
+        Self {
+            projection: Projection::default(),
+            fov: default_fov(),
+            ortho_size: default_ortho_size(),
+            near: default_near(),
+            far: default_far(),
+            reversed_z: false,
+        }
+    }
+}
+
+impl CameraComponent {
+    // orthographic projections ignore reversed_z
+    pub fn is_depth_reversed(&self) -> bool {
+        self.reversed_z && self.projection == Projection::Perspective
+    }
+
+    pub fn projection_matrix(&self, aspect: f32) -> Matrix4 {
+        match self.projection {
+            Projection::Perspective if self.is_depth_reversed() => {
+                Matrix4::perspective_reversed(self.fov.to_radians(), aspect, self.near, self.far)
+            }
+            Projection::Perspective => {
+                Matrix4::perspective(self.fov.to_radians(), aspect, self.near, self.far)
+            }
+            Projection::Orthographic => {
+                let half_height = self.ortho_size / 2.0;
+                let half_width = half_height * aspect;
+                Matrix4::orthographic(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.near,
+                    self.far,
+                )
+            }
+        }
+    }
+}
 
 #[typetag::serde]
-impl ComponentAsset for CameraComponent {}
+impl ComponentAsset for CameraComponent {
+    fn add_to<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a> {
+        builder.with(self.clone())
+    }
+
+    fn requires_transform(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_perspective_cameras_reverse_depth() {
+        let camera = CameraComponent {
+            reversed_z: true,
+            ..Default::default()
+        };
+        assert!(camera.is_depth_reversed());
+
+        let camera = CameraComponent {
+            projection: Projection::Orthographic,
+            reversed_z: true,
+            ..Default::default()
+        };
+        assert!(!camera.is_depth_reversed());
+
+        assert!(!CameraComponent::default().is_depth_reversed());
+    }
+}
@@ -0,0 +1,6 @@
+use specs::prelude::*;
+
+// disabled entities are skipped by the simulation and rendering
+#[derive(Component, Default, Debug)]
+#[storage(NullStorage)]
+pub struct DisabledComponent;
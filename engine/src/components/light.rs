@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use core::math::{Color, Matrix4, Vector, Vector3};
+
+use crate::assets::ComponentAsset;
+
+fn default_direction() -> Vector3 {
+    Vector3 {
+        x: 0.0,
+        y: -1.0,
+        z: 0.0,
+    }
+}
+
+fn default_color() -> Color {
+    Color::WHITE
+}
+
+fn default_intensity() -> f32 {
+    1.0
+}
+
+fn default_shadow_bias() -> f32 {
+    0.005
+}
+
+fn default_shadow_extent() -> f32 {
+    50.0
+}
+
+// infinitely far away, only the direction matters
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct DirectionalLightComponent {
+    // the direction the light travels
+    #[serde(default = "default_direction")]
+    pub direction: Vector3,
+
+    #[serde(default = "default_color")]
+    pub color: Color,
+
+    #[serde(default = "default_intensity")]
+    pub intensity: f32,
+
+    // only the first shadow casting light is drawn into the shadow map
+    #[serde(default)]
+    pub casts_shadows: bool,
+
+    // depth offset when comparing against the shadow map,
+    // too small causes shadow acne, too large causes peter-panning
+    #[serde(default = "default_shadow_bias")]
+    pub shadow_bias: f32,
+
+    // world units covered by the shadow map, centered on the focus point
+    #[serde(default = "default_shadow_extent")]
+    pub shadow_extent: f32,
+}
+
+impl Default for DirectionalLightComponent {
+    fn default() -> Self {
+        Self {
+            direction: default_direction(),
+            color: default_color(),
+            intensity: default_intensity(),
+            casts_shadows: false,
+            shadow_bias: default_shadow_bias(),
+            shadow_extent: default_shadow_extent(),
+        }
+    }
+}
+
+impl DirectionalLightComponent {
+    // orthographic view from the light, looking at focus
+    pub fn shadow_view_projection(&self, focus: Vector3) -> Option<Matrix4> {
+        let direction = self.direction.try_normalized()?;
+
+        // any up that isn't parallel to the light
+        let up = if direction.y.abs() > 0.99 {
+            Vector3::back()
+        } else {
+            Vector3::up()
+        };
+
+        let half_extent = self.shadow_extent / 2.0;
+        let eye = focus - direction * half_extent;
+
+        let back = -direction;
+        let right = up.cross(&back).try_normalized()?;
+        let up = back.cross(&right);
+
+        let mut view = Matrix4::identity();
+        for (row, axis) in [right, up, back].iter().enumerate() {
+            view.m[row][0] = axis.x;
+            view.m[row][1] = axis.y;
+            view.m[row][2] = axis.z;
+            view.m[row][3] = -axis.dot(&eye);
+        }
+
+        let projection = Matrix4::orthographic(
+            -half_extent,
+            half_extent,
+            -half_extent,
+            half_extent,
+            0.0,
+            self.shadow_extent,
+        );
+
+        Some(projection * view)
+    }
+}
+
+#[typetag::serde]
+impl ComponentAsset for DirectionalLightComponent {
+    fn add_to<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a> {
+        builder.with(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shadow_focus_is_centered_in_the_map() {
+        let light = DirectionalLightComponent {
+            direction: Vector3 {
+                x: 1.0,
+                y: -1.0,
+                z: 0.0,
+            },
+            ..Default::default()
+        };
+
+        let focus = Vector3 {
+            x: 3.0,
+            y: 0.0,
+            z: -2.0,
+        };
+        let view_projection = light.shadow_view_projection(focus).unwrap();
+
+        let p = view_projection.transform_point_projected(focus);
+        assert!(p.x.abs() < 1e-4);
+        assert!(p.y.abs() < 1e-4);
+        assert!((p.z - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn shadows_need_a_direction() {
+        let light = DirectionalLightComponent {
+            direction: Vector3::default(),
+            ..Default::default()
+        };
+        assert!(light.shadow_view_projection(Vector3::default()).is_none());
+    }
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use core::math::Color;
+
+use crate::assets::ComponentAsset;
+
+fn default_base_color() -> Color {
+    Color::WHITE
+}
+
+fn default_roughness() -> f32 {
+    0.5
+}
+
+// meshes are drawn with the base color,
+// the texture and surface properties need uvs and lighting
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct MaterialComponent {
+    #[serde(default = "default_base_color")]
+    pub base_color: Color,
+
+    // relative to the asset root
+    #[serde(default)]
+    pub texture: Option<String>,
+
+    #[serde(default = "default_roughness")]
+    pub roughness: f32,
+
+    #[serde(default)]
+    pub metalness: f32,
+
+    // alpha blended and drawn back-to-front after the opaque meshes
+    #[serde(default)]
+    pub transparent: bool,
+}
+
+impl Default for MaterialComponent {
+    fn default() -> Self {
+        Self {
+            base_color: default_base_color(),
+            texture: None,
+            roughness: default_roughness(),
+            metalness: 0.0,
+            transparent: false,
+        }
+    }
+}
+
+#[typetag::serde]
+impl ComponentAsset for MaterialComponent {
+    fn add_to<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a> {
+        builder.with(self.clone())
+    }
+}
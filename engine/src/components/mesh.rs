@@ -1,12 +1,22 @@
 use serde::{Deserialize, Serialize};
 use specs::prelude::*;
+use uuid::Uuid;
 
 use crate::assets::ComponentAsset;
 
-#[derive(Component, Debug, Serialize, Deserialize)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct MeshComponent {
+    pub mesh: Uuid,
     //pub vertex_buffer: renderer::VertexBuffer,
 }
 
 #[typetag::serde]
-impl ComponentAsset for MeshComponent {}
+impl ComponentAsset for MeshComponent {
+    fn add_to<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a> {
+        builder.with(self.clone())
+    }
+
+    fn requires_transform(&self) -> bool {
+        true
+    }
+}
@@ -1,7 +1,49 @@
+mod audio;
+mod audio_listener;
+mod bounds;
 mod camera;
+mod disabled;
+mod light;
+mod material;
 mod mesh;
+mod name;
+mod particles;
+mod selected;
+mod sprite;
 mod transform;
+mod velocity;
 
+pub use audio::*;
+pub use audio_listener::*;
+pub use bounds::*;
 pub use camera::*;
+pub use disabled::*;
+pub use light::*;
+pub use material::*;
 pub use mesh::*;
+pub use name::*;
+pub use particles::*;
+pub use selected::*;
+pub use sprite::*;
 pub use transform::*;
+pub use velocity::*;
+
+use specs::prelude::*;
+
+// storages have to exist before any entities are loaded or queried
+pub(crate) fn register_components(world: &mut World) {
+    world.register::<AudioListenerComponent>();
+    world.register::<AudioSourceComponent>();
+    world.register::<BoundsComponent>();
+    world.register::<CameraComponent>();
+    world.register::<DirectionalLightComponent>();
+    world.register::<DisabledComponent>();
+    world.register::<MaterialComponent>();
+    world.register::<MeshComponent>();
+    world.register::<NameComponent>();
+    world.register::<ParticleEmitterComponent>();
+    world.register::<SelectedComponent>();
+    world.register::<SpriteComponent>();
+    world.register::<TransformComponent>();
+    world.register::<VelocityComponent>();
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use crate::assets::ComponentAsset;
+
+// not required to be unique
+#[derive(Component, Default, Debug, Clone, Serialize, Deserialize)]
+pub struct NameComponent(pub String);
+
+#[typetag::serde]
+impl ComponentAsset for NameComponent {
+    fn add_to<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a> {
+        builder.with(self.clone())
+    }
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use core::math::Vector3;
+
+use crate::assets::ComponentAsset;
+
+fn default_rate() -> f32 {
+    10.0
+}
+
+fn default_lifetime() -> f32 {
+    1.0
+}
+
+fn default_max_particles() -> usize {
+    1000
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Particle {
+    // world space
+    pub position: Vector3,
+    pub velocity: Vector3,
+    pub age: f32,
+}
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct ParticleEmitterComponent {
+    // particles per second
+    #[serde(default = "default_rate")]
+    pub rate: f32,
+
+    // seconds
+    #[serde(default = "default_lifetime")]
+    pub lifetime: f32,
+
+    #[serde(default)]
+    pub velocity: Vector3,
+
+    // each axis is randomized by +/- this much
+    #[serde(default)]
+    pub velocity_spread: Vector3,
+
+    #[serde(default = "default_max_particles")]
+    pub max_particles: usize,
+
+    #[serde(skip)]
+    pub(crate) particles: Vec<Particle>,
+
+    // fractional particles carried between frames
+    #[serde(skip)]
+    pub(crate) spawn_accumulator: f32,
+}
+
+impl Default for ParticleEmitterComponent {
+    fn default() -> Self {
+        Self {
+            rate: default_rate(),
+            lifetime: default_lifetime(),
+            velocity: Vector3::default(),
+            velocity_spread: Vector3::default(),
+            max_particles: default_max_particles(),
+            particles: Vec::new(),
+            spawn_accumulator: 0.0,
+        }
+    }
+}
+
+impl ParticleEmitterComponent {
+    pub fn get_particles(&self) -> &[Particle] {
+        &self.particles
+    }
+}
+
+#[typetag::serde]
+impl ComponentAsset for ParticleEmitterComponent {
+    fn add_to<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a> {
+        builder.with(self.clone())
+    }
+}
@@ -0,0 +1,6 @@
+use specs::prelude::*;
+
+// marks an entity as selected in the editor / viewer
+#[derive(Component, Default, Debug)]
+#[storage(NullStorage)]
+pub struct SelectedComponent;
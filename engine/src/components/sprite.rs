@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use core::math::Vector2;
+
+use crate::assets::ComponentAsset;
+
+fn default_size() -> Vector2 {
+    Vector2 { x: 1.0, y: 1.0 }
+}
+
+// a textured quad in the XY plane, centered on the transform,
+// drawn alpha blended after the meshes
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct SpriteComponent {
+    // relative to the asset root
+    pub texture: String,
+
+    // world units
+    #[serde(default = "default_size")]
+    pub size: Vector2,
+}
+
+#[typetag::serde]
+impl ComponentAsset for SpriteComponent {
+    fn add_to<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a> {
+        builder.with(self.clone())
+    }
+}
@@ -1,16 +1,267 @@
 use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 
-use core::math::{Quaternion, Vector3};
+use core::math::{Matrix4, Quaternion, Vector, Vector3};
 
 use crate::assets::ComponentAsset;
 
-#[derive(Component, Default, Debug, Serialize, Deserialize)]
+fn default_dirty() -> bool {
+    true
+}
+
+fn default_scale() -> Vector3 {
+    Vector3 {
+        x: 1.0,
+        y: 1.0,
+        z: 1.0,
+    }
+}
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct TransformComponent {
-    pub position: Vector3,
-    pub rotation: Quaternion,
-    pub scale: Vector3,
+    #[serde(default)]
+    position: Vector3,
+
+    #[serde(default)]
+    rotation: Quaternion,
+
+    #[serde(default = "default_scale")]
+    scale: Vector3,
+
+    // world matrix, valid when not dirty
+    #[serde(skip)]
+    world_matrix: Matrix4,
+
+    #[serde(skip, default = "default_dirty")]
+    dirty: bool,
+
+    // pose at the start of the current simulation tick
+    #[serde(skip)]
+    previous: Option<(Vector3, Quaternion, Vector3)>,
+}
+
+impl Default for TransformComponent {
+    fn default() -> Self {
+        Self::new(Vector3::default(), Quaternion::default(), default_scale())
+    }
+}
+
+impl TransformComponent {
+    pub fn new(position: Vector3, rotation: Quaternion, scale: Vector3) -> Self {
+        Self {
+            position,
+            rotation,
+            scale,
+            world_matrix: Matrix4::identity(),
+            dirty: true,
+            previous: None,
+        }
+    }
+
+    pub fn get_position(&self) -> Vector3 {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Vector3) {
+        self.position = position;
+        self.dirty = true;
+    }
+
+    pub fn translate(&mut self, offset: Vector3) {
+        self.set_position(self.position + offset);
+    }
+
+    pub fn get_rotation(&self) -> Quaternion {
+        self.rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: Quaternion) {
+        self.rotation = rotation;
+        self.dirty = true;
+    }
+
+    // -Z, matching the camera
+    pub fn forward(&self) -> Vector3 {
+        self.rotation.rotate_vector(Vector3::back())
+    }
+
+    pub fn right(&self) -> Vector3 {
+        self.rotation.rotate_vector(Vector3::right())
+    }
+
+    pub fn up(&self) -> Vector3 {
+        self.rotation.rotate_vector(Vector3::up())
+    }
+
+    // -Z faces the target, matching the camera, up is a hint
+    // leaves the rotation alone if there's no well defined direction
+    pub fn look_at(&mut self, target: Vector3, up: Vector3) {
+        let back = match (self.position - target).try_normalized() {
+            Some(back) => back,
+            None => return,
+        };
+
+        let right = match up.cross(&back).try_normalized() {
+            Some(right) => right,
+            None => return,
+        };
+        let up = back.cross(&right);
+
+        let mut rotation = Matrix4::identity();
+        for (col, axis) in [right, up, back].iter().enumerate() {
+            rotation.m[0][col] = axis.x;
+            rotation.m[1][col] = axis.y;
+            rotation.m[2][col] = axis.z;
+        }
+
+        self.set_rotation(Quaternion::from_matrix(&rotation));
+    }
+
+    pub fn get_scale(&self) -> Vector3 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: Vector3) {
+        self.scale = scale;
+        self.dirty = true;
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn compose(position: Vector3, rotation: Quaternion, scale: Vector3) -> Matrix4 {
+        Matrix4::from_translation(position) * rotation.to_matrix() * Matrix4::from_scale(scale)
+    }
+
+    fn compute_world_matrix(&self) -> Matrix4 {
+        Self::compose(self.position, self.rotation, self.scale)
+    }
+
+    // local to world space
+    pub fn transform_point(&self, local: Vector3) -> Vector3 {
+        let scaled = Vector3 {
+            x: local.x * self.scale.x,
+            y: local.y * self.scale.y,
+            z: local.z * self.scale.z,
+        };
+        self.rotation.rotate_vector(scaled) + self.position
+    }
+
+    // world to local space, axes with a zero scale collapse to 0
+    pub fn inverse_transform_point(&self, world: Vector3) -> Vector3 {
+        let unrotated = self
+            .rotation
+            .conjugate()
+            .rotate_vector(world - self.position);
+
+        let unscale = |v: f32, s: f32| if s == 0.0 { 0.0 } else { v / s };
+        Vector3 {
+            x: unscale(unrotated.x, self.scale.x),
+            y: unscale(unrotated.y, self.scale.y),
+            z: unscale(unrotated.z, self.scale.z),
+        }
+    }
+
+    // snapshots the current pose before the simulation advances it
+    pub fn store_previous(&mut self) {
+        self.previous = Some((self.position, self.rotation, self.scale));
+    }
+
+    // blends from the previous tick's pose (alpha = 0) to the current one (alpha = 1)
+    pub fn interpolated(&self, alpha: f32) -> Matrix4 {
+        let (position, rotation, scale) = match self.previous {
+            Some(previous) => previous,
+            None => return self.world_matrix(),
+        };
+
+        Self::compose(
+            position.lerp(self.position, alpha),
+            rotation.slerp(self.rotation, alpha),
+            scale.lerp(self.scale, alpha),
+        )
+    }
+
+    // TODO: include the parent transforms
+    // computed on the fly if the cache hasn't been updated yet
+    pub fn world_matrix(&self) -> Matrix4 {
+        if self.dirty {
+            self.compute_world_matrix()
+        } else {
+            self.world_matrix
+        }
+    }
+
+    // returns true if the matrix had to be recomputed
+    pub fn update_world_matrix(&mut self) -> bool {
+        if !self.dirty {
+            return false;
+        }
+
+        self.world_matrix = self.compute_world_matrix();
+        self.dirty = false;
+        true
+    }
 }
 
 #[typetag::serde]
-impl ComponentAsset for TransformComponent {}
+impl ComponentAsset for TransformComponent {
+    fn add_to<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a> {
+        builder.with(self.clone())
+    }
+
+    fn is_transform(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::math::ApproxEq;
+
+    // about +y
+    fn yaw(degrees: f32) -> Quaternion {
+        let half = degrees.to_radians() / 2.0;
+        Quaternion::new(0.0, half.sin(), 0.0, half.cos())
+    }
+
+    #[test]
+    fn interpolated_half_is_the_midpoint() {
+        let mut transform = TransformComponent::default();
+        transform.store_previous();
+
+        transform.set_position(Vector3 {
+            x: 2.0,
+            y: 4.0,
+            z: 0.0,
+        });
+        transform.set_rotation(yaw(90.0));
+
+        let midpoint = transform.interpolated(0.5);
+        assert!(midpoint.transform_point(Vector3::default()).approx_eq(
+            &Vector3 {
+                x: 1.0,
+                y: 2.0,
+                z: 0.0
+            },
+            1e-5
+        ));
+        assert!(midpoint
+            .transform_vector(Vector3::right())
+            .approx_eq(&yaw(45.0).rotate_vector(Vector3::right()), 1e-5));
+
+        assert!(transform
+            .interpolated(1.0)
+            .approx_eq(&transform.world_matrix(), 1e-5));
+    }
+
+    #[test]
+    fn interpolated_without_history_is_the_current_pose() {
+        let mut transform = TransformComponent::default();
+        transform.translate(Vector3::up());
+
+        assert_eq!(transform.interpolated(0.5), transform.world_matrix());
+    }
+}
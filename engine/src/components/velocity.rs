@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use core::math::Vector3;
+
+use crate::assets::ComponentAsset;
+
+// world units per second
+#[derive(Component, Default, Debug, Clone, Serialize, Deserialize)]
+pub struct VelocityComponent(pub Vector3);
+
+#[typetag::serde]
+impl ComponentAsset for VelocityComponent {
+    fn add_to<'a>(&self, builder: EntityBuilder<'a>) -> EntityBuilder<'a> {
+        builder.with(self.clone())
+    }
+}
@@ -1,8 +1,111 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
+use winit::dpi::{LogicalSize, PhysicalSize};
+use winit::{MouseButton, VirtualKeyCode};
+
+pub use renderer::RendererConfig;
+
+use crate::assets::{default_version, Resource};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct WindowConfig {
     pub width: u32,
     pub height: u32,
+
+    // 0 leaves the dimension unclamped
+    #[serde(default)]
+    pub min_width: u32,
+    #[serde(default)]
+    pub min_height: u32,
+
     pub fullscreen: bool,
+
+    // treat the sizes above as physical pixels rather than DPI-scaled logical pixels
+    #[serde(default)]
+    pub size_in_physical_pixels: bool,
+    // stop rendering while the window doesn't have focus
+    #[serde(default)]
+    pub pause_when_unfocused: bool,
+}
+
+impl WindowConfig {
+    // winit sizes windows in logical pixels, the swapchain is always physical pixels
+    pub fn to_logical_size(&self, width: u32, height: u32, hidpi_factor: f64) -> LogicalSize {
+        if self.size_in_physical_pixels {
+            PhysicalSize::new(width.into(), height.into()).to_logical(hidpi_factor)
+        } else {
+            LogicalSize::new(width.into(), height.into())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StatsConfig {
+    // seconds between frame stats logs, None disables them
+    pub log_interval: Option<u64>,
+
+    pub log_target: String,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            log_interval: Some(5),
+            log_target: "engine::stats".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+pub enum InputBinding {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+    GamepadButton(gilrs::Button),
+    GamepadAxis(gilrs::Axis),
+}
+
+// action name to the inputs that trigger it
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct InputBindings {
+    #[serde(default = "default_version")]
+    pub version: u32,
+
+    #[serde(default)]
+    pub actions: HashMap<String, Vec<InputBinding>>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            actions: HashMap::new(),
+        }
+    }
+}
+
+impl Resource for InputBindings {
+    const EXTENSION: &'static str = "bindings";
+    const VERSION: u32 = 1;
+
+    fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u32) {
+        self.version = version;
+    }
+}
+
+impl InputBindings {
+    pub fn bind<S>(&mut self, action: S, binding: InputBinding)
+    where
+        S: Into<String>,
+    {
+        self.actions.entry(action.into()).or_default().push(binding);
+    }
+
+    pub fn get_bindings(&self, action: &str) -> &[InputBinding] {
+        self.actions.get(action).map_or(&[], |b| b.as_slice())
+    }
 }
@@ -0,0 +1,121 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use log::{error, info};
+
+use core::fs::FileWatcher;
+
+// file modification times are checked at most this often
+pub(crate) const SHADER_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// rebuilds pipelines when their watched shader files change
+#[derive(Debug, Default)]
+pub(crate) struct ShaderReloader {
+    watcher: FileWatcher,
+    last_poll: Option<Instant>,
+}
+
+impl ShaderReloader {
+    pub fn watch<P>(&mut self, path: P)
+    where
+        P: Into<PathBuf>,
+    {
+        self.watcher.watch(path);
+    }
+
+    pub fn unwatch(&mut self, path: &Path) {
+        self.watcher.unwatch(path);
+    }
+
+    // calls rebuild once with every changed file,
+    // errors are logged and the changes aren't retried until the files change again
+    // returns true if the rebuild succeeded
+    pub fn poll<F>(&mut self, now: Instant, rebuild: F) -> bool
+    where
+        F: FnOnce(&[PathBuf]) -> anyhow::Result<()>,
+    {
+        if self.watcher.is_empty() {
+            return false;
+        }
+
+        if let Some(last_poll) = self.last_poll {
+            if now.duration_since(last_poll) < SHADER_POLL_INTERVAL {
+                return false;
+            }
+        }
+        self.last_poll = Some(now);
+
+        let changed = self.watcher.poll();
+        if changed.is_empty() {
+            return false;
+        }
+
+        for path in changed.iter() {
+            info!("Shader {} changed, rebuilding pipelines...", path.display());
+        }
+
+        match rebuild(&changed) {
+            Ok(()) => true,
+            Err(e) => {
+                error!("Error rebuilding pipelines, keeping the old ones: {}", e);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::bail;
+
+    use super::*;
+
+    // created after it's watched, mtimes are too coarse to rewrite reliably
+    fn missing_shader(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}.spv", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn changed_shader_triggers_rebuild() {
+        let path = missing_shader("changed_shader_triggers_rebuild");
+
+        let mut reloader = ShaderReloader::default();
+        reloader.watch(path.clone());
+
+        let start = Instant::now();
+        assert!(!reloader.poll(start, |_| panic!("Nothing changed")));
+
+        std::fs::write(&path, [0u8; 4]).unwrap();
+
+        // throttled
+        assert!(!reloader.poll(start, |_| panic!("Polled too soon")));
+
+        let mut rebuilt = Vec::new();
+        assert!(reloader.poll(start + SHADER_POLL_INTERVAL, |changed| {
+            rebuilt.extend_from_slice(changed);
+            Ok(())
+        }));
+        assert_eq!(rebuilt, vec![path.clone()]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn failed_rebuild_waits_for_the_next_change() {
+        let path = missing_shader("failed_rebuild_waits_for_the_next_change");
+
+        let mut reloader = ShaderReloader::default();
+        reloader.watch(path.clone());
+        std::fs::write(&path, [0u8; 4]).unwrap();
+
+        let start = Instant::now();
+        assert!(!reloader.poll(start, |_| bail!("Invalid SPIR-V")));
+        assert!(!reloader.poll(start + SHADER_POLL_INTERVAL, |_| panic!(
+            "Retried without a change"
+        )));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
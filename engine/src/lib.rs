@@ -1,35 +1,136 @@
 mod assets;
+mod audio;
 pub mod components;
 pub mod config;
+mod hot_reload;
+mod picking;
+pub mod resources;
 mod scene;
 pub mod systems;
+mod trace;
 
 #[macro_use]
 extern crate specs_derive;
 
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use anyhow::bail;
 use chrono::prelude::*;
 use imgui_winit_support::{HiDpiMode, WinitPlatform};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use specs::prelude::*;
+use uuid::Uuid;
 use winit::{Event, EventsLoop, Window};
 
+use core::fs::{default_asset_root, AssetSource, DirectorySource};
+use core::jobs::JobPool;
+use core::math::{Color, Matrix4, Vector2, Vector3};
+
+use assets::{load_resource, AssetCatalog};
+use audio::AudioPlayer;
+use components::{
+    CameraComponent, DirectionalLightComponent, ParticleEmitterComponent, SelectedComponent,
+    TransformComponent,
+};
+use resources::*;
 use scene::*;
 use systems::*;
+use trace::*;
 
+pub use assets::{AssetError, SceneAsset, SceneBuilder, SceneDiff};
+
+const PAUSED_SLEEP: std::time::Duration = std::time::Duration::from_millis(100);
+
+// fixed tick used by Engine::step()
+const STEP_DELTA_TIME: f32 = 1.0 / 60.0;
+
+const LOAD_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
+const SKYBOX_TRIANGLES: usize = 12;
+
+const SPRITE_TRIANGLES: usize = 2;
+
+// converts pixel scroll deltas (touchpads) to lines
+const SCROLL_PIXELS_PER_LINE: f32 = 16.0;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum RendererType {
+    // picks the best available backend
+    Auto,
+
     Vulkan,
+
+    // headless, records renderer calls for testing
+    Mock,
+}
+
+impl RendererType {
+    pub fn resolve(self) -> anyhow::Result<Self> {
+        Ok(match self {
+            RendererType::Auto => {
+                if renderer::VulkanRendererState::is_available() {
+                    RendererType::Vulkan
+                } else {
+                    bail!("No supported renderer available");
+                }
+            }
+            _ => self,
+        })
+    }
 }
 
 struct EngineStats {
+    config: config::StatsConfig,
+
     frame_count: u64,
     start_time: DateTime<Utc>,
     last_frame_start: DateTime<Utc>,
     last_fps_dump: DateTime<Utc>,
+
+    // counted by render_scene
+    draw_calls: usize,
+    triangles: usize,
+
+    trace: Option<FrameTrace>,
 }
 
 impl EngineStats {
+    // returns the delta time in seconds
+    fn begin_frame(&mut self) -> f32 {
+        let now = Utc::now();
+        let delta = now - self.last_frame_start;
+        self.last_frame_start = now;
+
+        delta.num_microseconds().unwrap_or(0) as f32 / 1_000_000.0
+    }
+
+    fn end_frame(&mut self) {
+        self.frame_count += 1;
+
+        if let Some(trace) = &mut self.trace {
+            let row = FrameTraceRow {
+                frame: self.frame_count,
+                frame_time_ms: (Utc::now() - self.last_frame_start)
+                    .num_microseconds()
+                    .unwrap_or(0) as f64
+                    / 1000.0,
+                draw_calls: self.draw_calls,
+                triangles: self.triangles,
+            };
+
+            if let Err(e) = trace.write_row(&row) {
+                error!("Error writing frame trace, disabling: {}", e);
+                self.trace = None;
+            }
+        }
+
+        self.draw_calls = 0;
+        self.triangles = 0;
+    }
+
     fn frame_time(&self) -> i64 {
         (Utc::now() - self.last_frame_start).num_milliseconds()
     }
@@ -43,22 +144,35 @@ impl EngineStats {
         }
     }
 
-    fn log_frame_stats(&mut self) {
+    fn log_frame_stats(&mut self, renderer: &renderer::Renderer) {
+        let log_interval = match self.config.log_interval {
+            Some(log_interval) => log_interval as i64,
+            None => return,
+        };
+
         let now = Utc::now();
-        if (now - self.last_fps_dump).num_seconds() < 5 {
+        if (now - self.last_fps_dump).num_seconds() < log_interval {
             return;
         }
 
         // TODO:  average FPS vs last frame time extrapolated
         // also print the frame time
+        let memory = renderer.memory_stats();
         debug!(
+            target: &self.config.log_target,
             "Render Stats:
 \tFrames: {}
 \tFrame Time: {}ms
-\tFPS: {}",
+\tFPS: {}
+\tBuffers: {} ({} KiB)
+\tImages: {} ({} KiB)",
             self.frame_count,
             self.frame_time(),
-            self.fps()
+            self.fps(),
+            memory.buffer_count,
+            memory.buffer_bytes / 1024,
+            memory.image_count,
+            memory.image_bytes / 1024
         );
         self.last_fps_dump = now;
     }
@@ -67,10 +181,17 @@ impl EngineStats {
 impl Default for EngineStats {
     fn default() -> Self {
         Self {
+            config: config::StatsConfig::default(),
+
             frame_count: 0,
             start_time: Utc::now(),
             last_frame_start: Utc::now(),
             last_fps_dump: Utc::now(),
+
+            draw_calls: 0,
+            triangles: 0,
+
+            trace: None,
         }
     }
 }
@@ -140,7 +261,17 @@ impl EngineDebug {
 pub struct Engine<'a> {
     quit: bool,
 
-    events_loop: EventsLoop,
+    pause_when_unfocused: bool,
+    paused: bool,
+
+    // systems don't dispatch, but events and rendering continue
+    simulation_paused: bool,
+    step_requested: bool,
+
+    // window, gamepad, and audio devices aren't opened for headless renderers
+    events_loop: Option<EventsLoop>,
+    gilrs: Option<gilrs::Gilrs>,
+    audio: Option<AudioPlayer>,
 
     dispatcher: AsyncDispatcher<'a, World>,
 
@@ -148,9 +279,48 @@ pub struct Engine<'a> {
     render_pass: renderer::RenderPass,
     frame_buffers: Vec<renderer::FrameBuffer>,
     render_pipeline: renderer::RenderPipeline,
+    transparent_pipeline: renderer::RenderPipeline,
+    debug_line_pipeline: renderer::RenderPipeline,
+    particle_pipeline: renderer::RenderPipeline,
+    skybox_pipeline: renderer::RenderPipeline,
+    sprite_pipeline: renderer::RenderPipeline,
+    text_pipeline: renderer::RenderPipeline,
+    tonemap_pipeline: renderer::RenderPipeline,
+    shadow_pipeline: renderer::RenderPipeline,
+
+    // independent of the swapchain, so these live as long as the renderer
+    shadow_render_pass: renderer::RenderPass,
+    shadow_map: renderer::ShadowMap,
+
+    // one per frame buffer
+    tonemap_descriptor_sets: Vec<renderer::DescriptorSet>,
+    skybox_vertex_buffer: renderer::VertexBuffer,
+    skybox: renderer::Texture,
+    sprite_vertex_buffer: renderer::VertexBuffer,
+
+    // by asset path, loaded the first time they're drawn
+    // failed loads are kept with no descriptor set so they aren't retried every frame
+    sprite_textures: HashMap<String, (renderer::Texture, renderer::DescriptorSet)>,
+    font: renderer::FontAtlas,
+    font_texture: renderer::Texture,
+    wireframe: bool,
+    tonemap: renderer::Tonemap,
+
+    // SPIR-V vertex / fragment shader files the particle pipeline is rebuilt from
+    simple_shader_spirv: Option<(PathBuf, PathBuf)>,
+    shader_reloader: hot_reload::ShaderReloader,
+
+    // from the scene camera when the pipelines are created
+    depth_mode: renderer::DepthMode,
     recreate_swapchain: bool,
 
+    asset_root: PathBuf,
+    asset_source: Arc<dyn AssetSource>,
+    // scanned the first time a scene is loaded
+    asset_catalog: Option<AssetCatalog>,
+    job_pool: JobPool,
     loaded_scenes: Vec<Scene>,
+    inject_default_components: bool,
 
     stats: EngineStats,
     debug: EngineDebug,
@@ -159,42 +329,122 @@ pub struct Engine<'a> {
 impl<'a> Engine<'a> {
     pub fn new<S>(
         appid: S,
+        app_version: [u16; 3],
         renderer_type: RendererType,
         window_config: &config::WindowConfig,
+        renderer_config: &config::RendererConfig,
     ) -> anyhow::Result<Self>
     where
         S: Into<String>,
     {
+        let appid: String = appid.into();
         info!("Initializing engine...");
 
-        let events_loop = EventsLoop::new();
-
-        let world = World::new();
-        let dispatcher = DispatcherBuilder::new()
+        let mut world = World::new();
+        world.insert(DebugLines::default());
+        world.insert(GizmoSettings::default());
+        world.insert(TextQueue::default());
+        world.insert(RenderQueue::default());
+        world.insert(DeltaTime::default());
+        world.insert(Random::default());
+        // TODO: set from the accumulator once the simulation runs on a fixed timestep
+        world.insert(InterpolationAlpha::default());
+        world.insert(InputState::default());
+        components::register_components(&mut world);
+
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(TransformHistorySystem, "transform_history", &[])
+            .with(MovementSystem, "movement", &["transform_history"])
+            .with(ParticleSystem, "particles", &["movement"])
+            .with(CollisionSystem, "collision", &["movement"])
             .with_thread_local(RenderSystem)
             .build_async(world);
+        dispatcher.setup();
+
+        let renderer_type = renderer_type.resolve()?;
+        info!("Using renderer {:?}", renderer_type);
 
-        let renderer = match renderer_type {
+        let (renderer, events_loop) = match renderer_type {
             RendererType::Vulkan => {
-                renderer::Renderer::Vulkan(renderer::VulkanRendererState::new(&events_loop)?)
+                let events_loop = EventsLoop::new();
+                let renderer =
+                    renderer::Renderer::Vulkan(Box::new(renderer::VulkanRendererState::new(
+                        &events_loop,
+                        &appid,
+                        app_version,
+                        renderer_config,
+                    )?));
+                (renderer, Some(events_loop))
             }
+            RendererType::Mock => (
+                renderer::Renderer::Mock(renderer::MockRendererState::new()),
+                None,
+            ),
+            _ => bail!("Renderer type {:?} not implemented", renderer_type),
         };
-
-        info!("Resizing window {:?}", window_config);
-        let window = renderer.get_window()?;
-        window.set_title(&appid.into());
-        window.set_inner_size(winit::dpi::LogicalSize::new(
-            window_config.width.into(),
-            window_config.height.into(),
-        ));
-        if window_config.fullscreen {
-            window.set_fullscreen(Some(window.get_current_monitor()));
+        let headless = renderer.is_headless();
+
+        let shadow_render_pass = renderer.create_shadow_render_pass()?;
+        let shadow_map = renderer.create_shadow_map(
+            &shadow_render_pass,
+            match renderer_config.shadow_map_size {
+                0 => renderer::DEFAULT_SHADOW_MAP_SIZE,
+                size => size,
+            },
+        )?;
+
+        if !headless {
+            info!("Resizing window {:?}", window_config);
+            let window = renderer.get_window()?;
+            window.set_title(&appid);
+            let hidpi_factor = window.get_hidpi_factor();
+            window.set_inner_size(window_config.to_logical_size(
+                window_config.width,
+                window_config.height,
+                hidpi_factor,
+            ));
+            if window_config.min_width > 0 || window_config.min_height > 0 {
+                window.set_min_dimensions(Some(window_config.to_logical_size(
+                    window_config.min_width,
+                    window_config.min_height,
+                    hidpi_factor,
+                )));
+            }
+            if window_config.fullscreen {
+                window.set_fullscreen(Some(window.get_current_monitor()));
+            }
         }
 
         let mut engine = Self {
             quit: false,
 
+            pause_when_unfocused: window_config.pause_when_unfocused,
+            paused: false,
+
+            simulation_paused: false,
+            step_requested: false,
+
             events_loop,
+            gilrs: if headless {
+                None
+            } else {
+                match gilrs::Gilrs::new() {
+                    Ok(gilrs) => Some(gilrs),
+                    Err(e) => {
+                        warn!("Gamepad support unavailable: {}", e);
+                        None
+                    }
+                }
+            },
+            audio: if headless {
+                None
+            } else {
+                let audio = AudioPlayer::new();
+                if audio.is_none() {
+                    warn!("Audio unavailable: no output device");
+                }
+                audio
+            },
 
             dispatcher,
 
@@ -202,29 +452,181 @@ impl<'a> Engine<'a> {
             render_pass: renderer::RenderPass::None,
             frame_buffers: Vec::new(),
             render_pipeline: renderer::RenderPipeline::None,
+            transparent_pipeline: renderer::RenderPipeline::None,
+            debug_line_pipeline: renderer::RenderPipeline::None,
+            particle_pipeline: renderer::RenderPipeline::None,
+            skybox_pipeline: renderer::RenderPipeline::None,
+            sprite_pipeline: renderer::RenderPipeline::None,
+            text_pipeline: renderer::RenderPipeline::None,
+            tonemap_pipeline: renderer::RenderPipeline::None,
+            shadow_pipeline: renderer::RenderPipeline::None,
+            shadow_render_pass,
+            shadow_map,
+            tonemap_descriptor_sets: Vec::new(),
+            skybox_vertex_buffer: renderer::VertexBuffer::None,
+            skybox: renderer::Texture::None,
+            sprite_vertex_buffer: renderer::VertexBuffer::None,
+            sprite_textures: HashMap::new(),
+            font: renderer::FontAtlas::default(),
+            font_texture: renderer::Texture::None,
+            wireframe: false,
+            tonemap: renderer::Tonemap::default(),
+
+            simple_shader_spirv: None,
+            shader_reloader: hot_reload::ShaderReloader::default(),
+            depth_mode: renderer::DepthMode::default(),
             recreate_swapchain: false,
 
+            asset_root: PathBuf::new(),
+            asset_source: Arc::new(DirectorySource::new(PathBuf::new())),
+            asset_catalog: None,
+            job_pool: JobPool::new(0)?,
             loaded_scenes: Vec::new(),
+            inject_default_components: true,
 
             stats: EngineStats::default(),
             debug: EngineDebug::default(),
         };
 
-        engine.debug.attach_window(engine.renderer.get_window()?);
+        engine.set_asset_root(default_asset_root()?);
+
+        if !engine.renderer.is_headless() {
+            engine.debug.attach_window(engine.renderer.get_window()?);
+        }
 
         Ok(engine)
     }
 
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn is_simulation_paused(&self) -> bool {
+        self.simulation_paused
+    }
+
+    pub fn pause(&mut self) {
+        info!("Pausing simulation...");
+        self.simulation_paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        info!("Resuming simulation...");
+        self.simulation_paused = false;
+        self.step_requested = false;
+    }
+
+    // advances a paused simulation by a single fixed tick
+    pub fn step(&mut self) {
+        if !self.simulation_paused {
+            warn!("Simulation is not paused, ignoring step");
+            return;
+        }
+        self.step_requested = true;
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.paused = !focused && self.pause_when_unfocused;
+        if self.paused {
+            info!("Lost focus, pausing...");
+        }
+    }
+
+    pub fn get_renderer(&self) -> &renderer::Renderer {
+        &self.renderer
+    }
+
+    pub fn set_stats_config(&mut self, config: config::StatsConfig) {
+        self.stats.config = config;
+    }
+
+    // writes per-frame timings as CSV, flushed periodically and on shutdown
+    pub fn enable_frame_trace<P>(&mut self, path: P) -> anyhow::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        self.stats.trace = Some(FrameTrace::create(path)?);
+
+        Ok(())
+    }
+
+    pub fn disable_frame_trace(&mut self) {
+        self.stats.trace = None;
+    }
+
+    pub fn get_asset_root(&self) -> &Path {
+        &self.asset_root
+    }
+
+    // relative asset paths are resolved against this
+    pub fn set_asset_root<P>(&mut self, asset_root: P)
+    where
+        P: Into<PathBuf>,
+    {
+        self.asset_root = asset_root.into();
+        info!("Asset root: {}", self.asset_root.display());
+
+        self.set_asset_source(Box::new(DirectorySource::new(self.asset_root.clone())));
+    }
+
+    // replaces the loose asset directory, eg. with a packed archive
+    pub fn set_asset_source(&mut self, asset_source: Box<dyn AssetSource>) {
+        self.asset_source = Arc::from(asset_source);
+
+        // rescanned the next time a scene is loaded
+        self.asset_catalog = None;
+    }
+
+    // adds a default transform to loaded actors with a camera or mesh but no transform
+    pub fn set_inject_default_components(&mut self, inject: bool) {
+        self.inject_default_components = inject;
+    }
+
     // TODO: return an ID for the scene
     pub fn load_scene<P>(&mut self, filepath: P) -> anyhow::Result<()>
     where
         P: AsRef<Path>,
     {
+        let source = self.asset_source.clone();
+        let catalog = self
+            .asset_catalog
+            .get_or_insert_with(|| AssetCatalog::scan_or_default(source.as_ref()));
+
         let mut scene = Scene::default();
-        scene.load(self.dispatcher.world_mut(), filepath)?;
-        self.loaded_scenes.push(scene);
+        scene.set_inject_default_components(self.inject_default_components);
+        let handle = scene.load_async(
+            self.dispatcher.world_mut(),
+            self.asset_source.clone(),
+            catalog,
+            filepath,
+            &self.job_pool,
+        )?;
+
+        // TODO: draw a loading bar instead of blocking
+        let mut last_loaded = 0;
+        while !handle.is_complete() {
+            let progress = handle.progress();
+            if progress.loaded != last_loaded {
+                info!(
+                    "Loaded {}/{} scene resources",
+                    progress.loaded, progress.total
+                );
+                last_loaded = progress.loaded;
+            }
+            std::thread::sleep(LOAD_PROGRESS_INTERVAL);
+        }
+
+        let faces = handle
+            .wait()?
+            .into_iter()
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if !faces.is_empty() {
+            self.skybox = self.renderer.create_cubemap(&faces)?;
+        }
 
-        self.dispatcher.setup();
+        scene.create_mesh_buffers(&self.renderer)?;
+
+        self.loaded_scenes.push(scene);
 
         /*self.scene.vertex_buffer = self.renderer.create_vertex_buffer(vec![
             renderer::Vertex {
@@ -238,45 +640,499 @@ impl<'a> Engine<'a> {
             },
         ])?;*/
 
+        if !self.renderer.is_headless() {
+            self.create_render_pipelines();
+        }
+
+        Ok(())
+    }
+
+    fn create_render_pipelines(&mut self) {
+        self.depth_mode = self.camera_depth_mode();
+
+        self.render_pass = self
+            .renderer
+            .create_simple_render_pass()
+            .unwrap_or_else(|e| panic!("Error creating render pass: {}", e));
+
+        let (render_pipeline, transparent_pipeline) = self
+            .create_scene_pipelines(self.wireframe)
+            .unwrap_or_else(|e| panic!("Error creating render pipeline: {}", e));
+        self.render_pipeline = render_pipeline;
+        self.transparent_pipeline = transparent_pipeline;
+
+        let (vs, fs) = self
+            .renderer
+            .load_line_shader()
+            .unwrap_or_else(|e| panic!("Error loading line shader: {}", e));
+
+        self.debug_line_pipeline = self
+            .renderer
+            .create_line_render_pipeline(&self.render_pass, vs, fs)
+            .unwrap_or_else(|e| panic!("Error creating debug line pipeline: {}", e));
+
         let (vs, fs) = self
             .renderer
             .load_simple_shader()
             .unwrap_or_else(|e| panic!("Error loading simple shader: {}", e));
 
-        self.render_pass = self
+        self.particle_pipeline = self
+            .create_particle_render_pipeline(vs, fs)
+            .unwrap_or_else(|e| panic!("Error creating particle pipeline: {}", e));
+
+        let (vs, fs) = self
             .renderer
-            .create_simple_render_pass()
-            .unwrap_or_else(|e| panic!("Error creating render pass: {}", e));
+            .load_skybox_shader()
+            .unwrap_or_else(|e| panic!("Error loading skybox shader: {}", e));
 
-        self.render_pipeline = self
+        self.skybox_pipeline = self
             .renderer
-            .create_simple_render_pipeline(&self.render_pass, vs, fs)
-            .unwrap_or_else(|e| panic!("Error creating render pipeline: {}", e));
+            .create_skybox_render_pipeline(&self.render_pass, vs, fs, self.depth_mode)
+            .unwrap_or_else(|e| panic!("Error creating skybox pipeline: {}", e));
+
+        let (vs, fs) = self
+            .renderer
+            .load_sprite_shader()
+            .unwrap_or_else(|e| panic!("Error loading sprite shader: {}", e));
+
+        self.sprite_pipeline = self
+            .renderer
+            .create_sprite_render_pipeline(&self.render_pass, vs, fs)
+            .unwrap_or_else(|e| panic!("Error creating sprite pipeline: {}", e));
+
+        // descriptor sets are tied to the old pipeline
+        self.sprite_textures.clear();
+
+        let (vs, fs) = self
+            .renderer
+            .load_text_shader()
+            .unwrap_or_else(|e| panic!("Error loading text shader: {}", e));
+
+        self.text_pipeline = self
+            .renderer
+            .create_text_render_pipeline(&self.render_pass, vs, fs)
+            .unwrap_or_else(|e| panic!("Error creating text pipeline: {}", e));
+
+        let (vs, fs) = self
+            .renderer
+            .load_shadow_shader()
+            .unwrap_or_else(|e| panic!("Error loading shadow shader: {}", e));
+
+        self.shadow_pipeline = self
+            .renderer
+            .create_shadow_render_pipeline(&self.shadow_render_pass, vs, fs)
+            .unwrap_or_else(|e| panic!("Error creating shadow pipeline: {}", e));
+
+        let (vs, fs) = self
+            .renderer
+            .load_tonemap_shader()
+            .unwrap_or_else(|e| panic!("Error loading tonemap shader: {}", e));
+
+        self.tonemap_pipeline = self
+            .renderer
+            .create_tonemap_render_pipeline(&self.render_pass, vs, fs)
+            .unwrap_or_else(|e| panic!("Error creating tonemap pipeline: {}", e));
+
+        self.skybox_vertex_buffer = self
+            .renderer
+            .create_skybox_vertex_buffer()
+            .unwrap_or_else(|e| panic!("Error creating skybox vertex buffer: {}", e));
+
+        self.sprite_vertex_buffer = self
+            .renderer
+            .create_sprite_vertex_buffer()
+            .unwrap_or_else(|e| panic!("Error creating sprite vertex buffer: {}", e));
 
         self.frame_buffers = self
             .renderer
             .create_frame_buffers(&self.render_pass)
             .unwrap_or_else(|e| panic!("Error creating frame buffers: {}", e));
 
+        self.tonemap_descriptor_sets = self
+            .renderer
+            .create_tonemap_descriptor_sets(&self.tonemap_pipeline, self.tonemap)
+            .unwrap_or_else(|e| panic!("Error creating tonemap descriptor sets: {}", e));
+
+        self.name_render_objects();
+    }
+
+    fn name_render_objects(&self) {
+        if !self.renderer.has_debug_names() {
+            return;
+        }
+
+        let pipelines = [
+            (&self.render_pipeline, "scene"),
+            (&self.transparent_pipeline, "transparent"),
+            (&self.debug_line_pipeline, "debug_line"),
+            (&self.particle_pipeline, "particle"),
+            (&self.skybox_pipeline, "skybox"),
+            (&self.sprite_pipeline, "sprite"),
+            (&self.text_pipeline, "text"),
+            (&self.tonemap_pipeline, "tonemap"),
+            (&self.shadow_pipeline, "shadow"),
+        ];
+        for (pipeline, name) in pipelines.iter() {
+            self.renderer.set_render_pipeline_name(pipeline, name);
+        }
+
+        self.renderer
+            .set_vertex_buffer_name(&self.skybox_vertex_buffer, "skybox");
+        self.renderer
+            .set_vertex_buffer_name(&self.sprite_vertex_buffer, "sprite");
+        self.renderer
+            .set_texture_name(&self.shadow_map.texture, "shadow_map");
+    }
+
+    // adds to the most recently loaded scene without unloading anything
+    pub fn load_scene_additive<P>(&mut self, filepath: P, group: &str) -> anyhow::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let scene = match self.loaded_scenes.last_mut() {
+            Some(scene) => scene,
+            None => bail!("No scene loaded to add to"),
+        };
+
+        let source = self.asset_source.clone();
+        let catalog = self
+            .asset_catalog
+            .get_or_insert_with(|| AssetCatalog::scan_or_default(source.as_ref()));
+
+        scene.load_additive(
+            self.dispatcher.world_mut(),
+            self.asset_source.as_ref(),
+            catalog,
+            filepath,
+            group,
+        )?;
+        scene.create_mesh_buffers(&self.renderer)?;
+
         Ok(())
     }
 
+    pub fn unload_scene_group(&mut self, group: &str) -> anyhow::Result<()> {
+        match self.loaded_scenes.last_mut() {
+            Some(scene) => scene.unload_group(self.dispatcher.world_mut(), group),
+            None => Ok(()),
+        }
+    }
+
+    pub fn get_scene_group(&self, group: &str) -> &[Entity] {
+        self.loaded_scenes
+            .last()
+            .map_or(&[], |scene| scene.get_group(group))
+    }
+
     // TODO: take in the ID of the scene to unload
     pub fn unload_scene(&mut self) {
-        // TODO: unload the scene
+        let mut scene = match self.loaded_scenes.pop() {
+            Some(scene) => scene,
+            None => return,
+        };
+
+        // pooled buffers may still be referenced by in-flight frames
+        if let Err(e) = self.renderer.wait_idle() {
+            error!("Error waiting for the renderer to go idle: {}", e);
+        }
+
+        if let Err(e) = scene.unload(self.dispatcher.world_mut(), &self.renderer) {
+            error!("Error unloading scene: {}", e);
+        }
+
+        self.renderer.set_scene_dirty();
+    }
+
+    // the entity spawned for the actor in the current scene
+    pub fn find_entity(&self, actor_id: &Uuid) -> Option<Entity> {
+        self.loaded_scenes.last()?.find(actor_id)
+    }
+
+    pub fn find_entity_by_name(&mut self, name: &str) -> Option<Entity> {
+        let scene = self.loaded_scenes.last()?;
+        scene.find_by_name(self.dispatcher.world(), name)
+    }
+
+    // disabled entities aren't simulated or rendered
+    pub fn set_entity_enabled(&mut self, entity: Entity, enabled: bool) -> anyhow::Result<()> {
+        match self.loaded_scenes.last() {
+            Some(scene) => scene.set_enabled(self.dispatcher.world_mut(), entity, enabled),
+            None => Ok(()),
+        }
+    }
+
+    pub fn scene_entity_count(&self) -> usize {
+        self.loaded_scenes.last().map_or(0, Scene::entity_count)
+    }
+
+    pub fn set_wireframe(&mut self, wireframe: bool) -> anyhow::Result<()> {
+        if self.wireframe == wireframe {
+            return Ok(());
+        }
+
+        let (render_pipeline, transparent_pipeline) = self.create_scene_pipelines(wireframe)?;
+        self.render_pipeline = render_pipeline;
+        self.transparent_pipeline = transparent_pipeline;
+        self.wireframe = wireframe;
+
+        Ok(())
+    }
+
+    pub fn set_tonemap(&mut self, tonemap: renderer::Tonemap) -> anyhow::Result<()> {
+        if self.tonemap == tonemap {
+            return Ok(());
+        }
+
+        if !self.renderer.is_headless() {
+            self.tonemap_descriptor_sets = self
+                .renderer
+                .create_tonemap_descriptor_sets(&self.tonemap_pipeline, tonemap)?;
+        }
+        self.tonemap = tonemap;
+
+        Ok(())
+    }
+
+    fn create_particle_render_pipeline(
+        &self,
+        vs: renderer::shaders::simple::vs::Shader,
+        fs: renderer::shaders::simple::fs::Shader,
+    ) -> anyhow::Result<renderer::RenderPipeline> {
+        self.renderer.create_render_pipeline(
+            &self.render_pass,
+            vs,
+            fs,
+            &renderer::PipelineConfig {
+                topology: renderer::PrimitiveTopology::PointList,
+                depth_mode: self.depth_mode,
+                ..Default::default()
+            },
+        )
+    }
+
+    //#region Shader hot reload
+
+    // rebuilds the particle pipeline when either SPIR-V file changes,
+    // the current pipeline is kept if the new shaders fail to load
+    pub fn watch_simple_shader<P>(&mut self, vs: P, fs: P)
+    where
+        P: Into<PathBuf>,
+    {
+        if let Some((vs, fs)) = self.simple_shader_spirv.take() {
+            self.shader_reloader.unwatch(&vs);
+            self.shader_reloader.unwatch(&fs);
+        }
+
+        let (vs, fs) = (vs.into(), fs.into());
+        info!(
+            "Watching simple shader {} / {}...",
+            vs.display(),
+            fs.display()
+        );
+
+        self.shader_reloader.watch(vs.clone());
+        self.shader_reloader.watch(fs.clone());
+        self.simple_shader_spirv = Some((vs, fs));
+    }
+
+    fn reload_simple_shader(&self) -> anyhow::Result<renderer::RenderPipeline> {
+        let (vs, fs) = match &self.simple_shader_spirv {
+            Some(spirv) => spirv,
+            None => bail!("No simple shader watched"),
+        };
+
+        let vs = std::fs::read(vs)?;
+        let fs = std::fs::read(fs)?;
+        let (vs, fs) = self.renderer.load_simple_shader_spirv(&vs, &fs)?;
+
+        self.create_particle_render_pipeline(vs, fs)
+    }
+
+    fn reload_shaders(&mut self) {
+        let mut shader_reloader = std::mem::take(&mut self.shader_reloader);
+
+        shader_reloader.poll(std::time::Instant::now(), |_| {
+            self.particle_pipeline = self.reload_simple_shader()?;
+            Ok(())
+        });
+
+        self.shader_reloader = shader_reloader;
+    }
+
+    //#endregion
+
+    // meshes are drawn instanced, returns the opaque and alpha blended pipelines
+    fn create_scene_pipelines(
+        &self,
+        wireframe: bool,
+    ) -> anyhow::Result<(renderer::RenderPipeline, renderer::RenderPipeline)> {
+        let config = renderer::PipelineConfig {
+            polygon_mode: if wireframe {
+                renderer::PolygonMode::Line
+            } else {
+                renderer::PolygonMode::Fill
+            },
+            depth_mode: self.depth_mode,
+            ..Default::default()
+        };
+
+        let (vs, fs) = self.renderer.load_instanced_shader()?;
+        let opaque =
+            self.renderer
+                .create_instanced_render_pipeline(&self.render_pass, vs, fs, &config)?;
+
+        let (vs, fs) = self.renderer.load_instanced_shader()?;
+        let transparent = self.renderer.create_instanced_render_pipeline(
+            &self.render_pass,
+            vs,
+            fs,
+            &renderer::PipelineConfig {
+                blend_mode: renderer::BlendMode::Alpha,
+                ..config
+            },
+        )?;
+
+        Ok((opaque, transparent))
+    }
+
+    // lines are drawn on top of the scene for the current frame only
+    pub fn draw_debug_line(&mut self, a: Vector3, b: Vector3) {
+        self.draw_debug_line_colored(a, b, DEBUG_LINE_COLOR);
+    }
+
+    pub fn draw_debug_line_colored(&mut self, a: Vector3, b: Vector3, color: [f32; 4]) {
+        self.dispatcher
+            .world_mut()
+            .write_resource::<DebugLines>()
+            .add_line(a, b, color);
+    }
+
+    // HUD text isn't drawn until a font atlas is loaded
+    pub fn load_font_atlas<P>(
+        &mut self,
+        filepath: P,
+        font: renderer::FontAtlas,
+    ) -> anyhow::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let data = self.asset_source.read(filepath.as_ref())?;
+        self.font_texture = self.renderer.create_texture(&data)?;
+        self.font = font;
+
+        self.renderer.set_texture_name(&self.font_texture, "font");
+
+        Ok(())
+    }
+
+    // HUD text, drawn for the current frame only
+    pub fn draw_text<S>(&mut self, text: S, position: Vector2, size: f32, color: Color)
+    where
+        S: Into<String>,
+    {
+        self.dispatcher
+            .world_mut()
+            .write_resource::<TextQueue>()
+            .add_text(QueuedText {
+                text: text.into(),
+                position,
+                size,
+                color,
+            });
+    }
+
+    pub fn set_input_bindings(&mut self, bindings: config::InputBindings) {
+        self.dispatcher
+            .world_mut()
+            .write_resource::<InputState>()
+            .set_bindings(bindings);
+    }
+
+    // replaces the input bindings with ones loaded from the asset source
+    pub fn load_input_bindings<P>(&mut self, filepath: P) -> anyhow::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let bindings =
+            load_resource::<config::InputBindings, _>(self.asset_source.as_ref(), filepath)?;
+        self.set_input_bindings(bindings);
+
+        Ok(())
+    }
+
+    // reseeds the shared RNG so runs are reproducible
+    pub fn set_seed(&mut self, seed: u64) {
+        info!("Using random seed {}", seed);
+        self.dispatcher
+            .world_mut()
+            .write_resource::<Random>()
+            .set_seed(seed);
+    }
+
+    // draws the transform axes of selected entities
+    pub fn show_gizmos(&mut self, show: bool) {
+        self.dispatcher
+            .world_mut()
+            .write_resource::<GizmoSettings>()
+            .enabled = show;
+    }
+
+    pub fn select_entity(&mut self, entity: Entity, selected: bool) -> anyhow::Result<()> {
+        let world = self.dispatcher.world_mut();
+        let mut storage = world.write_storage::<SelectedComponent>();
+        if selected {
+            storage.insert(entity, SelectedComponent)?;
+        } else {
+            storage.remove(entity);
+        }
+
+        Ok(())
+    }
+
+    // screen coordinates are logical pixels from the window's top-left
+    pub fn pick(&mut self, screen_x: f64, screen_y: f64) -> Option<Entity> {
+        if self.renderer.is_headless() {
+            return None;
+        }
+
+        let size = self.renderer.get_window().ok()?.get_inner_size()?;
+        if size.width <= 0.0 || size.height <= 0.0 {
+            return None;
+        }
+
+        let ndc = [
+            (2.0 * screen_x / size.width - 1.0) as f32,
+            (2.0 * screen_y / size.height - 1.0) as f32,
+        ];
+        let aspect = (size.width / size.height) as f32;
+
+        picking::pick(self.dispatcher.world_mut(), ndc, aspect)
     }
 
     pub fn run(&mut self) -> anyhow::Result<()> {
         info!("Running...");
 
         loop {
-            self.stats.last_frame_start = Utc::now();
-
-            self.dispatcher.dispatch();
+            let delta = self.stats.begin_frame();
+            self.update(delta);
 
             self.renderer.begin_frame();
 
             self.handle_events()?;
+            self.poll_gamepads();
+            self.update_audio();
+
+            if self.paused || self.renderer.is_minimized() {
+                if self.quit {
+                    break;
+                }
+
+                // nothing to present to until the window is restored / focused
+                std::thread::sleep(PAUSED_SLEEP);
+                continue;
+            }
+
+            self.reload_shaders();
 
             if self.recreate_swapchain {
                 if !self.renderer.recreate_swapchain()? {
@@ -284,6 +1140,9 @@ impl<'a> Engine<'a> {
                 }
 
                 self.frame_buffers = self.renderer.create_frame_buffers(&self.render_pass)?;
+                self.tonemap_descriptor_sets = self
+                    .renderer
+                    .create_tonemap_descriptor_sets(&self.tonemap_pipeline, self.tonemap)?;
 
                 self.recreate_swapchain = false;
             }
@@ -291,7 +1150,16 @@ impl<'a> Engine<'a> {
             /*self.debug.prepare_frame(self.renderer.get_window()?);
             let _ui = self.debug.imgui.frame();*/
 
-            self.render_scene()?;
+            if let Err(e) = self.render_scene() {
+                match e.downcast_ref::<renderer::RendererError>() {
+                    // TODO: attempt to reinitialize the renderer instead
+                    Some(renderer::RendererError::DeviceLost) => {
+                        error!("Renderer device lost, shutting down...");
+                        self.quit = true;
+                    }
+                    None => return Err(e),
+                }
+            }
 
             /*self.debug.prepare_render(&ui, self.renderer.get_window()?);
 
@@ -301,8 +1169,8 @@ impl<'a> Engine<'a> {
 
             self.dispatcher.world_mut().maintain();
 
-            self.stats.frame_count += 1;
-            self.stats.log_frame_stats();
+            self.stats.end_frame();
+            self.stats.log_frame_stats(&self.renderer);
 
             if self.quit {
                 break;
@@ -312,14 +1180,43 @@ impl<'a> Engine<'a> {
         Ok(())
     }
 
+    // debug drawing runs even while the simulation is paused,
+    // the renderer consumes the debug lines every frame
+    fn update(&mut self, delta: f32) {
+        if !self.simulation_paused || self.step_requested {
+            let delta = if self.step_requested {
+                STEP_DELTA_TIME
+            } else {
+                delta
+            };
+            self.step_requested = false;
+
+            *self.dispatcher.world_mut().write_resource::<DeltaTime>() = DeltaTime(delta);
+            self.dispatcher.dispatch();
+        }
+
+        GizmoSystem.run_now(self.dispatcher.world_mut());
+    }
+
     fn handle_events(&mut self) -> anyhow::Result<()> {
+        let events_loop = match &mut self.events_loop {
+            Some(events_loop) => events_loop,
+            None => return Ok(()),
+        };
+
         let window = self.renderer.get_window()?;
         let debug = &mut self.debug;
 
         let mut quit = false;
         let mut recreate_swapchain = false;
-
-        self.events_loop.poll_events(|event| {
+        let mut focused = None;
+        let mut keys = Vec::new();
+        let mut mouse_buttons = Vec::new();
+        let mut cursor_position = None;
+        let mut mouse_motion = Vector2::default();
+        let mut scroll = Vector2::default();
+
+        events_loop.poll_events(|event| {
             debug.handle_event(window, &event);
 
             match event {
@@ -331,27 +1228,739 @@ impl<'a> Engine<'a> {
                     event: winit::WindowEvent::Resized(_),
                     ..
                 } => recreate_swapchain = true,
+                winit::Event::WindowEvent {
+                    event: winit::WindowEvent::Focused(f),
+                    ..
+                } => focused = Some(f),
+                winit::Event::WindowEvent {
+                    event:
+                        winit::WindowEvent::KeyboardInput {
+                            input:
+                                winit::KeyboardInput {
+                                    state,
+                                    virtual_keycode: Some(key),
+                                    ..
+                                },
+                            ..
+                        },
+                    ..
+                } => keys.push((key, state)),
+                winit::Event::WindowEvent {
+                    event: winit::WindowEvent::MouseInput { state, button, .. },
+                    ..
+                } => mouse_buttons.push((button, state)),
+                winit::Event::WindowEvent {
+                    event: winit::WindowEvent::CursorMoved { position, .. },
+                    ..
+                } => cursor_position = Some(position),
+                winit::Event::WindowEvent {
+                    event: winit::WindowEvent::MouseWheel { delta, .. },
+                    ..
+                } => {
+                    scroll += match delta {
+                        winit::MouseScrollDelta::LineDelta(x, y) => Vector2 { x, y },
+                        winit::MouseScrollDelta::PixelDelta(p) => Vector2 {
+                            x: p.x as f32 / SCROLL_PIXELS_PER_LINE,
+                            y: p.y as f32 / SCROLL_PIXELS_PER_LINE,
+                        },
+                    }
+                }
+                winit::Event::DeviceEvent {
+                    event: winit::DeviceEvent::MouseMotion { delta },
+                    ..
+                } => {
+                    mouse_motion += Vector2 {
+                        x: delta.0 as f32,
+                        y: delta.1 as f32,
+                    }
+                }
                 _ => (),
             }
         });
 
+        {
+            let mut input = self.dispatcher.world_mut().write_resource::<InputState>();
+            input.begin_frame();
+            if focused == Some(false) {
+                input.clear();
+            }
+            if let Some(position) = cursor_position {
+                input.set_cursor_position(Vector2 {
+                    x: position.x as f32,
+                    y: position.y as f32,
+                });
+            }
+            input.add_mouse_motion(mouse_motion);
+            input.add_scroll(scroll);
+            for (key, state) in keys {
+                input.set_key(key, state);
+            }
+            for (button, state) in mouse_buttons {
+                input.set_mouse_button(button, state);
+            }
+        }
+
         self.quit = quit;
-        self.recreate_swapchain = recreate_swapchain;
+        self.recreate_swapchain |= recreate_swapchain;
+        if let Some(focused) = focused {
+            self.set_focused(focused);
+        }
 
         Ok(())
     }
 
+    fn poll_gamepads(&mut self) {
+        let gilrs = match &mut self.gilrs {
+            Some(gilrs) => gilrs,
+            None => return,
+        };
+
+        let mut input = self.dispatcher.world_mut().write_resource::<InputState>();
+        while let Some(gilrs::Event { id, event, .. }) = gilrs.next_event() {
+            match event {
+                gilrs::EventType::ButtonPressed(button, _) => {
+                    input.set_gamepad_button(id.into(), button, true)
+                }
+                gilrs::EventType::ButtonReleased(button, _) => {
+                    input.set_gamepad_button(id.into(), button, false)
+                }
+                gilrs::EventType::AxisChanged(axis, value, _) => {
+                    input.set_gamepad_axis(id.into(), axis, value)
+                }
+                gilrs::EventType::Connected => info!("Gamepad {} connected", id),
+                gilrs::EventType::Disconnected => {
+                    info!("Gamepad {} disconnected", id);
+                    input.clear_gamepad(id.into());
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn update_audio(&mut self) {
+        if let Some(audio) = &mut self.audio {
+            audio.update(self.dispatcher.world_mut(), self.asset_source.as_ref());
+        }
+    }
+
+    // reversed-Z cameras need the Greater depth test and a 0 depth clear
+    fn camera_depth_mode(&mut self) -> renderer::DepthMode {
+        let world = self.dispatcher.world_mut();
+        let cameras = world.read_storage::<CameraComponent>();
+        match (&cameras).join().next() {
+            Some(camera) if camera.is_depth_reversed() => renderer::DepthMode::Reversed,
+            _ => renderer::DepthMode::Standard,
+        }
+    }
+
+    // rotation only keeps the skybox centered on the camera
+    fn camera_view_projection(&mut self, rotation_only: bool) -> anyhow::Result<Option<Matrix4>> {
+        if self.renderer.is_headless() {
+            return Ok(None);
+        }
+
+        let size = match self.renderer.get_window()?.get_inner_size() {
+            Some(size) if size.height > 0.0 => size,
+            _ => return Ok(None),
+        };
+
+        let world = self.dispatcher.world_mut();
+        let cameras = world.read_storage::<CameraComponent>();
+        let transforms = world.read_storage::<TransformComponent>();
+        let (camera, transform) = match (&cameras, &transforms).join().next() {
+            Some(camera) => camera,
+            None => return Ok(None),
+        };
+
+        let view = if rotation_only {
+            transform.get_rotation().to_matrix()
+        } else {
+            transform.world_matrix()
+        };
+        let view = match view.inverse() {
+            Some(view) => view,
+            None => return Ok(None),
+        };
+
+        Ok(Some(
+            camera.projection_matrix((size.width / size.height) as f32) * view,
+        ))
+    }
+
+    // the first shadow casting light, centered on the camera
+    // returns the light's view * projection and its bias
+    fn shadow_view_projection(&mut self) -> Option<(Matrix4, f32)> {
+        let world = self.dispatcher.world_mut();
+        let lights = world.read_storage::<DirectionalLightComponent>();
+        let light = (&lights).join().find(|light| light.casts_shadows)?;
+
+        let cameras = world.read_storage::<CameraComponent>();
+        let transforms = world.read_storage::<TransformComponent>();
+        let focus = (&cameras, &transforms)
+            .join()
+            .next()
+            .map_or(Vector3::default(), |(_, transform)| {
+                transform.get_position()
+            });
+
+        Some((light.shadow_view_projection(focus)?, light.shadow_bias))
+    }
+
+    fn load_sprite_texture(&mut self, texture: &str) {
+        if self.sprite_textures.contains_key(texture) {
+            return;
+        }
+
+        let renderer = &self.renderer;
+        let sprite_pipeline = &self.sprite_pipeline;
+        let loaded = self
+            .asset_source
+            .read(Path::new(texture))
+            .and_then(|data| renderer.create_texture(&data))
+            .and_then(|image| {
+                let descriptor_set =
+                    renderer.create_texture_descriptor_set(sprite_pipeline, &image)?;
+                Ok((image, descriptor_set))
+            });
+
+        let loaded = loaded.unwrap_or_else(|e| {
+            error!("Error loading sprite texture {}: {}", texture, e);
+            (renderer::Texture::None, renderer::DescriptorSet::None)
+        });
+        self.sprite_textures.insert(texture.to_owned(), loaded);
+    }
+
     fn render_scene(&mut self) -> anyhow::Result<()> {
-        /*if !self.renderer.draw_data(
-            &self.render_pipeline,
-            [0.0, 0.0, 1.0, 1.0],
-            &self.scene.vertex_buffer,
-            &self.frame_buffers,
-        )? {
-            self.recreate_swapchain = true;
+        if self.frame_buffers.is_empty() && !self.renderer.is_headless() {
             return Ok(());
-        }*/
+        }
+
+        // drawn first, everything else draws over it
+        let mut skybox_descriptor_set = renderer::DescriptorSet::None;
+        if let renderer::Texture::Vulkan(_) = self.skybox {
+            if let Some(view_projection) = self.camera_view_projection(true)? {
+                skybox_descriptor_set = self.renderer.create_skybox_descriptor_set(
+                    &self.skybox_pipeline,
+                    &self.skybox,
+                    // shaders expect column-major
+                    view_projection.transpose().m,
+                )?;
+            }
+        }
+
+        // shaders expect column-major
+        let camera_view_projection = self.camera_view_projection(false)?;
+        let view_projection =
+            camera_view_projection.map(|view_projection| view_projection.transpose().m);
+
+        // without a shadow casting light the cleared shadow map shadows nothing
+        let shadow = self.shadow_view_projection();
+        let mut shadow_descriptor_set = renderer::DescriptorSet::None;
+        if view_projection.is_some() {
+            let (shadow_view_projection, shadow_bias) =
+                shadow.unwrap_or((Matrix4::identity(), 0.0));
+            shadow_descriptor_set = self.renderer.create_shadow_descriptor_set(
+                &self.render_pipeline,
+                &self.shadow_map,
+                shadow_view_projection.transpose().m,
+                shadow_bias,
+            )?;
+        }
+
+        // one instance buffer per opaque mesh, then one per transparent mesh in draw order
+        let mut meshes = Vec::new();
+        let mut sprites = Vec::new();
+        if let Some(camera_view_projection) = camera_view_projection {
+            let queue = self.dispatcher.world_mut().read_resource::<RenderQueue>();
+            for (mesh, instances) in queue.instances.iter() {
+                let instance_buffer = self.renderer.create_instance_buffer(instances.clone())?;
+                meshes.push((*mesh, instance_buffer, instances.len(), false));
+            }
+            for (mesh, instance) in queue.transparent.iter() {
+                let instance_buffer = self.renderer.create_instance_buffer(vec![*instance])?;
+                meshes.push((*mesh, instance_buffer, 1, true));
+            }
+            for (texture, model) in queue.sprites.iter() {
+                let transform = camera_view_projection * *model;
+                sprites.push((texture.clone(), transform.transpose().m));
+            }
+        }
+        for (texture, _) in sprites.iter() {
+            self.load_sprite_texture(texture);
+        }
+
+        let debug_lines = self
+            .dispatcher
+            .world_mut()
+            .write_resource::<DebugLines>()
+            .take();
+        let debug_lines = if debug_lines.is_empty() {
+            renderer::VertexBuffer::None
+        } else {
+            self.renderer
+                .create_colored_line_vertex_buffer(debug_lines)?
+        };
+
+        // all of the frame's text is laid out into one buffer
+        let text = self
+            .dispatcher
+            .world_mut()
+            .write_resource::<TextQueue>()
+            .take();
+        let mut text_vertices = Vec::new();
+        for text in text.iter() {
+            text_vertices.extend(self.font.layout_text(
+                &text.text,
+                text.position.into(),
+                text.size,
+                text.color.into(),
+            ));
+        }
+
+        let mut text_buffer = renderer::VertexBuffer::None;
+        let mut text_descriptor_set = renderer::DescriptorSet::None;
+        let mut text_transform = Matrix4::identity().m;
+        let mut text_triangles = 0;
+        if let renderer::Texture::Vulkan(_) = self.font_texture {
+            let size = self.renderer.get_window()?.get_inner_size();
+            if let Some(size) = size.filter(|size| size.width > 0.0 && size.height > 0.0) {
+                if !text_vertices.is_empty() {
+                    text_triangles = text_vertices.len() / 3;
+                    text_buffer = self.renderer.create_text_vertex_buffer(text_vertices)?;
+                    text_descriptor_set = self
+                        .renderer
+                        .create_texture_descriptor_set(&self.text_pipeline, &self.font_texture)?;
+
+                    // text positions are logical pixels from the top-left
+                    let projection = Matrix4::orthographic(
+                        0.0,
+                        size.width as f32,
+                        size.height as f32,
+                        0.0,
+                        0.0,
+                        1.0,
+                    );
+                    text_transform = projection.transpose().m;
+                }
+            }
+        }
+
+        let particles = {
+            let emitters = self
+                .dispatcher
+                .world_mut()
+                .read_storage::<ParticleEmitterComponent>();
+            (&emitters)
+                .join()
+                .flat_map(|emitter| emitter.get_particles().iter())
+                .map(|particle| renderer::Vertex {
+                    position: particle.position.into(),
+                })
+                .collect::<Vec<_>>()
+        };
+        let particles = if particles.is_empty() {
+            renderer::VertexBuffer::None
+        } else {
+            self.renderer.create_vertex_buffer(particles)?
+        };
+
+        // the most recently loaded scene owns the background
+        let clear_color = self
+            .loaded_scenes
+            .last()
+            .map_or(assets::DEFAULT_CLEAR_COLOR, |scene| scene.get_clear_color());
+
+        // opaque meshes cast shadows
+        let mut shadow_items = Vec::new();
+        if let Some((shadow_view_projection, _)) = shadow {
+            for (mesh, instance_buffer, _, transparent) in meshes.iter() {
+                if *transparent {
+                    continue;
+                }
+
+                let buffer = match self
+                    .loaded_scenes
+                    .iter()
+                    .rev()
+                    .find_map(|scene| scene.get_mesh_buffer(mesh))
+                {
+                    Some(buffer) => buffer,
+                    None => continue,
+                };
+
+                if let renderer::VertexBuffer::Vulkan(_) = buffer.get_vertex_buffer() {
+                    shadow_items.push(renderer::DrawItem {
+                        render_pipeline: &self.shadow_pipeline,
+                        vertex_buffer: buffer.get_vertex_buffer(),
+                        descriptor_set: &renderer::DescriptorSet::None,
+                        instance_buffer: Some(instance_buffer),
+                        push_constants: Some(renderer::PushConstants {
+                            transform: shadow_view_projection.transpose().m,
+                        }),
+                    });
+                }
+            }
+        }
+
+        let mut draw_items = Vec::new();
+        if let renderer::DescriptorSet::Vulkan(_) = skybox_descriptor_set {
+            draw_items.push(renderer::DrawItem {
+                render_pipeline: &self.skybox_pipeline,
+                vertex_buffer: &self.skybox_vertex_buffer,
+                descriptor_set: &skybox_descriptor_set,
+                instance_buffer: None,
+                push_constants: None,
+            });
+        }
+        if let Some(view_projection) = view_projection {
+            for (mesh, instance_buffer, instance_count, transparent) in meshes.iter() {
+                // the most recently loaded scene wins if mesh ids collide
+                let buffer = match self
+                    .loaded_scenes
+                    .iter()
+                    .rev()
+                    .find_map(|scene| scene.get_mesh_buffer(mesh))
+                {
+                    Some(buffer) => buffer,
+                    None => continue,
+                };
+
+                if let renderer::VertexBuffer::Vulkan(_) = buffer.get_vertex_buffer() {
+                    draw_items.push(renderer::DrawItem {
+                        render_pipeline: if *transparent {
+                            &self.transparent_pipeline
+                        } else {
+                            &self.render_pipeline
+                        },
+                        vertex_buffer: buffer.get_vertex_buffer(),
+                        descriptor_set: &shadow_descriptor_set,
+                        instance_buffer: Some(instance_buffer),
+                        push_constants: Some(renderer::PushConstants {
+                            transform: view_projection,
+                        }),
+                    });
+                    self.stats.triangles += instance_count * buffer.vertex_count() / 3;
+                }
+            }
+        }
+        for (texture, transform) in sprites.iter() {
+            let descriptor_set = match self.sprite_textures.get(texture) {
+                Some((_, descriptor_set)) => descriptor_set,
+                None => continue,
+            };
+
+            if let renderer::DescriptorSet::Vulkan(_) = descriptor_set {
+                draw_items.push(renderer::DrawItem {
+                    render_pipeline: &self.sprite_pipeline,
+                    vertex_buffer: &self.sprite_vertex_buffer,
+                    descriptor_set,
+                    instance_buffer: None,
+                    push_constants: Some(renderer::PushConstants {
+                        transform: *transform,
+                    }),
+                });
+                self.stats.triangles += SPRITE_TRIANGLES;
+            }
+        }
+        if let (renderer::VertexBuffer::Vulkan(_), Some(view_projection)) =
+            (&particles, view_projection)
+        {
+            draw_items.push(renderer::DrawItem {
+                render_pipeline: &self.particle_pipeline,
+                vertex_buffer: &particles,
+                descriptor_set: &renderer::DescriptorSet::None,
+                instance_buffer: None,
+                // particles are already in world space
+                push_constants: Some(renderer::PushConstants {
+                    transform: view_projection,
+                }),
+            });
+        }
+        if let renderer::VertexBuffer::Vulkan(_) = debug_lines {
+            draw_items.push(renderer::DrawItem {
+                render_pipeline: &self.debug_line_pipeline,
+                vertex_buffer: &debug_lines,
+                descriptor_set: &renderer::DescriptorSet::None,
+                instance_buffer: None,
+                push_constants: None,
+            });
+        }
+        // HUD text draws over everything
+        if let renderer::VertexBuffer::Vulkan(_) = text_buffer {
+            draw_items.push(renderer::DrawItem {
+                render_pipeline: &self.text_pipeline,
+                vertex_buffer: &text_buffer,
+                descriptor_set: &text_descriptor_set,
+                instance_buffer: None,
+                push_constants: Some(renderer::PushConstants {
+                    transform: text_transform,
+                }),
+            });
+            self.stats.triangles += text_triangles;
+        }
+
+        self.stats.draw_calls += shadow_items.len() + draw_items.len();
+        if let renderer::DescriptorSet::Vulkan(_) = skybox_descriptor_set {
+            self.stats.triangles += SKYBOX_TRIANGLES;
+        }
+
+        // always drawn, the scene samples the shadow map even without a shadow casting light
+        let shadow_pass = renderer::ShadowPass {
+            shadow_map: &self.shadow_map,
+            draw_items: &shadow_items,
+        };
+        let post_process = renderer::PostProcessItem {
+            render_pipeline: &self.tonemap_pipeline,
+            descriptor_sets: &self.tonemap_descriptor_sets,
+        };
+        let frame_buffers = &self.frame_buffers;
+        let depth_mode = self.depth_mode;
+        let mut recreate_swapchain = false;
+        let mut graph = renderer::RenderGraph::new();
+        graph.add_pass(
+            "scene",
+            &[],
+            &[renderer::SWAPCHAIN_ATTACHMENT],
+            |renderer: &mut renderer::Renderer| {
+                if !renderer.draw_data(
+                    clear_color.into(),
+                    depth_mode,
+                    Some(&shadow_pass),
+                    &draw_items,
+                    Some(&post_process),
+                    frame_buffers,
+                )? {
+                    recreate_swapchain = true;
+                }
+                Ok(())
+            },
+        );
+        graph.execute(&mut self.renderer)?;
+        drop(graph);
+
+        if recreate_swapchain {
+            self.recreate_swapchain = true;
+        }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use components::VelocityComponent;
+
+    // the logger is global, records are kept per test thread
+    struct CapturingLogger {
+        records: std::sync::Mutex<Vec<(std::thread::ThreadId, log::Level, String)>>,
+    }
+
+    impl log::Log for CapturingLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            self.records.lock().unwrap().push((
+                std::thread::current().id(),
+                record.level(),
+                record.args().to_string(),
+            ));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger {
+        records: std::sync::Mutex::new(Vec::new()),
+    };
+
+    fn capture_logs() {
+        static INIT: std::sync::Once = std::sync::Once::new();
+        INIT.call_once(|| {
+            log::set_logger(&LOGGER).unwrap();
+            log::set_max_level(log::LevelFilter::Trace);
+        });
+    }
+
+    fn captured_logs() -> Vec<(log::Level, String)> {
+        let thread = std::thread::current().id();
+        LOGGER
+            .records
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(id, _, _)| *id == thread)
+            .map(|(_, level, message)| (*level, message.clone()))
+            .collect()
+    }
+
+    fn mock_engine() -> Engine<'static> {
+        mock_engine_with_config(&config::RendererConfig::default())
+    }
+
+    fn mock_engine_with_config(renderer_config: &config::RendererConfig) -> Engine<'static> {
+        let window_config = config::WindowConfig {
+            width: 640,
+            height: 480,
+            min_width: 0,
+            min_height: 0,
+            fullscreen: false,
+            size_in_physical_pixels: false,
+            pause_when_unfocused: false,
+        };
+
+        Engine::new(
+            "test",
+            [0, 1, 0],
+            RendererType::Mock,
+            &window_config,
+            renderer_config,
+        )
+        .unwrap()
+    }
+
+    fn position(engine: &mut Engine, entity: Entity) -> Vector3 {
+        engine
+            .dispatcher
+            .world_mut()
+            .read_storage::<TransformComponent>()
+            .get(entity)
+            .unwrap()
+            .get_position()
+    }
+
+    #[test]
+    fn step_advances_one_tick_while_paused() {
+        let mut engine = mock_engine();
+        let entity = engine
+            .dispatcher
+            .world_mut()
+            .create_entity()
+            .with(TransformComponent::default())
+            .with(VelocityComponent(Vector3::right()))
+            .build();
+
+        engine.pause();
+        engine.update(1.0);
+        assert_eq!(position(&mut engine, entity), Vector3::default());
+
+        engine.step();
+        engine.update(1.0);
+        assert_eq!(
+            position(&mut engine, entity),
+            Vector3::right() * STEP_DELTA_TIME
+        );
+
+        engine.update(1.0);
+        assert_eq!(
+            position(&mut engine, entity),
+            Vector3::right() * STEP_DELTA_TIME
+        );
+
+        engine.resume();
+        engine.update(1.0);
+        assert_eq!(
+            position(&mut engine, entity),
+            Vector3::right() * (1.0 + STEP_DELTA_TIME)
+        );
+    }
+
+    #[test]
+    fn initialization_is_logged() {
+        capture_logs();
+
+        let _engine = mock_engine();
+
+        assert!(captured_logs().contains(&(log::Level::Info, "Initializing engine...".to_owned())));
+    }
+
+    #[test]
+    fn gizmos_draw_while_paused() {
+        let mut engine = mock_engine();
+        let entity = engine
+            .dispatcher
+            .world_mut()
+            .create_entity()
+            .with(TransformComponent::default())
+            .build();
+        engine.select_entity(entity, true).unwrap();
+        engine.show_gizmos(true);
+
+        engine.pause();
+        for _ in 0..2 {
+            engine.update(1.0);
+
+            let lines = engine
+                .dispatcher
+                .world_mut()
+                .write_resource::<DebugLines>()
+                .take();
+            // one line per axis
+            assert_eq!(lines.len(), 6);
+        }
+    }
+
+    #[test]
+    fn shadow_map_uses_the_configured_size() {
+        let engine = mock_engine_with_config(&config::RendererConfig {
+            shadow_map_size: 512,
+            ..Default::default()
+        });
+
+        assert_eq!(engine.shadow_map.size, 512);
+        match &engine.renderer {
+            renderer::Renderer::Mock(r) => {
+                assert!(r
+                    .get_calls()
+                    .contains(&renderer::MockCall::CreateDepthImage {
+                        dimensions: [512, 512],
+                    }))
+            }
+            _ => unreachable!(),
+        }
+        drop(engine);
+
+        // only one engine at a time, they each own an imgui context
+        let engine = mock_engine();
+        assert_eq!(engine.shadow_map.size, renderer::DEFAULT_SHADOW_MAP_SIZE);
+    }
+
+    #[test]
+    fn shadow_pass_draws_before_the_scene() {
+        let mut engine = mock_engine();
+        engine.render_scene().unwrap();
+
+        match &engine.renderer {
+            renderer::Renderer::Mock(r) => {
+                let calls = r.get_calls();
+                let shadows = calls
+                    .iter()
+                    .position(|call| matches!(call, renderer::MockCall::DrawShadows { .. }));
+                let scene = calls
+                    .iter()
+                    .position(|call| matches!(call, renderer::MockCall::DrawData { .. }));
+                assert!(shadows.unwrap() < scene.unwrap());
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn scene_is_tonemapped() {
+        let mut engine = mock_engine();
+        engine.set_tonemap(renderer::Tonemap::Aces).unwrap();
+        engine.render_scene().unwrap();
+
+        match &engine.renderer {
+            renderer::Renderer::Mock(r) => {
+                assert!(r.get_calls().contains(&renderer::MockCall::DrawFullscreen {
+                    vertex_count: renderer::FULLSCREEN_TRIANGLE_VERTICES,
+                }))
+            }
+            _ => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use specs::prelude::*;
+
+use core::math::{Ray, Vector3};
+
+use crate::components::{BoundsComponent, CameraComponent, TransformComponent};
+
+// ndc is in vulkan clip space (-1..1, y down)
+pub(crate) fn pick(world: &World, ndc: [f32; 2], aspect: f32) -> Option<Entity> {
+    let entities = world.entities();
+    let cameras = world.read_storage::<CameraComponent>();
+    let transforms = world.read_storage::<TransformComponent>();
+    let bounds = world.read_storage::<BoundsComponent>();
+
+    // TODO: support picking through a specific camera
+    let (camera, camera_transform) = (&cameras, &transforms).join().next()?;
+
+    let view = camera_transform.world_matrix().inverse()?;
+    let inverse_view_projection = (camera.projection_matrix(aspect) * view).inverse()?;
+
+    let near = inverse_view_projection.transform_point_projected(Vector3 {
+        x: ndc[0],
+        y: ndc[1],
+        z: 0.0,
+    });
+    let far = inverse_view_projection.transform_point_projected(Vector3 {
+        x: ndc[0],
+        y: ndc[1],
+        z: 1.0,
+    });
+
+    let ray = Ray::new(near, far - near);
+
+    (&entities, &transforms, &bounds)
+        .join()
+        .filter_map(|(entity, transform, bounds)| {
+            let aabb = bounds.transformed(&transform.world_matrix());
+            ray.intersect_aabb(aabb.min, aabb.max).map(|t| (entity, t))
+        })
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(entity, _)| entity)
+}
@@ -0,0 +1,30 @@
+use core::math::Vector3;
+
+pub const DEBUG_LINE_COLOR: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
+
+// pairs of line end points, cleared every frame
+#[derive(Default, Debug)]
+pub struct DebugLines {
+    vertices: Vec<renderer::LineVertex>,
+}
+
+impl DebugLines {
+    pub fn add_line(&mut self, a: Vector3, b: Vector3, color: [f32; 4]) {
+        self.vertices.push(renderer::LineVertex {
+            position: a.into(),
+            color,
+        });
+        self.vertices.push(renderer::LineVertex {
+            position: b.into(),
+            color,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    pub(crate) fn take(&mut self) -> Vec<renderer::LineVertex> {
+        std::mem::take(&mut self.vertices)
+    }
+}
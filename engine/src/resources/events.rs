@@ -0,0 +1,42 @@
+use specs::prelude::*;
+pub use specs::shrev::{Event, EventChannel, ReaderId};
+
+// emitted once per overlapping pair per frame
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CollisionEvent {
+    pub a: Entity,
+    pub b: Entity,
+}
+
+// systems can also use Read / Write<EventChannel<E>> directly
+pub trait EventBus {
+    fn publish<E>(&self, event: E)
+    where
+        E: Event;
+
+    // creates the channel if this is the first subscriber
+    fn subscribe<E>(&mut self) -> ReaderId<E>
+    where
+        E: Event;
+}
+
+impl EventBus for World {
+    fn publish<E>(&self, event: E)
+    where
+        E: Event,
+    {
+        // dropped if nobody has subscribed yet
+        if let Some(mut channel) = self.try_fetch_mut::<EventChannel<E>>() {
+            channel.single_write(event);
+        }
+    }
+
+    fn subscribe<E>(&mut self) -> ReaderId<E>
+    where
+        E: Event,
+    {
+        self.entry::<EventChannel<E>>()
+            .or_insert_with(EventChannel::new)
+            .register_reader()
+    }
+}
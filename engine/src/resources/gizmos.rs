@@ -0,0 +1,16 @@
+#[derive(Debug, Copy, Clone)]
+pub struct GizmoSettings {
+    pub enabled: bool,
+
+    // length of each axis line in world units
+    pub size: f32,
+}
+
+impl Default for GizmoSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            size: 1.0,
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::{HashMap, HashSet};
+
+use winit::{ElementState, MouseButton, VirtualKeyCode};
+
+use core::math::Vector2;
+
+use crate::config::{InputBinding, InputBindings};
+
+// axis values past this count as pressed
+const AXIS_PRESSED_THRESHOLD: f32 = 0.5;
+
+#[derive(Default, Debug)]
+struct GamepadState {
+    buttons: HashSet<gilrs::Button>,
+    axes: HashMap<gilrs::Axis, f32>,
+}
+
+// raw input state, updated from window events each frame
+#[derive(Default, Debug)]
+pub struct InputState {
+    keys: HashSet<VirtualKeyCode>,
+    mouse_buttons: HashSet<MouseButton>,
+
+    // logical pixels
+    cursor_position: Option<Vector2>,
+
+    // reset every frame
+    cursor_delta: Vector2,
+    mouse_motion: Vector2,
+    scroll_delta: Vector2,
+
+    // by gilrs::GamepadId, merged when queried
+    gamepads: HashMap<usize, GamepadState>,
+
+    bindings: InputBindings,
+}
+
+impl InputState {
+    pub fn is_key_down(&self, key: VirtualKeyCode) -> bool {
+        self.keys.contains(&key)
+    }
+
+    pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.contains(&button)
+    }
+
+    pub fn get_cursor_position(&self) -> Option<Vector2> {
+        self.cursor_position
+    }
+
+    // change in cursor position since last frame
+    pub fn get_cursor_delta(&self) -> Vector2 {
+        self.cursor_delta
+    }
+
+    // raw device motion since last frame, not affected by the cursor
+    // hitting the window edge, better for FPS-style look
+    pub fn get_mouse_motion(&self) -> Vector2 {
+        self.mouse_motion
+    }
+
+    // scroll since last frame in lines, positive y is away from the user
+    pub fn get_scroll_delta(&self) -> Vector2 {
+        self.scroll_delta
+    }
+
+    // down on any connected gamepad
+    pub fn is_gamepad_button_down(&self, button: gilrs::Button) -> bool {
+        self.gamepads
+            .values()
+            .any(|gamepad| gamepad.buttons.contains(&button))
+    }
+
+    // -1..1, 0 if not reported
+    // the largest magnitude across connected gamepads
+    pub fn get_gamepad_axis(&self, axis: gilrs::Axis) -> f32 {
+        self.gamepads
+            .values()
+            .filter_map(|gamepad| gamepad.axes.get(&axis).copied())
+            .fold(
+                0.0,
+                |value, v| if v.abs() > value.abs() { v } else { value },
+            )
+    }
+
+    pub fn get_bindings(&self) -> &InputBindings {
+        &self.bindings
+    }
+
+    pub fn set_bindings(&mut self, bindings: InputBindings) {
+        self.bindings = bindings;
+    }
+
+    fn binding_value(&self, binding: &InputBinding) -> f32 {
+        let pressed = |down: bool| if down { 1.0 } else { 0.0 };
+
+        match binding {
+            InputBinding::Key(key) => pressed(self.is_key_down(*key)),
+            InputBinding::Mouse(button) => pressed(self.is_mouse_button_down(*button)),
+            InputBinding::GamepadButton(button) => pressed(self.is_gamepad_button_down(*button)),
+            InputBinding::GamepadAxis(axis) => self.get_gamepad_axis(*axis),
+        }
+    }
+
+    // the bound input with the largest magnitude, buttons are 0 or 1
+    pub fn action_value(&self, action: &str) -> f32 {
+        self.bindings
+            .get_bindings(action)
+            .iter()
+            .map(|binding| self.binding_value(binding))
+            .fold(
+                0.0,
+                |value, v| if v.abs() > value.abs() { v } else { value },
+            )
+    }
+
+    // true if any input bound to the action is down
+    pub fn action_pressed(&self, action: &str) -> bool {
+        self.action_value(action).abs() >= AXIS_PRESSED_THRESHOLD
+    }
+
+    pub(crate) fn set_key(&mut self, key: VirtualKeyCode, state: ElementState) {
+        match state {
+            ElementState::Pressed => self.keys.insert(key),
+            ElementState::Released => self.keys.remove(&key),
+        };
+    }
+
+    pub(crate) fn set_mouse_button(&mut self, button: MouseButton, state: ElementState) {
+        match state {
+            ElementState::Pressed => self.mouse_buttons.insert(button),
+            ElementState::Released => self.mouse_buttons.remove(&button),
+        };
+    }
+
+    pub(crate) fn begin_frame(&mut self) {
+        self.cursor_delta = Vector2::default();
+        self.mouse_motion = Vector2::default();
+        self.scroll_delta = Vector2::default();
+    }
+
+    pub(crate) fn set_cursor_position(&mut self, position: Vector2) {
+        if let Some(previous) = self.cursor_position {
+            self.cursor_delta += position - previous;
+        }
+        self.cursor_position = Some(position);
+    }
+
+    pub(crate) fn add_mouse_motion(&mut self, motion: Vector2) {
+        self.mouse_motion += motion;
+    }
+
+    pub(crate) fn add_scroll(&mut self, scroll: Vector2) {
+        self.scroll_delta += scroll;
+    }
+
+    pub(crate) fn set_gamepad_button(
+        &mut self,
+        gamepad: usize,
+        button: gilrs::Button,
+        pressed: bool,
+    ) {
+        let buttons = &mut self.gamepads.entry(gamepad).or_default().buttons;
+        if pressed {
+            buttons.insert(button);
+        } else {
+            buttons.remove(&button);
+        }
+    }
+
+    pub(crate) fn set_gamepad_axis(&mut self, gamepad: usize, axis: gilrs::Axis, value: f32) {
+        self.gamepads
+            .entry(gamepad)
+            .or_default()
+            .axes
+            .insert(axis, value);
+    }
+
+    // the other gamepads keep their state
+    pub(crate) fn clear_gamepad(&mut self, gamepad: usize) {
+        self.gamepads.remove(&gamepad);
+    }
+
+    // nothing is reported released while unfocused
+    pub(crate) fn clear(&mut self) {
+        self.keys.clear();
+        self.mouse_buttons.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_bound_to_space_is_pressed_while_space_is_down() {
+        let mut input = InputState::default();
+        let bindings: InputBindings =
+            serde_json::from_str(r#"{ "actions": { "jump": [{ "Key": "Space" }] } }"#).unwrap();
+        input.set_bindings(bindings);
+
+        assert!(!input.action_pressed("jump"));
+
+        input.set_key(VirtualKeyCode::Space, ElementState::Pressed);
+        assert!(input.action_pressed("jump"));
+        assert!(!input.action_pressed("crouch"));
+
+        input.set_key(VirtualKeyCode::Space, ElementState::Released);
+        assert!(!input.action_pressed("jump"));
+    }
+
+    #[test]
+    fn gamepad_axis_drives_action_value() {
+        let mut input = InputState::default();
+        let mut bindings = InputBindings::default();
+        bindings.bind("move_x", InputBinding::GamepadAxis(gilrs::Axis::LeftStickX));
+        input.set_bindings(bindings);
+
+        input.set_gamepad_axis(0, gilrs::Axis::LeftStickX, -0.75);
+        assert_eq!(input.action_value("move_x"), -0.75);
+        assert!(input.action_pressed("move_x"));
+
+        input.set_gamepad_axis(0, gilrs::Axis::LeftStickX, 0.25);
+        assert_eq!(input.action_value("move_x"), 0.25);
+        assert!(!input.action_pressed("move_x"));
+    }
+
+    #[test]
+    fn gamepads_are_merged() {
+        let mut input = InputState::default();
+
+        input.set_gamepad_button(0, gilrs::Button::South, true);
+        input.set_gamepad_button(1, gilrs::Button::South, true);
+        input.set_gamepad_button(0, gilrs::Button::South, false);
+        assert!(input.is_gamepad_button_down(gilrs::Button::South));
+
+        input.set_gamepad_axis(0, gilrs::Axis::LeftStickY, 0.5);
+        input.set_gamepad_axis(1, gilrs::Axis::LeftStickY, -0.9);
+        assert_eq!(input.get_gamepad_axis(gilrs::Axis::LeftStickY), -0.9);
+
+        // disconnecting one pad leaves the other's state alone
+        input.clear_gamepad(1);
+        assert!(!input.is_gamepad_button_down(gilrs::Button::South));
+        assert_eq!(input.get_gamepad_axis(gilrs::Axis::LeftStickY), 0.5);
+    }
+}
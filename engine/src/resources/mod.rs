@@ -0,0 +1,17 @@
+mod debug_lines;
+mod events;
+mod gizmos;
+mod input;
+mod random;
+mod render_queue;
+mod text;
+mod time;
+
+pub use debug_lines::*;
+pub use events::*;
+pub use gizmos::*;
+pub use input::*;
+pub use random::*;
+pub use render_queue::*;
+pub use text::*;
+pub use time::*;
@@ -0,0 +1,50 @@
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+
+// shared seedable RNG so runs can be reproduced
+#[derive(Debug)]
+pub struct Random {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::new(rand::thread_rng().next_u64())
+    }
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    // restarts the sequence
+    pub fn set_seed(&mut self, seed: u64) {
+        *self = Self::new(seed);
+    }
+
+    // -1..1
+    pub fn next_signed(&mut self) -> f32 {
+        self.rng.gen_range(-1.0, 1.0)
+    }
+
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        if min >= max {
+            return min;
+        }
+
+        self.rng.gen_range(min, max)
+    }
+
+    pub fn get_rng_mut(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use core::math::Matrix4;
+
+// draws gathered by the RenderSystem, rebuilt every simulation tick
+// and drawn every frame, so paused simulations keep rendering
+#[derive(Default, Debug)]
+pub struct RenderQueue {
+    // entities sharing a mesh are drawn with a single instanced draw
+    pub(crate) instances: HashMap<Uuid, Vec<renderer::InstanceData>>,
+
+    // alpha blended, drawn one at a time in order after the instanced meshes
+    pub(crate) transparent: Vec<(Uuid, renderer::InstanceData)>,
+
+    // texture path and model matrix scaled to the sprite size, drawn last in order
+    pub(crate) sprites: Vec<(String, Matrix4)>,
+}
+
+impl RenderQueue {
+    pub fn add_instance(&mut self, mesh: Uuid, instance: renderer::InstanceData) {
+        self.instances.entry(mesh).or_default().push(instance);
+    }
+
+    // should be added back-to-front
+    pub fn add_transparent(&mut self, mesh: Uuid, instance: renderer::InstanceData) {
+        self.transparent.push((mesh, instance));
+    }
+
+    // should be added back-to-front
+    pub fn add_sprite<S>(&mut self, texture: S, model: Matrix4)
+    where
+        S: Into<String>,
+    {
+        self.sprites.push((texture.into(), model));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty() && self.transparent.is_empty() && self.sprites.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.instances.clear();
+        self.transparent.clear();
+        self.sprites.clear();
+    }
+}
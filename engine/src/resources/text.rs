@@ -0,0 +1,31 @@
+use core::math::{Color, Vector2};
+
+#[derive(Debug, Clone)]
+pub struct QueuedText {
+    pub text: String,
+
+    // screen space pixels from the top-left
+    pub position: Vector2,
+    pub size: f32,
+    pub color: Color,
+}
+
+// HUD text, cleared every frame
+#[derive(Default, Debug)]
+pub struct TextQueue {
+    text: Vec<QueuedText>,
+}
+
+impl TextQueue {
+    pub fn add_text(&mut self, text: QueuedText) {
+        self.text.push(text);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub(crate) fn take(&mut self) -> Vec<QueuedText> {
+        std::mem::take(&mut self.text)
+    }
+}
@@ -0,0 +1,13 @@
+// seconds since the previous frame started
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct DeltaTime(pub f32);
+
+// how far rendering is between the previous and current simulation tick
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct InterpolationAlpha(pub f32);
+
+impl Default for InterpolationAlpha {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
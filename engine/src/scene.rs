@@ -1,52 +1,398 @@
-use std::fs::File;
-use std::io::{BufReader, BufWriter};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use anyhow::bail;
 use log::{debug, info, warn};
 use specs::prelude::*;
+use uuid::Uuid;
 
-use core::fs::to_absolute_path;
+use core::fs::AssetSource;
+use core::jobs::{JobHandle, JobPool};
+use core::math::Color;
 
-use crate::assets::{Resource, SceneAsset};
+use crate::assets::{
+    import_gltf, load_prefab_instance, load_resource, ActorAsset, AssetCatalog, MeshAsset,
+    Resource, ResourceFormat, SceneAsset, SceneBuilder, DEFAULT_CLEAR_COLOR, GLTF_EXTENSIONS,
+};
+use crate::components::{DisabledComponent, NameComponent, TransformComponent};
+
+fn inject_default_components(actors: &mut [ActorAsset]) {
+    for actor in actors.iter_mut() {
+        let components = &actor.components;
+        if components.iter().any(|c| c.requires_transform())
+            && !components.iter().any(|c| c.is_transform())
+        {
+            debug!("Adding a default transform to actor {}", actor.id);
+            actor
+                .components
+                .push(Box::new(TransformComponent::default()));
+        }
+
+        inject_default_components(&mut actor.children);
+    }
+}
 
-#[derive(Default)]
 pub struct Scene {
+    id: Uuid,
+    clear_color: Color,
+    skybox: Vec<String>,
+
+    // add a default transform to actors with components that need one
+    inject_default_components: bool,
+
     entities: Vec<Entity>,
+
+    // actor asset id to its spawned entity
+    actors: HashMap<Uuid, Entity>,
+
+    // entities spawned by each additive load
+    groups: HashMap<String, Vec<Entity>>,
+
+    meshes: HashMap<Uuid, MeshAsset>,
+
+    // returned to the renderer's pool on unload
+    mesh_buffers: HashMap<Uuid, renderer::PooledVertexBuffer>,
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            clear_color: DEFAULT_CLEAR_COLOR,
+            skybox: Vec::new(),
+            inject_default_components: true,
+            entities: Vec::new(),
+            actors: HashMap::new(),
+            groups: HashMap::new(),
+            meshes: HashMap::new(),
+            mesh_buffers: HashMap::new(),
+        }
+    }
 }
 
 impl Scene {
-    pub fn load<P>(&mut self, world: &mut World, filepath: P) -> anyhow::Result<()>
+    pub fn get_clear_color(&self) -> Color {
+        self.clear_color
+    }
+
+    pub fn set_inject_default_components(&mut self, inject: bool) {
+        self.inject_default_components = inject;
+    }
+
+    // loads the scene itself and then its referenced resources on the job pool,
+    // results are the skybox faces in order
+    pub fn load_async<P>(
+        &mut self,
+        world: &mut World,
+        source: Arc<dyn AssetSource>,
+        catalog: &AssetCatalog,
+        filepath: P,
+        pool: &JobPool,
+    ) -> anyhow::Result<JobHandle<anyhow::Result<Vec<u8>>>>
     where
         P: AsRef<Path>,
     {
-        let mut filepath = to_absolute_path(filepath)?;
-        filepath.set_extension(SceneAsset::EXTENSION);
+        self.load(world, source.as_ref(), catalog, filepath)?;
+
+        // TODO: load prefabs and meshes here as well
+        let jobs = self
+            .skybox
+            .iter()
+            .map(|face| {
+                let source = source.clone();
+                let face = PathBuf::from(face);
+                move || source.read(&face)
+            })
+            .collect();
+
+        Ok(pool.spawn_all(jobs))
+    }
+
+    pub fn load<P>(
+        &mut self,
+        world: &mut World,
+        source: &dyn AssetSource,
+        catalog: &AssetCatalog,
+        filepath: P,
+    ) -> anyhow::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        self.load_file(world, source, catalog, filepath.as_ref(), false)
+    }
+
+    // merges another scene's actors into this one, keeping the existing entities
+    // and scene settings, the new entities can be removed with unload_group()
+    pub fn load_additive<P>(
+        &mut self,
+        world: &mut World,
+        source: &dyn AssetSource,
+        catalog: &AssetCatalog,
+        filepath: P,
+        group: &str,
+    ) -> anyhow::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let first = self.entities.len();
+        self.load_file(world, source, catalog, filepath.as_ref(), true)?;
+
+        let spawned = &self.entities[first..];
+        debug!("Loaded {} entities into group {}", spawned.len(), group);
+        self.groups
+            .entry(group.to_owned())
+            .or_default()
+            .extend_from_slice(spawned);
+
+        Ok(())
+    }
+
+    pub fn get_group(&self, group: &str) -> &[Entity] {
+        self.groups
+            .get(group)
+            .map_or(&[], |entities| entities.as_slice())
+    }
+
+    pub fn unload_group(&mut self, world: &mut World, group: &str) -> anyhow::Result<()> {
+        let entities = match self.groups.remove(group) {
+            Some(entities) => entities,
+            None => {
+                warn!("Scene group {} not found", group);
+                return Ok(());
+            }
+        };
+
+        for entity in entities {
+            self.despawn(world, entity)?;
+        }
+
+        Ok(())
+    }
+
+    fn load_file(
+        &mut self,
+        world: &mut World,
+        source: &dyn AssetSource,
+        catalog: &AssetCatalog,
+        filepath: &Path,
+        additive: bool,
+    ) -> anyhow::Result<()> {
+        let mut filepath = filepath.to_path_buf();
+
+        let is_gltf = filepath
+            .extension()
+            .and_then(|x| x.to_str())
+            .map(|x| GLTF_EXTENSIONS.contains(&x))
+            .unwrap_or(false);
+        if is_gltf {
+            let mut import = import_gltf(source, filepath)?;
+            if self.inject_default_components {
+                inject_default_components(&mut import.scene.actors);
+            }
+            self.load_actors(world, source, catalog, &import.scene.actors)?;
+            for mesh in import.meshes {
+                self.meshes.insert(mesh.id, mesh);
+            }
+            return Ok(());
+        }
+
+        // scene.json / scene.toml / scene.ron keep their format extension
+        if ResourceFormat::from_path(&filepath).is_none() {
+            filepath.set_extension(SceneAsset::EXTENSION);
+        }
         info!("Loading scene from {}...", filepath.display());
 
-        warn!("TODO: load scene async");
-        let file = File::open(filepath)?;
-        let reader = BufReader::new(file);
-        let asset = serde_json::from_reader::<_, SceneAsset>(reader)?;
+        let mut asset = load_resource::<SceneAsset, _>(source, &filepath)?;
+
+        let errors = asset.validate(catalog);
+        if !errors.is_empty() {
+            bail!(
+                "Invalid scene {} ({} errors):\n  {}",
+                filepath.display(),
+                errors.len(),
+                errors.join("\n  ")
+            );
+        }
 
-        debug!("Loading {} actors...", asset.actors.len());
-        for actor in asset.actors.iter() {
-            if let Some(prefab) = &actor.prefab {
-                warn!("TODO: load prefab {}", prefab);
+        if self.inject_default_components {
+            inject_default_components(&mut asset.actors);
+        }
+
+        if !additive {
+            self.id = asset.id;
+            self.clear_color = asset.clear_color;
+            self.skybox = asset.skybox;
+        }
+
+        self.load_actors(world, source, catalog, &asset.actors)?;
+
+        Ok(())
+    }
+
+    fn load_actors(
+        &mut self,
+        world: &mut World,
+        source: &dyn AssetSource,
+        catalog: &AssetCatalog,
+        actors: &[ActorAsset],
+    ) -> anyhow::Result<()> {
+        debug!("Loading {} actors...", actors.len());
+        for actor in actors.iter() {
+            self.load_actor(world, source, catalog, actor)?;
+        }
+
+        Ok(())
+    }
+
+    // children are spawned as entities of their own
+    // TODO: parent the children's transforms
+    fn load_actor(
+        &mut self,
+        world: &mut World,
+        source: &dyn AssetSource,
+        catalog: &AssetCatalog,
+        actor: &ActorAsset,
+    ) -> anyhow::Result<()> {
+        if !actor.enabled {
+            debug!("Skipping disabled actor {}", actor.id);
+            return Ok(());
+        }
+
+        if actor.prefab.is_some() {
+            let mut instances = [load_prefab_instance(source, catalog, actor)?];
+            if self.inject_default_components {
+                inject_default_components(&mut instances);
+            }
+            return self.load_actor(world, source, catalog, &instances[0]);
+        }
+
+        self.spawn(world, actor);
+        for child in actor.children.iter() {
+            self.load_actor(world, source, catalog, child)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn spawn(&mut self, world: &mut World, actor: &ActorAsset) -> Entity {
+        let mut builder = world.create_entity();
+        for component in actor.components.iter() {
+            builder = component.add_to(builder);
+        }
+
+        let entity = builder.build();
+        self.entities.push(entity);
+        if self.actors.insert(actor.id, entity).is_some() {
+            warn!("Duplicate actor id {}", actor.id);
+        }
+
+        entity
+    }
+
+    // entities not owned by this scene are left alone
+    pub fn despawn(&mut self, world: &mut World, entity: Entity) -> anyhow::Result<()> {
+        let idx = match self.entities.iter().position(|e| *e == entity) {
+            Some(idx) => idx,
+            None => {
+                warn!("Entity {:?} not found in the scene", entity);
+                return Ok(());
             }
+        };
+
+        // keep spawn order for lookups
+        self.entities.remove(idx);
+        self.actors.retain(|_, e| *e != entity);
+        for entities in self.groups.values_mut() {
+            entities.retain(|e| *e != entity);
+        }
+        world.delete_entity(entity)?;
+
+        Ok(())
+    }
+
+    // disabled entities stay in the scene but aren't simulated or rendered
+    pub fn set_enabled(
+        &self,
+        world: &mut World,
+        entity: Entity,
+        enabled: bool,
+    ) -> anyhow::Result<()> {
+        if !self.entities.contains(&entity) {
+            warn!("Entity {:?} not found in the scene", entity);
+            return Ok(());
+        }
+
+        let mut storage = world.write_storage::<DisabledComponent>();
+        if enabled {
+            storage.remove(entity);
+        } else {
+            storage.insert(entity, DisabledComponent)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn find(&self, id: &Uuid) -> Option<Entity> {
+        self.actors.get(id).copied()
+    }
 
-            let builder = world.create_entity();
-            for component in actor.components.iter() {
-                warn!("TODO: add component");
+    // first match in spawn order if names collide
+    pub fn find_by_name(&self, world: &World, name: &str) -> Option<Entity> {
+        let names = world.read_storage::<NameComponent>();
+        self.entities
+            .iter()
+            .find(|entity| names.get(**entity).is_some_and(|n| n.0 == name))
+            .copied()
+    }
+
+    pub fn entity_count(&self) -> usize {
+        self.entities.len()
+    }
+
+    // None until create_mesh_buffers() has run
+    pub fn get_mesh_buffer(&self, mesh: &Uuid) -> Option<&renderer::PooledVertexBuffer> {
+        self.mesh_buffers.get(mesh)
+    }
+
+    // meshes are drawn unindexed for now
+    pub fn create_mesh_buffers(&mut self, renderer: &renderer::Renderer) -> anyhow::Result<()> {
+        for (id, mesh) in self.meshes.iter() {
+            if self.mesh_buffers.contains_key(id) {
+                continue;
             }
-            self.entities.push(builder.build());
 
-            warn!("actor");
+            let vertices = if mesh.indices.is_empty() {
+                mesh.vertices.clone()
+            } else {
+                mesh.indices
+                    .iter()
+                    .filter_map(|idx| mesh.vertices.get(*idx as usize).copied())
+                    .collect()
+            };
+            let vertices = vertices
+                .into_iter()
+                .map(|position| renderer::Vertex { position })
+                .collect::<Vec<_>>();
+
+            self.mesh_buffers
+                .insert(*id, renderer.acquire_vertex_buffer(vertices)?);
         }
 
         Ok(())
     }
 
-    pub fn unload(&mut self, world: &mut World) -> anyhow::Result<()> {
+    pub fn unload(
+        &mut self,
+        world: &mut World,
+        renderer: &renderer::Renderer,
+    ) -> anyhow::Result<()> {
+        for (_, buffer) in self.mesh_buffers.drain() {
+            renderer.release_vertex_buffer(buffer);
+        }
+
+        self.actors.clear();
+        self.groups.clear();
         for entity in self.entities.drain(0..) {
             world.delete_entity(entity)?;
         }
@@ -59,13 +405,14 @@ impl Scene {
     where
         P: AsRef<Path>,
     {
-        let asset = SceneAsset::default();
-        warn!("TODO: build the scene asset");
+        // TODO: add the actors
+        let asset = SceneBuilder::new(self.id)
+            .clear_color(self.clear_color)
+            .skybox(self.skybox.clone())
+            .build()?;
 
         warn!("TODO: save scene async");
-        let file = File::open(filepath)?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer(writer, &asset)?;
+        asset.save(filepath)?;
 
         Ok(())
     }
@@ -0,0 +1,45 @@
+use specs::prelude::*;
+
+use core::spatial::UniformGrid;
+
+use crate::components::{BoundsComponent, DisabledComponent, TransformComponent};
+use crate::resources::{CollisionEvent, EventChannel};
+
+const GRID_CELL_SIZE: f32 = 10.0;
+
+pub(crate) struct CollisionSystem;
+
+impl<'a> System<'a> for CollisionSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, DisabledComponent>,
+        ReadStorage<'a, TransformComponent>,
+        ReadStorage<'a, BoundsComponent>,
+        Write<'a, EventChannel<CollisionEvent>>,
+    );
+
+    fn run(&mut self, (entities, disabled, transform, bounds, mut events): Self::SystemData) {
+        let boxes = (&entities, &transform, &bounds, !&disabled)
+            .join()
+            .map(|(entity, transform, bounds, _)| {
+                (entity, bounds.transformed(&transform.world_matrix()))
+            })
+            .collect::<Vec<_>>();
+
+        // broad phase
+        let mut grid = UniformGrid::new(GRID_CELL_SIZE);
+        for (idx, (_, aabb)) in boxes.iter().enumerate() {
+            grid.insert(idx, *aabb);
+        }
+
+        for (i, (a, a_box)) in boxes.iter().enumerate() {
+            // only report each pair once
+            for j in grid.query(a_box).into_iter().filter(|j| *j > i) {
+                events.single_write(CollisionEvent {
+                    a: *a,
+                    b: boxes[j].0,
+                });
+            }
+        }
+    }
+}
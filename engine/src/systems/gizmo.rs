@@ -0,0 +1,42 @@
+use specs::prelude::*;
+
+use core::math::{Vector, Vector3};
+
+use crate::components::{SelectedComponent, TransformComponent};
+use crate::resources::{DebugLines, GizmoSettings};
+
+const X_AXIS_COLOR: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+const Y_AXIS_COLOR: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
+const Z_AXIS_COLOR: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+// draws the transform axes of selected entities
+pub(crate) struct GizmoSystem;
+
+impl<'a> System<'a> for GizmoSystem {
+    type SystemData = (
+        Read<'a, GizmoSettings>,
+        Write<'a, DebugLines>,
+        ReadStorage<'a, TransformComponent>,
+        ReadStorage<'a, SelectedComponent>,
+    );
+
+    fn run(&mut self, (settings, mut debug_lines, transform, selected): Self::SystemData) {
+        if !settings.enabled {
+            return;
+        }
+
+        for (transform, _) in (&transform, &selected).join() {
+            let world = transform.world_matrix();
+            let origin = world.transform_point(Vector3::default());
+
+            for (axis, color) in &[
+                (Vector3::right(), X_AXIS_COLOR),
+                (Vector3::up(), Y_AXIS_COLOR),
+                (Vector3::foward(), Z_AXIS_COLOR),
+            ] {
+                let axis = world.transform_vector(*axis).normalized() * settings.size;
+                debug_lines.add_line(origin, origin + axis, *color);
+            }
+        }
+    }
+}
@@ -1,3 +1,11 @@
+mod collision;
+mod gizmo;
+mod movement;
+mod particles;
 mod render;
 
+pub(crate) use collision::*;
+pub(crate) use gizmo::*;
+pub(crate) use movement::*;
+pub(crate) use particles::*;
 pub(crate) use render::*;
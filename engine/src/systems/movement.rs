@@ -0,0 +1,34 @@
+use specs::prelude::*;
+
+use crate::components::{DisabledComponent, TransformComponent, VelocityComponent};
+use crate::resources::DeltaTime;
+
+pub(crate) struct MovementSystem;
+
+impl<'a> System<'a> for MovementSystem {
+    type SystemData = (
+        Read<'a, DeltaTime>,
+        ReadStorage<'a, DisabledComponent>,
+        ReadStorage<'a, VelocityComponent>,
+        WriteStorage<'a, TransformComponent>,
+    );
+
+    fn run(&mut self, (delta, disabled, velocity, mut transform): Self::SystemData) {
+        for (velocity, transform, _) in (&velocity, &mut transform, !&disabled).join() {
+            transform.translate(velocity.0 * delta.0);
+        }
+    }
+}
+
+// runs first each tick so rendering can interpolate from the previous pose
+pub(crate) struct TransformHistorySystem;
+
+impl<'a> System<'a> for TransformHistorySystem {
+    type SystemData = WriteStorage<'a, TransformComponent>;
+
+    fn run(&mut self, mut transform: Self::SystemData) {
+        for transform in (&mut transform).join() {
+            transform.store_previous();
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use specs::prelude::*;
+
+use core::math::Vector3;
+
+use crate::components::{
+    DisabledComponent, Particle, ParticleEmitterComponent, TransformComponent,
+};
+use crate::resources::{DeltaTime, Random};
+
+#[derive(Default)]
+pub(crate) struct ParticleSystem;
+
+impl<'a> System<'a> for ParticleSystem {
+    type SystemData = (
+        Read<'a, DeltaTime>,
+        Write<'a, Random>,
+        ReadStorage<'a, DisabledComponent>,
+        ReadStorage<'a, TransformComponent>,
+        WriteStorage<'a, ParticleEmitterComponent>,
+    );
+
+    fn run(&mut self, (delta, mut random, disabled, transform, mut emitter): Self::SystemData) {
+        let dt = delta.0;
+
+        for (transform, emitter, _) in (&transform, &mut emitter, !&disabled).join() {
+            let lifetime = emitter.lifetime;
+            for particle in emitter.particles.iter_mut() {
+                particle.age += dt;
+                particle.position += particle.velocity * dt;
+            }
+            emitter.particles.retain(|p| p.age < lifetime);
+
+            emitter.spawn_accumulator += emitter.rate * dt;
+            while emitter.spawn_accumulator >= 1.0 {
+                emitter.spawn_accumulator -= 1.0;
+                if emitter.particles.len() >= emitter.max_particles {
+                    continue;
+                }
+
+                let spread = emitter.velocity_spread;
+                let velocity = emitter.velocity
+                    + Vector3 {
+                        x: spread.x * random.next_signed(),
+                        y: spread.y * random.next_signed(),
+                        z: spread.z * random.next_signed(),
+                    };
+
+                emitter.particles.push(Particle {
+                    position: transform.get_position(),
+                    velocity,
+                    age: 0.0,
+                });
+            }
+        }
+    }
+}
@@ -1,16 +1,105 @@
-use log::warn;
 use specs::prelude::*;
 
-use crate::components::TransformComponent;
+use core::math::{Color, Matrix4, Vector, Vector3};
+
+use crate::components::{
+    CameraComponent, DisabledComponent, MaterialComponent, MeshComponent, SpriteComponent,
+    TransformComponent,
+};
+use crate::resources::{InterpolationAlpha, RenderQueue};
+
+// farthest first so blending composites correctly
+pub(crate) fn sort_back_to_front<T>(camera_position: Vector3, draws: &mut [(Vector3, T)]) {
+    draws.sort_by(|a, b| {
+        let a = camera_position.distance_squared(a.0);
+        let b = camera_position.distance_squared(b.0);
+        b.partial_cmp(&a).unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
 
 pub(crate) struct RenderSystem;
 
 impl<'a> System<'a> for RenderSystem {
-    type SystemData = (ReadStorage<'a, TransformComponent>);
+    type SystemData = (
+        Read<'a, InterpolationAlpha>,
+        Write<'a, RenderQueue>,
+        ReadStorage<'a, DisabledComponent>,
+        ReadStorage<'a, CameraComponent>,
+        WriteStorage<'a, TransformComponent>,
+        ReadStorage<'a, MeshComponent>,
+        ReadStorage<'a, MaterialComponent>,
+        ReadStorage<'a, SpriteComponent>,
+    );
+
+    fn run(
+        &mut self,
+        (alpha, mut queue, disabled, camera, mut transform, mesh, material, sprite): Self::SystemData,
+    ) {
+        // only recomputes transforms that changed
+        for transform in (&mut transform).join() {
+            transform.update_world_matrix();
+        }
+
+        let alpha = alpha.0.clamp(0.0, 1.0);
+        let model = |transform: &TransformComponent| {
+            if alpha < 1.0 {
+                transform.interpolated(alpha)
+            } else {
+                transform.world_matrix()
+            }
+        };
+
+        // TODO: support rendering through a specific camera
+        let camera_position = (&camera, &transform, !&disabled)
+            .join()
+            .next()
+            .map(|(_, transform, _)| transform.world_matrix().transform_point(Vector3::default()))
+            .unwrap_or_default();
+
+        queue.clear();
+
+        let mut transparent = Vec::new();
+        for (transform, mesh, material, _) in
+            (&transform, &mesh, material.maybe(), !&disabled).join()
+        {
+            let model = model(transform);
+
+            // meshes without a material draw white
+            let (color, is_transparent) = material.map_or((Color::WHITE, false), |material| {
+                (material.base_color, material.transparent)
+            });
+            let instance = renderer::InstanceData::from_rows(model.m).with_color(color.into());
+
+            if is_transparent {
+                let position = model.transform_point(Vector3::default());
+                transparent.push((position, (mesh.mesh, instance)));
+                continue;
+            }
+
+            queue.add_instance(mesh.mesh, instance);
+        }
+
+        sort_back_to_front(camera_position, &mut transparent);
+        for (_, (mesh, instance)) in transparent {
+            queue.add_transparent(mesh, instance);
+        }
+
+        // sprites are alpha blended too
+        let mut sprites = Vec::new();
+        for (transform, sprite, _) in (&transform, &sprite, !&disabled).join() {
+            let model = model(transform)
+                * Matrix4::from_scale(Vector3 {
+                    x: sprite.size.x,
+                    y: sprite.size.y,
+                    z: 1.0,
+                });
+            let position = model.transform_point(Vector3::default());
+            sprites.push((position, (sprite.texture.as_str(), model)));
+        }
 
-    fn run(&mut self, (transform): Self::SystemData) {
-        for (transform) in (&transform).join() {
-            warn!("render");
+        sort_back_to_front(camera_position, &mut sprites);
+        for (_, (texture, model)) in sprites {
+            queue.add_sprite(texture, model);
         }
     }
 }
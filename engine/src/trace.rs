@@ -0,0 +1,60 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use log::{error, info};
+
+// rows between flushes
+const FLUSH_INTERVAL: u64 = 60;
+
+#[derive(Debug, Default, Copy, Clone)]
+pub(crate) struct FrameTraceRow {
+    pub frame: u64,
+    pub frame_time_ms: f64,
+    pub draw_calls: usize,
+    pub triangles: usize,
+}
+
+// per-frame timings as CSV
+pub(crate) struct FrameTrace {
+    writer: BufWriter<File>,
+    rows: u64,
+}
+
+impl FrameTrace {
+    pub fn create<P>(path: P) -> anyhow::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        info!("Writing frame trace to {}", path.display());
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "frame,frame_time_ms,draw_calls,triangles")?;
+
+        Ok(Self { writer, rows: 0 })
+    }
+
+    pub fn write_row(&mut self, row: &FrameTraceRow) -> anyhow::Result<()> {
+        writeln!(
+            self.writer,
+            "{},{:.3},{},{}",
+            row.frame, row.frame_time_ms, row.draw_calls, row.triangles
+        )?;
+
+        self.rows += 1;
+        if self.rows.is_multiple_of(FLUSH_INTERVAL) {
+            self.writer.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for FrameTrace {
+    fn drop(&mut self) {
+        if let Err(e) = self.writer.flush() {
+            error!("Error flushing frame trace: {}", e);
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::panic::RefUnwindSafe;
+use std::sync::Arc;
+
+use derivative::Derivative;
+
+use crate::{BlendMode, CullMode, DepthMode, FrontFace, PolygonMode, PrimitiveTopology};
+
+// receives the mapped log level, the reporting layer, and the message
+pub type DebugCallbackFn = Arc<dyn Fn(log::Level, &str, &str) + Send + Sync + RefUnwindSafe>;
+
+#[derive(Derivative, Default, Clone)]
+#[derivative(Debug)]
+pub struct RendererConfig {
+    pub validation: bool,
+    pub layers: Vec<String>,
+
+    pub instance_extensions: Vec<String>,
+    pub device_extensions: Vec<String>,
+
+    // max sampler anisotropy, 1 or less disables anisotropic filtering
+    pub anisotropy: f32,
+
+    // 0 uses the renderer default (double buffered)
+    pub frames_in_flight: usize,
+
+    // prefer a high precision swapchain format if the surface has one
+    pub hdr: bool,
+
+    // one graphics queue per entry, priorities are 0..1
+    // empty requests a single queue at the default priority
+    pub graphics_queue_priorities: Vec<f32>,
+
+    // width and height of directional light shadow maps
+    // 0 uses DEFAULT_SHADOW_MAP_SIZE
+    pub shadow_map_size: u32,
+
+    // 3 for triple buffering, clamped to what the surface supports
+    // 0 uses one more than the surface minimum
+    pub swapchain_image_count: u32,
+
+    // called for validation messages in addition to logging them
+    #[derivative(Debug = "ignore")]
+    pub debug_callback: Option<DebugCallbackFn>,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct PipelineConfig {
+    pub topology: PrimitiveTopology,
+    pub polygon_mode: PolygonMode,
+    pub cull_mode: CullMode,
+    pub front_face: FrontFace,
+    pub blend_mode: BlendMode,
+
+    // should match the camera's projection, see DepthMode
+    pub depth_mode: DepthMode,
+}
@@ -0,0 +1,18 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RendererError {
+    // the device was lost and every object created from it is now invalid
+    DeviceLost,
+}
+
+impl fmt::Display for RendererError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RendererError::DeviceLost => write!(f, "Device lost"),
+        }
+    }
+}
+
+impl Error for RendererError {}
@@ -0,0 +1,110 @@
+use anyhow::bail;
+
+// the presented image, written by the final pass
+pub const SWAPCHAIN_ATTACHMENT: &str = "swapchain";
+
+type PassFn<'a, C> = Box<dyn FnMut(&mut C) -> anyhow::Result<()> + 'a>;
+
+struct GraphPass<'a, C> {
+    name: String,
+
+    // attachments read / written by the pass
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+
+    execute: PassFn<'a, C>,
+}
+
+// passes run after every pass that writes one of their inputs,
+// otherwise in the order they were added
+pub struct RenderGraph<'a, C> {
+    passes: Vec<GraphPass<'a, C>>,
+}
+
+impl<'a, C> Default for RenderGraph<'a, C> {
+    fn default() -> Self {
+        Self { passes: Vec::new() }
+    }
+}
+
+impl<'a, C> RenderGraph<'a, C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_pass<F>(&mut self, name: &str, inputs: &[&str], outputs: &[&str], execute: F)
+    where
+        F: FnMut(&mut C) -> anyhow::Result<()> + 'a,
+    {
+        self.passes.push(GraphPass {
+            name: name.to_owned(),
+            inputs: inputs.iter().map(|x| (*x).to_owned()).collect(),
+            outputs: outputs.iter().map(|x| (*x).to_owned()).collect(),
+            execute: Box::new(execute),
+        });
+    }
+
+    pub fn pass_count(&self) -> usize {
+        self.passes.len()
+    }
+
+    // pass names in execution order
+    pub fn execution_order(&self) -> anyhow::Result<Vec<&str>> {
+        Ok(self
+            .sorted()?
+            .into_iter()
+            .map(|idx| self.passes[idx].name.as_str())
+            .collect())
+    }
+
+    pub fn execute(&mut self, context: &mut C) -> anyhow::Result<()> {
+        for idx in self.sorted()? {
+            (self.passes[idx].execute)(context)?;
+        }
+
+        Ok(())
+    }
+
+    // inputs nothing writes are treated as external
+    fn sorted(&self) -> anyhow::Result<Vec<usize>> {
+        let dependencies = self
+            .passes
+            .iter()
+            .enumerate()
+            .map(|(idx, pass)| {
+                self.passes
+                    .iter()
+                    .enumerate()
+                    .filter(|(other, writer)| {
+                        *other != idx && writer.outputs.iter().any(|x| pass.inputs.contains(x))
+                    })
+                    .map(|(other, _)| other)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut order = Vec::with_capacity(self.passes.len());
+        let mut scheduled = vec![false; self.passes.len()];
+        while order.len() < self.passes.len() {
+            let next = (0..self.passes.len()).find(|idx| {
+                !scheduled[*idx] && dependencies[*idx].iter().all(|dep| scheduled[*dep])
+            });
+
+            match next {
+                Some(idx) => {
+                    scheduled[idx] = true;
+                    order.push(idx);
+                }
+                None => {
+                    let remaining = (0..self.passes.len())
+                        .filter(|idx| !scheduled[*idx])
+                        .map(|idx| self.passes[idx].name.as_str())
+                        .collect::<Vec<_>>();
+                    bail!("Render graph cycle between passes {:?}", remaining);
+                }
+            }
+        }
+
+        Ok(order)
+    }
+}
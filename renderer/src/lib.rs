@@ -1,4 +1,9 @@
+mod config;
+mod error;
+mod graph;
+mod mock;
 pub mod shaders;
+mod text;
 mod vulkan;
 
 use std::fmt;
@@ -6,14 +11,26 @@ use std::sync::Arc;
 
 use anyhow::bail;
 use derivative::Derivative;
-use log::info;
-use vulkano::buffer::CpuAccessibleBuffer;
+use log::{info, warn};
+use vulkano::buffer::{BufferAccess, BufferUsage, CpuAccessibleBuffer};
+use vulkano::descriptor::DescriptorSet as VulkanDescriptorSet;
 use vulkano::framebuffer::{FramebufferAbstract, RenderPassAbstract};
-use vulkano::pipeline::GraphicsPipelineAbstract;
+use vulkano::image::ImageViewAccess;
+use vulkano::memory::Content;
+use vulkano::pipeline::shader::EntryPointAbstract;
+use vulkano::pipeline::{ComputePipelineAbstract, GraphicsPipelineAbstract};
 use winit::Window;
 
+pub use config::*;
+pub use error::*;
+pub use graph::*;
+pub use mock::*;
+pub use text::*;
 pub use vulkan::VulkanRendererState;
 
+// lets callers declare their own vertex types
+pub use vulkano::impl_vertex;
+
 pub(crate) fn get_window_dimensions(window: &Window) -> anyhow::Result<[u32; 2]> {
     Ok(if let Some(dimensions) = window.get_inner_size() {
         // convert to physical pixels
@@ -24,21 +41,199 @@ pub(crate) fn get_window_dimensions(window: &Window) -> anyhow::Result<[u32; 2]>
     })
 }
 
+// minimized windows report a zero-area size
+pub(crate) fn is_minimized_dimensions(dimensions: [u32; 2]) -> bool {
+    dimensions[0] == 0 || dimensions[1] == 0
+}
+
+// supported is a mask of power of two sample counts (VkSampleCountFlags),
+// single sampling is always supported
+pub(crate) fn select_sample_count(requested: u32, supported: u32) -> u32 {
+    (0..32)
+        .map(|bit| 1 << bit)
+        .filter(|&samples| samples <= requested && supported & samples != 0)
+        .max()
+        .unwrap_or(1)
+}
+
+// any vertex type declared with impl_vertex!() can be used as a pipeline input
+pub trait VertexLayout: vulkano::pipeline::vertex::Vertex + Send + Sync + 'static {}
+
+impl<T> VertexLayout for T where T: vulkano::pipeline::vertex::Vertex + Send + Sync + 'static {}
+
 #[derive(Default, Debug, Copy, Clone, PartialEq)]
 pub struct Vertex {
     pub position: [f32; 3],
 }
 vulkano::impl_vertex!(Vertex, position);
 
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+}
+vulkano::impl_vertex!(MeshVertex, position, normal, uv);
+
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct LineVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+vulkano::impl_vertex!(LineVertex, position, color);
+
+// per-instance model matrix, stored as columns, and color
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct InstanceData {
+    pub model_col0: [f32; 4],
+    pub model_col1: [f32; 4],
+    pub model_col2: [f32; 4],
+    pub model_col3: [f32; 4],
+    pub color: [f32; 4],
+}
+vulkano::impl_vertex!(
+    InstanceData,
+    model_col0,
+    model_col1,
+    model_col2,
+    model_col3,
+    color
+);
+
+impl InstanceData {
+    // white
+    pub fn from_rows(m: [[f32; 4]; 4]) -> Self {
+        Self {
+            model_col0: [m[0][0], m[1][0], m[2][0], m[3][0]],
+            model_col1: [m[0][1], m[1][1], m[2][1], m[3][1]],
+            model_col2: [m[0][2], m[1][2], m[2][2], m[3][2]],
+            model_col3: [m[0][3], m[1][3], m[2][3], m[3][3]],
+            color: [1.0, 1.0, 1.0, 1.0],
+        }
+    }
+
+    pub fn with_color(self, color: [f32; 4]) -> Self {
+        Self { color, ..self }
+    }
+}
+
 #[derive(Default, Debug, Copy, Clone, PartialEq)]
 pub struct Triangle {
     pub vertices: [Vertex; 3],
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Derivative)]
+#[derivative(Default)]
+pub enum PrimitiveTopology {
+    PointList,
+    LineList,
+    LineStrip,
+    #[derivative(Default)]
+    TriangleList,
+    TriangleStrip,
+    TriangleFan,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Derivative)]
+#[derivative(Default)]
+pub enum PolygonMode {
+    #[derivative(Default)]
+    Fill,
+
+    // requires the fill_mode_non_solid device feature
+    Line,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Derivative)]
+#[derivative(Default)]
+pub enum CullMode {
+    None,
+    #[derivative(Default)]
+    Back,
+    Front,
+}
+
+// winding order of front facing triangles in framebuffer space
+#[derive(Debug, Copy, Clone, PartialEq, Derivative)]
+#[derivative(Default)]
+pub enum FrontFace {
+    #[derivative(Default)]
+    CounterClockwise,
+    Clockwise,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Derivative)]
+#[derivative(Default)]
+pub enum BlendMode {
+    #[derivative(Default)]
+    Opaque,
+
+    // src * src_alpha + dst * (1 - src_alpha), draw back-to-front
+    Alpha,
+
+    // src * src_alpha + dst, order independent
+    Additive,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Derivative)]
+#[derivative(Default)]
+pub enum DepthMode {
+    // near is 0, far is 1, Less compare
+    #[derivative(Default)]
+    Standard,
+
+    // near is 1, far is 0, Greater compare
+    Reversed,
+}
+
+impl DepthMode {
+    // value the depth attachment is cleared to, the far plane
+    pub fn clear_value(self) -> f32 {
+        match self {
+            DepthMode::Standard => 1.0,
+            DepthMode::Reversed => 0.0,
+        }
+    }
+}
+
+// post-process operator mapping scene color to the display range
+#[derive(Debug, Copy, Clone, PartialEq, Derivative)]
+#[derivative(Default)]
+pub enum Tonemap {
+    // clamps
+    #[derivative(Default)]
+    None,
+
+    Reinhard,
+
+    // filmic curve fit
+    Aces,
+}
+
+impl Tonemap {
+    // operator index in the tonemap shader
+    pub fn shader_operator(self) -> u32 {
+        match self {
+            Tonemap::None => 0,
+            Tonemap::Reinhard => 1,
+            Tonemap::Aces => 2,
+        }
+    }
+}
+
+// vertices in the bufferless fullscreen triangle
+pub const FULLSCREEN_TRIANGLE_VERTICES: u32 = 3;
+
+// first word of a SPIR-V module
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+// used when RendererConfig::shadow_map_size is 0
+pub const DEFAULT_SHADOW_MAP_SIZE: u32 = 2048;
+
 #[derive(Derivative)]
 #[derivative(Default)]
 pub enum VertexBuffer {
-    Vulkan(Arc<CpuAccessibleBuffer<[Vertex]>>),
+    Vulkan(Arc<dyn BufferAccess + Send + Sync>),
 
     #[derivative(Default)]
     None,
@@ -53,6 +248,26 @@ impl fmt::Display for VertexBuffer {
     }
 }
 
+// a vertex buffer borrowed from the renderer's pool,
+// hand it back with Renderer::release_vertex_buffer() rather than dropping it
+#[derive(Default)]
+pub struct PooledVertexBuffer {
+    vertex_buffer: VertexBuffer,
+    vertex_count: usize,
+    backing: Option<Arc<CpuAccessibleBuffer<[Vertex]>>>,
+}
+
+impl PooledVertexBuffer {
+    pub fn get_vertex_buffer(&self) -> &VertexBuffer {
+        &self.vertex_buffer
+    }
+
+    // the backing buffer may be larger
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+}
+
 #[derive(Derivative)]
 #[derivative(Default)]
 pub enum RenderPass {
@@ -89,6 +304,49 @@ impl fmt::Display for FrameBuffer {
     }
 }
 
+#[derive(Derivative)]
+#[derivative(Default)]
+pub enum ComputePipeline {
+    Vulkan(Arc<dyn ComputePipelineAbstract + Send + Sync>),
+
+    #[derivative(Default)]
+    None,
+}
+
+impl fmt::Display for ComputePipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ComputePipeline::Vulkan(_) => write!(f, "Vulkan"),
+            ComputePipeline::None => write!(f, "None"),
+        }
+    }
+}
+
+// host visible so results can be read back after a dispatch
+#[derive(Derivative)]
+#[derivative(Default(bound = ""))]
+pub enum StorageBuffer<T>
+where
+    T: 'static,
+{
+    Vulkan(Arc<CpuAccessibleBuffer<[T]>>),
+
+    #[derivative(Default)]
+    None,
+}
+
+impl<T> StorageBuffer<T>
+where
+    T: Content + Clone + Send + Sync + 'static,
+{
+    pub fn read(&self) -> anyhow::Result<Vec<T>> {
+        Ok(match self {
+            StorageBuffer::Vulkan(b) => b.read()?.to_vec(),
+            StorageBuffer::None => Vec::new(),
+        })
+    }
+}
+
 #[derive(Derivative)]
 #[derivative(Default)]
 pub enum RenderPipeline {
@@ -107,10 +365,104 @@ impl fmt::Display for RenderPipeline {
     }
 }
 
+#[derive(Derivative)]
+#[derivative(Default)]
+pub enum DescriptorSet {
+    Vulkan(Arc<dyn VulkanDescriptorSet + Send + Sync>),
+
+    #[derivative(Default)]
+    None,
+}
+
+impl fmt::Display for DescriptorSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DescriptorSet::Vulkan(_) => write!(f, "Vulkan"),
+            DescriptorSet::None => write!(f, "None"),
+        }
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Default)]
+pub enum Texture {
+    Vulkan(Arc<dyn ImageViewAccess + Send + Sync>),
+
+    #[derivative(Default)]
+    None,
+}
+
+impl fmt::Display for Texture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Texture::Vulkan(_) => write!(f, "Vulkan"),
+            Texture::None => write!(f, "None"),
+        }
+    }
+}
+
+// live GPU allocations made through the renderer, sizes are approximate
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct MemoryStats {
+    pub buffer_count: usize,
+    pub buffer_bytes: usize,
+    pub image_count: usize,
+    pub image_bytes: usize,
+}
+
+// small per-draw data, must match the pipeline's push constant block
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct PushConstants {
+    // column-major model * view * projection,
+    // instanced pipelines apply the per-instance model first
+    pub transform: [[f32; 4]; 4],
+}
+
+pub struct DrawItem<'a> {
+    pub render_pipeline: &'a RenderPipeline,
+    pub vertex_buffer: &'a VertexBuffer,
+
+    // None draws without any bound descriptor sets
+    pub descriptor_set: &'a DescriptorSet,
+
+    // per-instance data, requires an instanced pipeline
+    pub instance_buffer: Option<&'a VertexBuffer>,
+
+    // required by pipelines with a push constant block
+    pub push_constants: Option<PushConstants>,
+}
+
+// depth rendered from a light's point of view, sampled by the instanced pipelines
+#[derive(Default)]
+pub struct ShadowMap {
+    pub texture: Texture,
+    pub frame_buffer: FrameBuffer,
+
+    // width and height in texels
+    pub size: u32,
+}
+
+// depth-only draws into a shadow map, rendered before the scene
+pub struct ShadowPass<'a> {
+    pub shadow_map: &'a ShadowMap,
+    pub draw_items: &'a [DrawItem<'a>],
+}
+
+// draws FULLSCREEN_TRIANGLE_VERTICES in the render pass's post-process subpass
+pub struct PostProcessItem<'a> {
+    pub render_pipeline: &'a RenderPipeline,
+
+    // one per frame buffer, in the same order
+    pub descriptor_sets: &'a [DescriptorSet],
+}
+
 #[derive(Debug, Derivative)]
 #[derivative(Default)]
 pub enum Renderer {
-    Vulkan(VulkanRendererState),
+    Vulkan(Box<VulkanRendererState>),
+
+    Mock(MockRendererState),
 
     #[derivative(Default)]
     None,
@@ -122,23 +474,41 @@ impl Renderer {
     pub fn get_window(&self) -> anyhow::Result<&Window> {
         Ok(match self {
             Renderer::Vulkan(r) => r.get_window(),
-            Renderer::None => bail!("No window"),
+            Renderer::Mock(_) | Renderer::None => bail!("No window"),
         })
     }
 
     //#endregion
 
+    // there's nothing to render to while minimized
+    pub fn is_minimized(&self) -> bool {
+        match self.get_window() {
+            Ok(window) => get_window_dimensions(window)
+                .map(is_minimized_dimensions)
+                .unwrap_or(false),
+            Err(_) => false,
+        }
+    }
+
+    // no window or GPU device backing the renderer
+    pub fn is_headless(&self) -> bool {
+        match self {
+            Renderer::Vulkan(_) => false,
+            Renderer::Mock(_) | Renderer::None => true,
+        }
+    }
+
     fn init_viewport(&mut self) {
         match self {
             Renderer::Vulkan(r) => r.init_viewport(),
-            Renderer::None => (),
+            Renderer::Mock(_) | Renderer::None => (),
         }
     }
 
     pub fn recreate_swapchain(&mut self) -> anyhow::Result<bool> {
         Ok(match self {
             Renderer::Vulkan(r) => r.recreate_swapchain()?,
-            Renderer::None => true,
+            Renderer::Mock(_) | Renderer::None => true,
         })
     }
 
@@ -148,105 +518,1239 @@ impl Renderer {
     where
         V: Into<Vec<Vertex>>,
     {
+        let vertices = vertices.into();
+
         Ok(match self {
-            Renderer::Vulkan(r) => VertexBuffer::Vulkan(r.create_cpu_buffer_iter(vertices)?),
+            Renderer::Vulkan(r) => VertexBuffer::Vulkan(
+                r.create_cpu_buffer_iter(vertices, BufferUsage::vertex_buffer())?,
+            ),
+            Renderer::Mock(r) => {
+                r.record(MockCall::CreateVertexBuffer {
+                    vertex_count: vertices.len(),
+                });
+                VertexBuffer::None
+            }
             Renderer::None => VertexBuffer::None,
         })
     }
 
-    //#endregion
-
-    //#region Shaders
-
-    // TODO: probably have to customize this so we have a trait to genericize against
-
-    pub fn load_simple_shader(
+    // vertices are pairs of line end points
+    pub fn create_line_vertex_buffer<V>(
         &self,
-    ) -> anyhow::Result<(shaders::simple::vs::Shader, shaders::simple::fs::Shader)> {
-        info!("Loading simple shaders...");
+        vertices: V,
+        color: [f32; 4],
+    ) -> anyhow::Result<VertexBuffer>
+    where
+        V: Into<Vec<[f32; 3]>>,
+    {
+        self.create_colored_line_vertex_buffer(
+            vertices
+                .into()
+                .into_iter()
+                .map(|position| LineVertex { position, color })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    pub fn create_colored_line_vertex_buffer<V>(&self, vertices: V) -> anyhow::Result<VertexBuffer>
+    where
+        V: Into<Vec<LineVertex>>,
+    {
+        let vertices = vertices.into();
 
         Ok(match self {
-            Renderer::Vulkan(r) => (
-                shaders::simple::vs::Shader::load(r.get_device().clone())?,
-                shaders::simple::fs::Shader::load(r.get_device().clone())?,
+            Renderer::Vulkan(r) => VertexBuffer::Vulkan(
+                r.create_cpu_buffer_iter(vertices, BufferUsage::vertex_buffer())?,
             ),
-            Renderer::None => bail!("Shaders not supported"),
+            Renderer::Mock(r) => {
+                r.record(MockCall::CreateVertexBuffer {
+                    vertex_count: vertices.len(),
+                });
+                VertexBuffer::None
+            }
+            Renderer::None => VertexBuffer::None,
         })
     }
 
     //#endregion
 
-    //#region Render Pass
+    //#region GPU Buffers
 
-    pub fn create_simple_render_pass(&self) -> anyhow::Result<RenderPass> {
-        info!("Creating simple render pass...");
+    // for static vertex data, uploaded once through a staging buffer
+    pub fn create_device_local_vertex_buffer<V>(&self, vertices: V) -> anyhow::Result<VertexBuffer>
+    where
+        V: Into<Vec<Vertex>>,
+    {
+        let vertices = vertices.into();
 
         Ok(match self {
-            Renderer::Vulkan(r) => r.create_simple_render_pass()?,
-            Renderer::None => RenderPass::None,
+            Renderer::Vulkan(r) => VertexBuffer::Vulkan(
+                r.create_device_local_buffer_iter(vertices, BufferUsage::vertex_buffer())?,
+            ),
+            Renderer::Mock(r) => {
+                r.record(MockCall::CreateVertexBuffer {
+                    vertex_count: vertices.len(),
+                });
+                VertexBuffer::None
+            }
+            Renderer::None => VertexBuffer::None,
         })
     }
 
-    //#endregion
+    // glyph quads from FontAtlas::layout_text(), to be drawn with the font atlas bound
+    pub fn create_text_vertex_buffer<V>(&self, vertices: V) -> anyhow::Result<VertexBuffer>
+    where
+        V: Into<Vec<TextVertex>>,
+    {
+        let vertices = vertices.into();
 
-    //#region Frame Buffers
+        Ok(match self {
+            Renderer::Vulkan(r) => VertexBuffer::Vulkan(
+                r.create_cpu_buffer_iter(vertices, BufferUsage::vertex_buffer())?,
+            ),
+            Renderer::Mock(r) => {
+                r.record(MockCall::DrawText {
+                    glyph_count: vertices.len() / 6,
+                });
+                VertexBuffer::None
+            }
+            Renderer::None => VertexBuffer::None,
+        })
+    }
 
-    pub fn create_frame_buffers(
-        &mut self,
-        render_pass: &RenderPass,
-    ) -> anyhow::Result<Vec<FrameBuffer>> {
-        info!("Creating frame buffers...");
+    // unit cube, drawn from the inside
+    pub fn create_skybox_vertex_buffer(&self) -> anyhow::Result<VertexBuffer> {
+        const FACES: [[[f32; 3]; 4]; 6] = [
+            [
+                [1.0, -1.0, -1.0],
+                [1.0, -1.0, 1.0],
+                [1.0, 1.0, 1.0],
+                [1.0, 1.0, -1.0],
+            ],
+            [
+                [-1.0, -1.0, 1.0],
+                [-1.0, -1.0, -1.0],
+                [-1.0, 1.0, -1.0],
+                [-1.0, 1.0, 1.0],
+            ],
+            [
+                [-1.0, 1.0, -1.0],
+                [1.0, 1.0, -1.0],
+                [1.0, 1.0, 1.0],
+                [-1.0, 1.0, 1.0],
+            ],
+            [
+                [-1.0, -1.0, 1.0],
+                [1.0, -1.0, 1.0],
+                [1.0, -1.0, -1.0],
+                [-1.0, -1.0, -1.0],
+            ],
+            [
+                [-1.0, -1.0, 1.0],
+                [-1.0, 1.0, 1.0],
+                [1.0, 1.0, 1.0],
+                [1.0, -1.0, 1.0],
+            ],
+            [
+                [1.0, -1.0, -1.0],
+                [1.0, 1.0, -1.0],
+                [-1.0, 1.0, -1.0],
+                [-1.0, -1.0, -1.0],
+            ],
+        ];
 
-        self.init_viewport();
+        let vertices = FACES
+            .iter()
+            .flat_map(|face| {
+                [face[0], face[1], face[2], face[0], face[2], face[3]]
+                    .iter()
+                    .map(|position| Vertex {
+                        position: *position,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
 
-        Ok(match self {
-            Renderer::Vulkan(r) => r.create_frame_buffers(render_pass)?,
-            Renderer::None => Vec::new(),
-        })
+        self.create_vertex_buffer(vertices)
     }
 
-    //#endregion
+    // unit quad in the XY plane, centered on the origin, counter-clockwise
+    pub fn create_sprite_vertex_buffer(&self) -> anyhow::Result<VertexBuffer> {
+        // uv 0, 0 is the top-left of the texture
+        let corners = [
+            ([-0.5, -0.5], [0.0, 1.0]),
+            ([0.5, -0.5], [1.0, 1.0]),
+            ([0.5, 0.5], [1.0, 0.0]),
+            ([-0.5, 0.5], [0.0, 0.0]),
+        ];
 
-    //#region Render Pipeline
+        let vertices = [0, 1, 2, 0, 2, 3]
+            .iter()
+            .map(|idx| {
+                let (position, uv) = corners[*idx];
+                MeshVertex {
+                    position: [position[0], position[1], 0.0],
+                    normal: [0.0, 0.0, 1.0],
+                    uv,
+                }
+            })
+            .collect::<Vec<_>>();
 
-    pub fn create_simple_render_pipeline(
-        &self,
-        render_pass: &RenderPass,
-        vs: shaders::simple::vs::Shader,
-        fs: shaders::simple::fs::Shader,
-    ) -> anyhow::Result<RenderPipeline> {
-        info!("Creating simple pipeline...");
+        Ok(match self {
+            Renderer::Vulkan(r) => VertexBuffer::Vulkan(
+                r.create_cpu_buffer_iter(vertices, BufferUsage::vertex_buffer())?,
+            ),
+            Renderer::Mock(r) => {
+                r.record(MockCall::CreateVertexBuffer {
+                    vertex_count: vertices.len(),
+                });
+                VertexBuffer::None
+            }
+            Renderer::None => VertexBuffer::None,
+        })
+    }
+
+    pub fn create_instance_buffer<V>(&self, instances: V) -> anyhow::Result<VertexBuffer>
+    where
+        V: Into<Vec<InstanceData>>,
+    {
+        let instances = instances.into();
 
         Ok(match self {
-            Renderer::Vulkan(r) => r.create_simple_render_pipeline(render_pass, vs, fs)?,
-            Renderer::None => RenderPipeline::None,
+            Renderer::Vulkan(r) => VertexBuffer::Vulkan(
+                r.create_cpu_buffer_iter(instances, BufferUsage::vertex_buffer())?,
+            ),
+            Renderer::Mock(r) => {
+                r.record(MockCall::CreateInstanceBuffer {
+                    instance_count: instances.len(),
+                });
+                VertexBuffer::None
+            }
+            Renderer::None => VertexBuffer::None,
         })
     }
 
     //#endregion
 
-    pub fn begin_frame(&mut self) {
-        match self {
-            Renderer::Vulkan(r) => r.begin_frame(),
-            Renderer::None => (),
-        }
-    }
+    //#region Textures
+
+    // data is an encoded image
+    pub fn create_texture(&self, data: &[u8]) -> anyhow::Result<Texture> {
+        let image = image::load_from_memory(data)?.to_rgba();
+        let (width, height) = image.dimensions();
 
-    pub fn draw_data<F>(
-        &mut self,
-        render_pipeline: &RenderPipeline,
-        clear_values: [f32; 4],
-        draw_data: &VertexBuffer,
-        frame_buffers: F,
-    ) -> anyhow::Result<bool>
-    where
-        F: AsRef<Vec<FrameBuffer>>,
-    {
         Ok(match self {
             Renderer::Vulkan(r) => {
-                r.draw_data(render_pipeline, clear_values, draw_data, frame_buffers)?
+                Texture::Vulkan(r.create_texture(width, height, image.into_raw())?)
             }
-            Renderer::None => false,
+            Renderer::Mock(r) => {
+                r.record(MockCall::CreateTexture { array_layers: 1 });
+                Texture::None
+            }
+            Renderer::None => Texture::None,
         })
     }
+
+    // faces are encoded images in +X, -X, +Y, -Y, +Z, -Z order
+    pub fn create_cubemap(&self, faces: &[Vec<u8>]) -> anyhow::Result<Texture> {
+        if faces.len() != 6 {
+            bail!("Cubemaps require 6 faces, got {}", faces.len());
+        }
+
+        let mut size = 0;
+        let mut data = Vec::new();
+        for (idx, face) in faces.iter().enumerate() {
+            let image = image::load_from_memory(face)?.to_rgba();
+            let (width, height) = image.dimensions();
+            if width != height {
+                bail!("Cubemap face {} is not square ({}x{})", idx, width, height);
+            }
+
+            if idx == 0 {
+                size = width;
+            } else if width != size {
+                bail!("Cubemap face {} size {} doesn't match {}", idx, width, size);
+            }
+
+            data.extend(image.into_raw());
+        }
+
+        Ok(match self {
+            Renderer::Vulkan(r) => Texture::Vulkan(r.create_cubemap(size, data)?),
+            Renderer::Mock(r) => {
+                r.record(MockCall::CreateTexture { array_layers: 6 });
+                Texture::None
+            }
+            Renderer::None => Texture::None,
+        })
+    }
+
+    //#endregion
+
+    //#region Shaders
+
+    // TODO: probably have to customize this so we have a trait to genericize against
+
+    pub fn load_simple_shader(
+        &self,
+    ) -> anyhow::Result<(shaders::simple::vs::Shader, shaders::simple::fs::Shader)> {
+        info!("Loading simple shaders...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => (
+                shaders::simple::vs::Shader::load(r.get_device().clone())?,
+                shaders::simple::fs::Shader::load(r.get_device().clone())?,
+            ),
+            Renderer::Mock(_) | Renderer::None => bail!("Shaders not supported"),
+        })
+    }
+
+    // compiled from the simple shader source at runtime (glslc), for hot reloading
+    // the interface (inputs, outputs, push constants) must not change
+    pub fn load_simple_shader_spirv(
+        &self,
+        vs: &[u8],
+        fs: &[u8],
+    ) -> anyhow::Result<(shaders::simple::vs::Shader, shaders::simple::fs::Shader)> {
+        info!("Loading simple shaders from SPIR-V...");
+
+        // ShaderModule::new only checks the alignment
+        for spirv in [vs, fs].iter() {
+            if spirv.len() < 4 || spirv[..4] != SPIRV_MAGIC.to_le_bytes() {
+                bail!("Invalid SPIR-V");
+            }
+        }
+
+        Ok(match self {
+            Renderer::Vulkan(r) => unsafe {
+                (
+                    shaders::simple::vs::Shader::from_spirv(r.get_device().clone(), vs)?,
+                    shaders::simple::fs::Shader::from_spirv(r.get_device().clone(), fs)?,
+                )
+            },
+            Renderer::Mock(_) | Renderer::None => bail!("Shaders not supported"),
+        })
+    }
+
+    pub fn load_line_shader(
+        &self,
+    ) -> anyhow::Result<(shaders::line::vs::Shader, shaders::line::fs::Shader)> {
+        info!("Loading line shaders...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => (
+                shaders::line::vs::Shader::load(r.get_device().clone())?,
+                shaders::line::fs::Shader::load(r.get_device().clone())?,
+            ),
+            Renderer::Mock(_) | Renderer::None => bail!("Shaders not supported"),
+        })
+    }
+
+    pub fn load_instanced_shader(
+        &self,
+    ) -> anyhow::Result<(
+        shaders::instanced::vs::Shader,
+        shaders::instanced::fs::Shader,
+    )> {
+        info!("Loading instanced shaders...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => (
+                shaders::instanced::vs::Shader::load(r.get_device().clone())?,
+                shaders::instanced::fs::Shader::load(r.get_device().clone())?,
+            ),
+            Renderer::Mock(_) | Renderer::None => bail!("Shaders not supported"),
+        })
+    }
+
+    pub fn load_skybox_shader(
+        &self,
+    ) -> anyhow::Result<(shaders::skybox::vs::Shader, shaders::skybox::fs::Shader)> {
+        info!("Loading skybox shaders...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => (
+                shaders::skybox::vs::Shader::load(r.get_device().clone())?,
+                shaders::skybox::fs::Shader::load(r.get_device().clone())?,
+            ),
+            Renderer::Mock(_) | Renderer::None => bail!("Shaders not supported"),
+        })
+    }
+
+    pub fn load_sprite_shader(
+        &self,
+    ) -> anyhow::Result<(shaders::sprite::vs::Shader, shaders::sprite::fs::Shader)> {
+        info!("Loading sprite shaders...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => (
+                shaders::sprite::vs::Shader::load(r.get_device().clone())?,
+                shaders::sprite::fs::Shader::load(r.get_device().clone())?,
+            ),
+            Renderer::Mock(_) | Renderer::None => bail!("Shaders not supported"),
+        })
+    }
+
+    pub fn load_text_shader(
+        &self,
+    ) -> anyhow::Result<(shaders::text::vs::Shader, shaders::text::fs::Shader)> {
+        info!("Loading text shaders...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => (
+                shaders::text::vs::Shader::load(r.get_device().clone())?,
+                shaders::text::fs::Shader::load(r.get_device().clone())?,
+            ),
+            Renderer::Mock(_) | Renderer::None => bail!("Shaders not supported"),
+        })
+    }
+
+    pub fn load_tonemap_shader(
+        &self,
+    ) -> anyhow::Result<(shaders::tonemap::vs::Shader, shaders::tonemap::fs::Shader)> {
+        info!("Loading tonemap shaders...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => (
+                shaders::tonemap::vs::Shader::load(r.get_device().clone())?,
+                shaders::tonemap::fs::Shader::load(r.get_device().clone())?,
+            ),
+            Renderer::Mock(_) | Renderer::None => bail!("Shaders not supported"),
+        })
+    }
+
+    pub fn load_shadow_shader(
+        &self,
+    ) -> anyhow::Result<(shaders::shadow::vs::Shader, shaders::shadow::fs::Shader)> {
+        info!("Loading shadow shaders...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => (
+                shaders::shadow::vs::Shader::load(r.get_device().clone())?,
+                shaders::shadow::fs::Shader::load(r.get_device().clone())?,
+            ),
+            Renderer::Mock(_) | Renderer::None => bail!("Shaders not supported"),
+        })
+    }
+
+    //#endregion
+
+    //#region Render Pass
+
+    pub fn create_simple_render_pass(&self) -> anyhow::Result<RenderPass> {
+        info!("Creating simple render pass...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => r.create_simple_render_pass()?,
+            Renderer::Mock(_) | Renderer::None => RenderPass::None,
+        })
+    }
+
+    // depth only, leaves the depth ready to be sampled
+    pub fn create_shadow_render_pass(&self) -> anyhow::Result<RenderPass> {
+        info!("Creating shadow render pass...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => r.create_shadow_render_pass()?,
+            Renderer::Mock(_) | Renderer::None => RenderPass::None,
+        })
+    }
+
+    // pipelines drawn by render_to_image() must be created against this
+    pub fn create_offscreen_render_pass(&self, samples: u32) -> anyhow::Result<RenderPass> {
+        info!("Creating offscreen render pass...");
+
+        let samples = self.supported_sample_count(samples);
+        Ok(match self {
+            Renderer::Vulkan(r) => r.create_offscreen_render_pass(samples)?,
+            Renderer::Mock(_) | Renderer::None => RenderPass::None,
+        })
+    }
+
+    //#endregion
+
+    //#region Frame Buffers
+
+    pub fn create_frame_buffers(
+        &mut self,
+        render_pass: &RenderPass,
+    ) -> anyhow::Result<Vec<FrameBuffer>> {
+        info!("Creating frame buffers...");
+
+        self.init_viewport();
+
+        Ok(match self {
+            Renderer::Vulkan(r) => r.create_frame_buffers(render_pass)?,
+            Renderer::Mock(_) | Renderer::None => Vec::new(),
+        })
+    }
+
+    // size x size depth image with a frame buffer for the shadow render pass
+    pub fn create_shadow_map(
+        &self,
+        render_pass: &RenderPass,
+        size: u32,
+    ) -> anyhow::Result<ShadowMap> {
+        if size == 0 {
+            bail!("Invalid shadow map size {}", size);
+        }
+
+        info!("Creating {}x{} shadow map...", size, size);
+
+        Ok(match self {
+            Renderer::Vulkan(r) => r.create_shadow_map(render_pass, size)?,
+            Renderer::Mock(r) => {
+                r.record(MockCall::CreateDepthImage {
+                    dimensions: [size, size],
+                });
+                ShadowMap {
+                    size,
+                    ..Default::default()
+                }
+            }
+            Renderer::None => ShadowMap::default(),
+        })
+    }
+
+    //#endregion
+
+    //#region Render Pipeline
+
+    pub fn create_simple_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::simple::vs::Shader,
+        fs: shaders::simple::fs::Shader,
+    ) -> anyhow::Result<RenderPipeline> {
+        info!("Creating simple pipeline...");
+
+        self.create_render_pipeline(render_pass, vs, fs, &PipelineConfig::default())
+    }
+
+    pub fn create_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::simple::vs::Shader,
+        fs: shaders::simple::fs::Shader,
+        config: &PipelineConfig,
+    ) -> anyhow::Result<RenderPipeline> {
+        self.create_vertex_render_pipeline::<Vertex>(render_pass, vs, fs, config)
+    }
+
+    // vertex attributes the shaders don't use are ignored
+    pub fn create_vertex_render_pipeline<V>(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::simple::vs::Shader,
+        fs: shaders::simple::fs::Shader,
+        config: &PipelineConfig,
+    ) -> anyhow::Result<RenderPipeline>
+    where
+        V: VertexLayout,
+    {
+        info!(
+            "Creating pipeline {:?} for {}...",
+            config,
+            std::any::type_name::<V>()
+        );
+
+        Ok(match self {
+            Renderer::Vulkan(r) => r.create_render_pipeline::<V>(render_pass, vs, fs, config)?,
+            Renderer::Mock(_) | Renderer::None => RenderPipeline::None,
+        })
+    }
+
+    pub fn create_line_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::line::vs::Shader,
+        fs: shaders::line::fs::Shader,
+    ) -> anyhow::Result<RenderPipeline> {
+        info!("Creating line pipeline...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => r.create_line_render_pipeline(render_pass, vs, fs)?,
+            Renderer::Mock(_) | Renderer::None => RenderPipeline::None,
+        })
+    }
+
+    // depth_mode should match the camera the skybox is drawn with
+    pub fn create_skybox_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::skybox::vs::Shader,
+        fs: shaders::skybox::fs::Shader,
+        depth_mode: DepthMode,
+    ) -> anyhow::Result<RenderPipeline> {
+        info!("Creating skybox pipeline...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => {
+                r.create_skybox_render_pipeline(render_pass, vs, fs, depth_mode)?
+            }
+            Renderer::Mock(_) | Renderer::None => RenderPipeline::None,
+        })
+    }
+
+    // alpha blended, draws the sprite vertex buffer with a texture descriptor set
+    pub fn create_sprite_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::sprite::vs::Shader,
+        fs: shaders::sprite::fs::Shader,
+    ) -> anyhow::Result<RenderPipeline> {
+        info!("Creating sprite pipeline...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => r.create_sprite_render_pipeline(render_pass, vs, fs)?,
+            Renderer::Mock(_) | Renderer::None => RenderPipeline::None,
+        })
+    }
+
+    pub fn create_text_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::text::vs::Shader,
+        fs: shaders::text::fs::Shader,
+    ) -> anyhow::Result<RenderPipeline> {
+        info!("Creating text pipeline...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => r.create_text_render_pipeline(render_pass, vs, fs)?,
+            Renderer::Mock(_) | Renderer::None => RenderPipeline::None,
+        })
+    }
+
+    // draws FULLSCREEN_TRIANGLE_VERTICES without a vertex buffer
+    pub fn create_tonemap_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::tonemap::vs::Shader,
+        fs: shaders::tonemap::fs::Shader,
+    ) -> anyhow::Result<RenderPipeline> {
+        info!("Creating tonemap pipeline...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => r.create_tonemap_render_pipeline(render_pass, vs, fs)?,
+            Renderer::Mock(_) | Renderer::None => RenderPipeline::None,
+        })
+    }
+
+    // reads the scene color of each frame buffer,
+    // recreate these whenever the frame buffers are
+    pub fn create_tonemap_descriptor_sets(
+        &self,
+        render_pipeline: &RenderPipeline,
+        tonemap: Tonemap,
+    ) -> anyhow::Result<Vec<DescriptorSet>> {
+        Ok(match self {
+            Renderer::Vulkan(r) => r.create_tonemap_descriptor_sets(render_pipeline, tonemap)?,
+            Renderer::Mock(_) | Renderer::None => Vec::new(),
+        })
+    }
+
+    // view_projection is column-major and should not include the camera translation
+    pub fn create_skybox_descriptor_set(
+        &mut self,
+        render_pipeline: &RenderPipeline,
+        cubemap: &Texture,
+        view_projection: [[f32; 4]; 4],
+    ) -> anyhow::Result<DescriptorSet> {
+        Ok(match self {
+            Renderer::Vulkan(r) => {
+                r.create_skybox_descriptor_set(render_pipeline, cubemap, view_projection)?
+            }
+            Renderer::Mock(_) | Renderer::None => DescriptorSet::None,
+        })
+    }
+
+    // for the instanced pipelines, view_projection is the light's and column-major
+    pub fn create_shadow_descriptor_set(
+        &mut self,
+        render_pipeline: &RenderPipeline,
+        shadow_map: &ShadowMap,
+        view_projection: [[f32; 4]; 4],
+        bias: f32,
+    ) -> anyhow::Result<DescriptorSet> {
+        Ok(match self {
+            Renderer::Vulkan(r) => {
+                r.create_shadow_descriptor_set(render_pipeline, shadow_map, view_projection, bias)?
+            }
+            Renderer::Mock(_) | Renderer::None => DescriptorSet::None,
+        })
+    }
+
+    // binds the texture to set 0, binding 0
+    pub fn create_texture_descriptor_set(
+        &self,
+        render_pipeline: &RenderPipeline,
+        texture: &Texture,
+    ) -> anyhow::Result<DescriptorSet> {
+        Ok(match self {
+            Renderer::Vulkan(r) => r.create_texture_descriptor_set(render_pipeline, texture)?,
+            Renderer::Mock(_) | Renderer::None => DescriptorSet::None,
+        })
+    }
+
+    pub fn create_instanced_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::instanced::vs::Shader,
+        fs: shaders::instanced::fs::Shader,
+        config: &PipelineConfig,
+    ) -> anyhow::Result<RenderPipeline> {
+        info!("Creating instanced pipeline {:?}...", config);
+
+        Ok(match self {
+            Renderer::Vulkan(r) => {
+                r.create_instanced_render_pipeline(render_pass, vs, fs, config)?
+            }
+            Renderer::Mock(_) | Renderer::None => RenderPipeline::None,
+        })
+    }
+
+    // same vertex layout as the instanced pipelines, push constants are the light's transform
+    pub fn create_shadow_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::shadow::vs::Shader,
+        fs: shaders::shadow::fs::Shader,
+    ) -> anyhow::Result<RenderPipeline> {
+        info!("Creating shadow pipeline...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => r.create_shadow_render_pipeline(render_pass, vs, fs)?,
+            Renderer::Mock(_) | Renderer::None => RenderPipeline::None,
+        })
+    }
+
+    //#endregion
+
+    pub fn begin_frame(&mut self) {
+        match self {
+            Renderer::Vulkan(r) => r.begin_frame(),
+            Renderer::Mock(_) | Renderer::None => (),
+        }
+    }
+
+    pub fn frames_in_flight(&self) -> usize {
+        match self {
+            Renderer::Vulkan(r) => r.frames_in_flight(),
+            Renderer::Mock(_) | Renderer::None => 0,
+        }
+    }
+
+    // the swapchain image acquired for the most recent frame
+    pub fn current_swapchain_image(&self) -> Option<usize> {
+        match self {
+            Renderer::Vulkan(r) => Some(r.current_swapchain_image()),
+            Renderer::Mock(_) | Renderer::None => None,
+        }
+    }
+
+    pub fn wait_idle(&mut self) -> anyhow::Result<()> {
+        match self {
+            Renderer::Vulkan(r) => r.wait_idle(),
+            Renderer::Mock(_) | Renderer::None => Ok(()),
+        }
+    }
+
+    // forces cached command buffers to be re-recorded on the next draw
+    pub fn set_scene_dirty(&mut self) {
+        match self {
+            Renderer::Vulkan(r) => r.set_command_buffers_dirty(),
+            Renderer::Mock(_) | Renderer::None => (),
+        }
+    }
+
+    pub fn recorded_command_buffer_count(&self) -> u64 {
+        match self {
+            Renderer::Vulkan(r) => r.recorded_command_buffer_count(),
+            Renderer::Mock(_) | Renderer::None => 0,
+        }
+    }
+
+    //#region Buffer Pool
+
+    // reuses a pooled buffer of the same size class if one is free
+    pub fn acquire_vertex_buffer<V>(&self, vertices: V) -> anyhow::Result<PooledVertexBuffer>
+    where
+        V: Into<Vec<Vertex>>,
+    {
+        let vertices = vertices.into();
+
+        Ok(match self {
+            Renderer::Vulkan(r) => {
+                let backing = r.acquire_pooled_buffer(&vertices)?;
+                PooledVertexBuffer {
+                    vertex_buffer: VertexBuffer::Vulkan(VulkanRendererState::pooled_buffer_slice(
+                        &backing,
+                        vertices.len(),
+                    )?),
+                    vertex_count: vertices.len(),
+                    backing: Some(backing),
+                }
+            }
+            Renderer::Mock(r) => {
+                r.record(MockCall::CreateVertexBuffer {
+                    vertex_count: vertices.len(),
+                });
+                PooledVertexBuffer {
+                    vertex_count: vertices.len(),
+                    ..PooledVertexBuffer::default()
+                }
+            }
+            Renderer::None => PooledVertexBuffer::default(),
+        })
+    }
+
+    // the GPU must be finished with the buffer
+    pub fn release_vertex_buffer(&self, buffer: PooledVertexBuffer) {
+        if let (Renderer::Vulkan(r), Some(backing)) = (self, buffer.backing) {
+            r.release_pooled_buffer(backing);
+        }
+    }
+
+    pub fn pooled_vertex_buffer_count(&self) -> usize {
+        match self {
+            Renderer::Vulkan(r) => r.pooled_buffer_count(),
+            Renderer::Mock(_) | Renderer::None => 0,
+        }
+    }
+
+    //#endregion
+
+    pub fn graphics_queue_count(&self) -> usize {
+        match self {
+            Renderer::Vulkan(r) => r.graphics_queue_count(),
+            Renderer::Mock(_) | Renderer::None => 0,
+        }
+    }
+
+    pub fn memory_stats(&self) -> MemoryStats {
+        match self {
+            Renderer::Vulkan(r) => r.memory_stats(),
+            Renderer::Mock(_) | Renderer::None => MemoryStats::default(),
+        }
+    }
+
+    //#region Debug Names
+
+    // names are no-ops without VK_EXT_debug_utils
+    pub fn has_debug_names(&self) -> bool {
+        match self {
+            Renderer::Vulkan(r) => r.has_debug_names(),
+            Renderer::Mock(_) | Renderer::None => false,
+        }
+    }
+
+    pub fn set_vertex_buffer_name(&self, vertex_buffer: &VertexBuffer, name: &str) {
+        if let (Renderer::Vulkan(r), VertexBuffer::Vulkan(buffer)) = (self, vertex_buffer) {
+            r.set_buffer_debug_name(buffer, name);
+        }
+    }
+
+    pub fn set_texture_name(&self, texture: &Texture, name: &str) {
+        if let (Renderer::Vulkan(r), Texture::Vulkan(image)) = (self, texture) {
+            r.set_image_debug_name(image, name);
+        }
+    }
+
+    pub fn set_render_pipeline_name(&self, pipeline: &RenderPipeline, name: &str) {
+        if let (Renderer::Vulkan(r), RenderPipeline::Vulkan(pipeline)) = (self, pipeline) {
+            r.set_pipeline_debug_name(pipeline, name);
+        }
+    }
+
+    //#endregion
+
+    //#region Compute
+
+    pub fn create_compute_pipeline<Cs>(&self, shader: &Cs) -> anyhow::Result<ComputePipeline>
+    where
+        Cs: EntryPointAbstract<SpecializationConstants = ()>,
+        Cs::PipelineLayout: Clone + Send + Sync + 'static,
+    {
+        info!("Creating compute pipeline...");
+
+        Ok(match self {
+            Renderer::Vulkan(r) => r.create_compute_pipeline(shader)?,
+            Renderer::Mock(_) | Renderer::None => ComputePipeline::None,
+        })
+    }
+
+    pub fn create_storage_buffer<V, T>(&self, data: V) -> anyhow::Result<StorageBuffer<T>>
+    where
+        V: Into<Vec<T>>,
+        T: Content + Clone + Send + Sync + 'static,
+    {
+        Ok(match self {
+            Renderer::Vulkan(r) => StorageBuffer::Vulkan(r.create_cpu_buffer_iter(
+                data,
+                BufferUsage {
+                    storage_buffer: true,
+                    transfer_destination: true,
+                    ..BufferUsage::none()
+                },
+            )?),
+            Renderer::Mock(_) | Renderer::None => StorageBuffer::None,
+        })
+    }
+
+    // binds the buffer to set 0, binding 0
+    pub fn create_storage_descriptor_set<T>(
+        &self,
+        compute_pipeline: &ComputePipeline,
+        buffer: &StorageBuffer<T>,
+    ) -> anyhow::Result<DescriptorSet>
+    where
+        T: Content + Send + Sync + 'static,
+    {
+        Ok(match self {
+            Renderer::Vulkan(r) => r.create_storage_descriptor_set(compute_pipeline, buffer)?,
+            Renderer::Mock(_) | Renderer::None => DescriptorSet::None,
+        })
+    }
+
+    // blocks until the dispatch completes
+    pub fn dispatch(
+        &self,
+        compute_pipeline: &ComputePipeline,
+        descriptor_set: &DescriptorSet,
+        groups: [u32; 3],
+    ) -> anyhow::Result<()> {
+        match self {
+            Renderer::Vulkan(r) => r.dispatch(compute_pipeline, descriptor_set, groups)?,
+            Renderer::Mock(r) => r.record(MockCall::Dispatch { groups }),
+            Renderer::None => (),
+        }
+
+        Ok(())
+    }
+
+    //#endregion
+
+    // depth is cleared to the depth mode's far plane
+    // the shadow pass is drawn first, in the same submission
+    // post_process is required if the render pass has a post-process subpass
+    pub fn draw_data<F>(
+        &mut self,
+        clear_values: [f32; 4],
+        depth_mode: DepthMode,
+        shadow_pass: Option<&ShadowPass>,
+        draw_items: &[DrawItem],
+        post_process: Option<&PostProcessItem>,
+        frame_buffers: F,
+    ) -> anyhow::Result<bool>
+    where
+        F: AsRef<Vec<FrameBuffer>>,
+    {
+        Ok(match self {
+            Renderer::Vulkan(r) => r.draw_data(
+                clear_values,
+                depth_mode,
+                shadow_pass,
+                draw_items,
+                post_process,
+                frame_buffers,
+            )?,
+            Renderer::Mock(r) => {
+                if let Some(shadow_pass) = shadow_pass {
+                    r.record(MockCall::DrawShadows {
+                        draw_item_count: shadow_pass.draw_items.len(),
+                    });
+                }
+                r.record(MockCall::DrawData {
+                    draw_item_count: draw_items.len(),
+                });
+                if post_process.is_some() {
+                    r.record(MockCall::DrawFullscreen {
+                        vertex_count: FULLSCREEN_TRIANGLE_VERTICES,
+                    });
+                }
+                true
+            }
+            Renderer::None => false,
+        })
+    }
+
+    //#region Offscreen
+
+    // highest sample count up to requested that the device can render offscreen with
+    pub fn supported_sample_count(&self, requested: u32) -> u32 {
+        let supported = match self {
+            Renderer::Vulkan(r) => r.supported_sample_counts(),
+            Renderer::Mock(_) => MOCK_SAMPLE_COUNTS,
+            Renderer::None => 1,
+        };
+
+        let samples = select_sample_count(requested, supported);
+        if samples < requested {
+            warn!("{}x MSAA not supported, using {}x", requested, samples);
+        }
+        samples
+    }
+
+    // draws into a new RGBA8 image instead of the swapchain and reads it back,
+    // multisampled renders are resolved to a single sample per pixel
+    pub fn render_to_image(
+        &mut self,
+        dimensions: [u32; 2],
+        samples: u32,
+        clear_color: [f32; 4],
+        depth_mode: DepthMode,
+        draw_items: &[DrawItem],
+    ) -> anyhow::Result<image::RgbaImage> {
+        let samples = self.supported_sample_count(samples);
+
+        Ok(match self {
+            Renderer::Vulkan(r) => {
+                r.render_to_image(dimensions, samples, clear_color, depth_mode, draw_items)?
+            }
+            Renderer::Mock(r) => {
+                r.record(MockCall::RenderToImage {
+                    dimensions,
+                    samples,
+                    draw_item_count: draw_items.len(),
+                });
+                image::RgbaImage::new(dimensions[0], dimensions[1])
+            }
+            Renderer::None => bail!("Offscreen rendering not supported"),
+        })
+    }
+
+    //#endregion
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // encoded, as read from an asset
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut data = Vec::new();
+        image::DynamicImage::ImageRgba8(image::RgbaImage::new(width, height))
+            .write_to(&mut data, image::ImageOutputFormat::PNG)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn sample_count_downgrades_to_supported() {
+        assert_eq!(select_sample_count(4, 0b1111), 4);
+        assert_eq!(select_sample_count(8, 0b0111), 4);
+        assert_eq!(select_sample_count(4, 0b0011), 2);
+        assert_eq!(select_sample_count(3, 0b1111), 2);
+        assert_eq!(select_sample_count(0, 0b1111), 1);
+        assert_eq!(select_sample_count(4, 0), 1);
+    }
+
+    #[test]
+    fn msaa_capture_resolves_to_the_requested_size() {
+        let mut renderer = Renderer::Mock(MockRendererState::new());
+
+        let image = renderer
+            .render_to_image([64, 32], 4, [0.0; 4], DepthMode::default(), &[])
+            .unwrap();
+        assert_eq!(image.dimensions(), (64, 32));
+
+        // downgraded to what the device supports
+        renderer
+            .render_to_image([64, 32], 16, [0.0; 4], DepthMode::default(), &[])
+            .unwrap();
+
+        match &renderer {
+            Renderer::Mock(r) => assert_eq!(
+                r.get_calls(),
+                vec![
+                    MockCall::RenderToImage {
+                        dimensions: [64, 32],
+                        samples: 4,
+                        draw_item_count: 0,
+                    },
+                    MockCall::RenderToImage {
+                        dimensions: [64, 32],
+                        samples: 8,
+                        draw_item_count: 0,
+                    },
+                ]
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn invalid_spirv_is_rejected() {
+        let renderer = Renderer::Mock(MockRendererState::new());
+
+        let spirv = SPIRV_MAGIC.to_le_bytes();
+        let err = match renderer.load_simple_shader_spirv(&spirv, b"void") {
+            Ok(_) => panic!("Loaded invalid SPIR-V"),
+            Err(e) => e,
+        };
+        assert_eq!(err.to_string(), "Invalid SPIR-V");
+    }
+
+    #[test]
+    fn cubemap_has_six_array_layers() {
+        let renderer = Renderer::Mock(MockRendererState::new());
+
+        let faces = (0..6).map(|_| png(4, 4)).collect::<Vec<_>>();
+        renderer.create_cubemap(&faces).unwrap();
+
+        match &renderer {
+            Renderer::Mock(r) => {
+                assert_eq!(
+                    r.get_calls(),
+                    vec![MockCall::CreateTexture { array_layers: 6 }]
+                )
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn cubemap_faces_must_match() {
+        let renderer = Renderer::Mock(MockRendererState::new());
+
+        let faces = (0..5).map(|_| png(4, 4)).collect::<Vec<_>>();
+        assert!(renderer.create_cubemap(&faces).is_err());
+
+        let mut faces = (0..6).map(|_| png(4, 4)).collect::<Vec<_>>();
+        faces[3] = png(8, 8);
+        assert!(renderer.create_cubemap(&faces).is_err());
+
+        faces[3] = png(4, 2);
+        assert!(renderer.create_cubemap(&faces).is_err());
+    }
+
+    #[test]
+    fn dispatch_records_groups() {
+        let renderer = Renderer::Mock(MockRendererState::new());
+
+        let buffer = renderer.create_storage_buffer(vec![1u32, 2, 3]).unwrap();
+        renderer
+            .dispatch(&ComputePipeline::None, &DescriptorSet::None, [3, 1, 1])
+            .unwrap();
+
+        // nothing to read back without a device
+        assert!(buffer.read().unwrap().is_empty());
+        match &renderer {
+            Renderer::Mock(r) => {
+                assert_eq!(
+                    r.get_calls(),
+                    vec![MockCall::Dispatch { groups: [3, 1, 1] }]
+                )
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn draw_items_are_recorded_in_one_pass() {
+        let mut renderer = Renderer::Mock(MockRendererState::new());
+
+        let render_pipeline = RenderPipeline::None;
+        let descriptor_set = DescriptorSet::None;
+        let vertex_buffers = (0..3)
+            .map(|_| {
+                renderer
+                    .create_vertex_buffer(vec![Vertex::default(); 3])
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let draw_items = vertex_buffers
+            .iter()
+            .map(|vertex_buffer| DrawItem {
+                render_pipeline: &render_pipeline,
+                vertex_buffer,
+                descriptor_set: &descriptor_set,
+                instance_buffer: None,
+                push_constants: None,
+            })
+            .collect::<Vec<_>>();
+
+        if let Renderer::Mock(r) = &renderer {
+            r.clear_calls();
+        }
+        renderer
+            .draw_data(
+                [0.0; 4],
+                DepthMode::Standard,
+                None,
+                &draw_items,
+                None,
+                Vec::<FrameBuffer>::new(),
+            )
+            .unwrap();
+
+        match &renderer {
+            Renderer::Mock(r) => assert_eq!(
+                r.get_calls(),
+                vec![MockCall::DrawData { draw_item_count: 3 }]
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn post_process_draws_a_fullscreen_triangle() {
+        let mut renderer = Renderer::Mock(MockRendererState::new());
+
+        let render_pipeline = RenderPipeline::None;
+        let descriptor_sets = renderer
+            .create_tonemap_descriptor_sets(&render_pipeline, Tonemap::Aces)
+            .unwrap();
+        let post_process = PostProcessItem {
+            render_pipeline: &render_pipeline,
+            descriptor_sets: &descriptor_sets,
+        };
+        renderer
+            .draw_data(
+                [0.0; 4],
+                DepthMode::Standard,
+                None,
+                &[],
+                Some(&post_process),
+                Vec::<FrameBuffer>::new(),
+            )
+            .unwrap();
+
+        // no vertex buffer is created for it
+        match &renderer {
+            Renderer::Mock(r) => assert_eq!(
+                r.get_calls(),
+                vec![
+                    MockCall::DrawData { draw_item_count: 0 },
+                    MockCall::DrawFullscreen { vertex_count: 3 },
+                ]
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn shadow_map_is_square() {
+        let renderer = Renderer::Mock(MockRendererState::new());
+
+        let shadow_map = renderer.create_shadow_map(&RenderPass::None, 1024).unwrap();
+        assert_eq!(shadow_map.size, 1024);
+        assert!(renderer.create_shadow_map(&RenderPass::None, 0).is_err());
+
+        match &renderer {
+            Renderer::Mock(r) => assert_eq!(
+                r.get_calls(),
+                vec![MockCall::CreateDepthImage {
+                    dimensions: [1024, 1024]
+                }]
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn depth_clears_to_the_far_plane() {
+        assert_eq!(DepthMode::Standard.clear_value(), 1.0);
+        assert_eq!(DepthMode::Reversed.clear_value(), 0.0);
+    }
 }
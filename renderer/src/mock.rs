@@ -0,0 +1,64 @@
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockCall {
+    CreateVertexBuffer {
+        vertex_count: usize,
+    },
+    CreateInstanceBuffer {
+        instance_count: usize,
+    },
+    CreateTexture {
+        array_layers: u32,
+    },
+    CreateDepthImage {
+        dimensions: [u32; 2],
+    },
+    DrawShadows {
+        draw_item_count: usize,
+    },
+    DrawData {
+        draw_item_count: usize,
+    },
+    DrawText {
+        glyph_count: usize,
+    },
+    DrawFullscreen {
+        vertex_count: u32,
+    },
+    RenderToImage {
+        dimensions: [u32; 2],
+        samples: u32,
+        draw_item_count: usize,
+    },
+    Dispatch {
+        groups: [u32; 3],
+    },
+}
+
+// 1, 2, 4 and 8 samples, like most desktop GPUs
+pub(crate) const MOCK_SAMPLE_COUNTS: u32 = 0b1111;
+
+// headless renderer that records calls instead of touching a GPU
+#[derive(Debug, Default)]
+pub struct MockRendererState {
+    calls: Mutex<Vec<MockCall>>,
+}
+
+impl MockRendererState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn record(&self, call: MockCall) {
+        self.calls.lock().unwrap().push(call);
+    }
+
+    pub fn get_calls(&self) -> Vec<MockCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    pub fn clear_calls(&self) {
+        self.calls.lock().unwrap().clear();
+    }
+}
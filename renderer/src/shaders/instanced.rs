@@ -0,0 +1,77 @@
+pub mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: "
+#version 450
+
+layout(location = 0) in vec3 position;
+
+// per-instance model matrix columns
+layout(location = 1) in vec4 model_col0;
+layout(location = 2) in vec4 model_col1;
+layout(location = 3) in vec4 model_col2;
+layout(location = 4) in vec4 model_col3;
+layout(location = 5) in vec4 color;
+
+layout(location = 0) out vec4 v_color;
+layout(location = 1) out vec4 v_shadow_position;
+
+// view * projection, the model comes from the instance
+layout(push_constant) uniform PushConstants {
+    mat4 transform;
+} push_constants;
+
+// the shadow casting light's view * projection
+layout(set = 0, binding = 0) uniform Shadow {
+    mat4 view_projection;
+    float bias;
+} shadow;
+
+void main() {
+    mat4 model = mat4(model_col0, model_col1, model_col2, model_col3);
+    vec4 world_position = model * vec4(position, 1.0);
+    gl_Position = push_constants.transform * world_position;
+    v_color = color;
+
+    // biased towards the light so surfaces don't shadow themselves
+    v_shadow_position = shadow.view_projection * world_position;
+    v_shadow_position.z -= shadow.bias * v_shadow_position.w;
+}
+"
+    }
+}
+
+pub mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: "
+#version 450
+
+layout(location = 0) in vec4 v_color;
+layout(location = 1) in vec4 v_shadow_position;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 1) uniform sampler2D shadow_map;
+
+// light left in shadowed areas
+const float SHADOW_AMBIENT = 0.3;
+
+float shadow_factor() {
+    vec3 p = v_shadow_position.xyz / v_shadow_position.w;
+    vec2 uv = p.xy * 0.5 + 0.5;
+
+    // outside of the light's view
+    if (any(lessThan(uv, vec2(0.0))) || any(greaterThan(uv, vec2(1.0))) || p.z > 1.0) {
+        return 1.0;
+    }
+
+    return p.z > texture(shadow_map, uv).r ? SHADOW_AMBIENT : 1.0;
+}
+
+void main() {
+    f_color = vec4(v_color.rgb * shadow_factor(), v_color.a);
+}
+"
+    }
+}
@@ -1 +1,8 @@
+pub mod instanced;
+pub mod line;
+pub mod shadow;
 pub mod simple;
+pub mod skybox;
+pub mod sprite;
+pub mod text;
+pub mod tonemap;
@@ -0,0 +1,39 @@
+pub mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: "
+#version 450
+
+layout(location = 0) in vec3 position;
+
+// per-instance model matrix columns
+layout(location = 1) in vec4 model_col0;
+layout(location = 2) in vec4 model_col1;
+layout(location = 3) in vec4 model_col2;
+layout(location = 4) in vec4 model_col3;
+
+// the light's view * projection
+layout(push_constant) uniform PushConstants {
+    mat4 transform;
+} push_constants;
+
+void main() {
+    mat4 model = mat4(model_col0, model_col1, model_col2, model_col3);
+    gl_Position = push_constants.transform * model * vec4(position, 1.0);
+}
+"
+    }
+}
+
+pub mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: "
+#version 450
+
+// depth only
+void main() {
+}
+"
+    }
+}
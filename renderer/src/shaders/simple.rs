@@ -6,11 +6,35 @@ pub mod vs {
 
 layout(location = 0) in vec3 position;
 
+// model * view * projection
+layout(push_constant) uniform PushConstants {
+    mat4 transform;
+} push_constants;
+
 void main() {
-    gl_Position = vec4(position, 1.0);
+    gl_Position = push_constants.transform * vec4(position, 1.0);
+
+    // required for point lists, larger points need the large_points feature
+    gl_PointSize = 1.0;
 }
 "
     }
+
+    impl Shader {
+        /// Replaces the compiled in code.
+        ///
+        /// # Safety
+        ///
+        /// The SPIR-V must be valid and keep this shader's interface.
+        pub unsafe fn from_spirv(
+            device: std::sync::Arc<vulkano::device::Device>,
+            spirv: &[u8],
+        ) -> Result<Self, vulkano::OomError> {
+            Ok(Shader {
+                shader: vulkano::pipeline::shader::ShaderModule::new(device, spirv)?,
+            })
+        }
+    }
 }
 
 pub mod fs {
@@ -26,4 +50,20 @@ void main() {
 }
 "
     }
+
+    impl Shader {
+        /// Replaces the compiled in code.
+        ///
+        /// # Safety
+        ///
+        /// The SPIR-V must be valid and keep this shader's interface.
+        pub unsafe fn from_spirv(
+            device: std::sync::Arc<vulkano::device::Device>,
+            spirv: &[u8],
+        ) -> Result<Self, vulkano::OomError> {
+            Ok(Shader {
+                shader: vulkano::pipeline::shader::ShaderModule::new(device, spirv)?,
+            })
+        }
+    }
 }
@@ -0,0 +1,47 @@
+pub mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: "
+#version 450
+
+layout(location = 0) in vec3 position;
+
+layout(location = 0) out vec3 v_direction;
+
+// rotation only view * projection
+layout(set = 0, binding = 0) uniform Data {
+    mat4 view_projection;
+} uniforms;
+
+// 1 for standard depth, 0 for reversed-Z
+layout(constant_id = 0) const float far_depth = 1.0;
+
+void main() {
+    v_direction = position;
+
+    // always lands on the far plane
+    vec4 p = uniforms.view_projection * vec4(position, 1.0);
+    gl_Position = vec4(p.xy, p.w * far_depth, p.w);
+}
+"
+    }
+}
+
+pub mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: "
+#version 450
+
+layout(location = 0) in vec3 v_direction;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 1) uniform samplerCube skybox;
+
+void main() {
+    f_color = texture(skybox, v_direction);
+}
+"
+    }
+}
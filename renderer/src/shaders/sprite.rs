@@ -0,0 +1,42 @@
+pub mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: "
+#version 450
+
+layout(location = 0) in vec3 position;
+layout(location = 2) in vec2 uv;
+
+layout(location = 0) out vec2 v_uv;
+
+// model * view * projection
+layout(push_constant) uniform PushConstants {
+    mat4 transform;
+} push_constants;
+
+void main() {
+    gl_Position = push_constants.transform * vec4(position, 1.0);
+    v_uv = uv;
+}
+"
+    }
+}
+
+pub mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: "
+#version 450
+
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D sprite;
+
+void main() {
+    f_color = texture(sprite, v_uv);
+}
+"
+    }
+}
@@ -0,0 +1,46 @@
+pub mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: "
+#version 450
+
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec2 uv;
+layout(location = 2) in vec4 color;
+
+layout(location = 0) out vec2 v_uv;
+layout(location = 1) out vec4 v_color;
+
+// pixels to clip space
+layout(push_constant) uniform PushConstants {
+    mat4 transform;
+} push_constants;
+
+void main() {
+    gl_Position = push_constants.transform * vec4(position, 0.0, 1.0);
+    v_uv = uv;
+    v_color = color;
+}
+"
+    }
+}
+
+pub mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: "
+#version 450
+
+layout(location = 0) in vec2 v_uv;
+layout(location = 1) in vec4 v_color;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D font;
+
+void main() {
+    f_color = v_color * texture(font, v_uv);
+}
+"
+    }
+}
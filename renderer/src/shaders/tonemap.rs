@@ -0,0 +1,56 @@
+pub mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: "
+#version 450
+
+layout(location = 0) out vec2 v_uv;
+
+// fullscreen triangle, no vertex buffer
+void main() {
+    v_uv = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2);
+    gl_Position = vec4(v_uv * 2.0 - 1.0, 0.0, 1.0);
+}
+"
+    }
+}
+
+pub mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: "
+#version 450
+
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+// written by the scene subpass
+layout(input_attachment_index = 0, set = 0, binding = 0) uniform subpassInput scene_color;
+
+// operator matches renderer::Tonemap
+layout(set = 0, binding = 1) uniform Data {
+    uint operator;
+} uniforms;
+
+vec3 aces(vec3 x) {
+    return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), 0.0, 1.0);
+}
+
+void main() {
+    vec4 color = subpassLoad(scene_color);
+    vec3 hdr = color.rgb;
+
+    vec3 ldr = hdr;
+    if (uniforms.operator == 1) {
+        ldr = hdr / (hdr + vec3(1.0));
+    } else if (uniforms.operator == 2) {
+        ldr = aces(hdr);
+    }
+
+    // the swapchain is sRGB, so no manual gamma
+    f_color = vec4(ldr, color.a);
+}
+"
+    }
+}
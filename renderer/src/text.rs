@@ -0,0 +1,110 @@
+// screen space position (pixels), atlas uv
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct TextVertex {
+    pub position: [f32; 2],
+    pub uv: [f32; 2],
+    pub color: [f32; 4],
+}
+vulkano::impl_vertex!(TextVertex, position, uv, color);
+
+// fixed-size glyphs laid out in a grid, starting at first_char
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontAtlas {
+    pub columns: u32,
+    pub rows: u32,
+    pub first_char: char,
+
+    // glyph width / height
+    pub glyph_aspect: f32,
+
+    // drawn for anything not in the atlas, usually a box
+    pub missing_glyph: char,
+}
+
+impl Default for FontAtlas {
+    // 16x16 ASCII / code page 437 style atlas
+    fn default() -> Self {
+        Self {
+            columns: 16,
+            rows: 16,
+            first_char: '\0',
+            glyph_aspect: 0.5,
+            missing_glyph: '\u{7f}',
+        }
+    }
+}
+
+impl FontAtlas {
+    fn glyph_index(&self, c: char) -> u32 {
+        let count = self.columns * self.rows;
+        let index = (c as u32).wrapping_sub(self.first_char as u32);
+        if index < count {
+            index
+        } else {
+            (self.missing_glyph as u32).wrapping_sub(self.first_char as u32) % count
+        }
+    }
+
+    // top-left and bottom-right uv of the glyph cell
+    fn glyph_uv(&self, c: char) -> ([f32; 2], [f32; 2]) {
+        let index = self.glyph_index(c);
+        let w = 1.0 / self.columns as f32;
+        let h = 1.0 / self.rows as f32;
+        let u = (index % self.columns) as f32 * w;
+        let v = (index / self.columns) as f32 * h;
+
+        ([u, v], [u + w, v + h])
+    }
+
+    // 6 vertices per glyph, whitespace advances without a quad
+    pub fn layout_text(
+        &self,
+        text: &str,
+        position: [f32; 2],
+        size: f32,
+        color: [f32; 4],
+    ) -> Vec<TextVertex> {
+        let advance = size * self.glyph_aspect;
+
+        let mut vertices = Vec::with_capacity(text.len() * 6);
+        let mut x = position[0];
+        let mut y = position[1];
+        for c in text.chars() {
+            match c {
+                '\n' => {
+                    x = position[0];
+                    y += size;
+                    continue;
+                }
+                '\r' => continue,
+                c if c.is_whitespace() => {
+                    x += advance;
+                    continue;
+                }
+                _ => (),
+            }
+
+            let (uv0, uv1) = self.glyph_uv(c);
+            let (x0, y0, x1, y1) = (x, y, x + advance, y + size);
+
+            for (position, uv) in &[
+                ([x0, y0], uv0),
+                ([x1, y0], [uv1[0], uv0[1]]),
+                ([x1, y1], uv1),
+                ([x0, y0], uv0),
+                ([x1, y1], uv1),
+                ([x0, y1], [uv0[0], uv1[1]]),
+            ] {
+                vertices.push(TextVertex {
+                    position: *position,
+                    uv: *uv,
+                    color,
+                });
+            }
+
+            x += advance;
+        }
+
+        vertices
+    }
+}
@@ -1,30 +1,425 @@
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::ffi::CString;
+use std::os::raw::{c_char, c_void};
+use std::sync::{Arc, Mutex, Weak};
 
 use anyhow::{anyhow, bail};
 use derivative::Derivative;
-use log::{info, warn};
-use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use log::{info, log, warn};
+use vulkano::buffer::{
+    BufferAccess, BufferSlice, BufferUsage, CpuAccessibleBuffer, DeviceLocalBuffer,
+    TypedBufferAccess,
+};
 use vulkano::command_buffer::pool::standard::StandardCommandPoolBuilder;
-use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
-use vulkano::device::{Device, DeviceExtensions, Queue};
-use vulkano::format::FormatDesc;
-use vulkano::framebuffer::{Framebuffer, FramebufferAbstract, Subpass};
-use vulkano::image::{Dimensions, StorageImage, SwapchainImage};
-use vulkano::instance::debug::DebugCallback;
-use vulkano::instance::{Instance, InstanceExtensions, LayerProperties, PhysicalDevice};
+use vulkano::command_buffer::{
+    AutoCommandBuffer, AutoCommandBufferBuilder, CommandBuffer, DynamicState,
+};
+use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
+use vulkano::descriptor::pipeline_layout::PipelineLayoutDesc;
+use vulkano::descriptor::DescriptorSet as VulkanDescriptorSet;
+use vulkano::device::{Device, DeviceExtensions, Queue, RawDeviceExtensions};
+use vulkano::format::{ClearValue, D16Unorm, R16G16B16A16Sfloat, R8G8B8A8Srgb, R8G8B8A8Unorm};
+use vulkano::format::{Format, FormatDesc};
+use vulkano::framebuffer::{
+    AttachmentDescription, Framebuffer, FramebufferAbstract, LoadOp, PassDependencyDescription,
+    PassDescription, RenderPassAbstract, RenderPassDesc, RenderPassDescClearValues, StoreOp,
+    Subpass,
+};
+use vulkano::image::{
+    AttachmentImage, Dimensions, ImageLayout, ImageUsage, ImageViewAccess, ImmutableImage,
+    StorageImage, SwapchainImage,
+};
+use vulkano::instance::debug::{DebugCallback, MessageTypes};
+use vulkano::instance::loader;
+use vulkano::instance::{
+    ApplicationInfo, Instance, InstanceExtensions, LayerProperties, PhysicalDevice, QueueFamily,
+    RawInstanceExtensions, Version,
+};
 use vulkano::memory::Content;
+use vulkano::pipeline::blend::{AttachmentBlend, BlendFactor};
+use vulkano::pipeline::depth_stencil::{Compare, DepthStencil};
+use vulkano::pipeline::input_assembly::PrimitiveTopology as VulkanPrimitiveTopology;
+use vulkano::pipeline::shader::EntryPointAbstract;
+use vulkano::pipeline::vertex::{
+    AttributeInfo, IncompatibleVertexDefinitionError, InputRate, OneVertexOneInstanceDefinition,
+    VertexDefinition, VertexSource,
+};
 use vulkano::pipeline::viewport::Viewport;
-use vulkano::pipeline::GraphicsPipeline;
+use vulkano::pipeline::ComputePipeline as VulkanComputePipeline;
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract};
+use vulkano::sampler::{Filter, MipmapMode, Sampler, SamplerAddressMode};
 use vulkano::swapchain::{
-    AcquireError, PresentMode, Surface, SurfaceTransform, Swapchain, SwapchainAcquireFuture,
-    SwapchainCreationError,
+    AcquireError, ColorSpace, PresentMode, Surface, SurfaceTransform, Swapchain,
+    SwapchainAcquireFuture, SwapchainCreationError,
 };
-use vulkano::sync::{FlushError, GpuFuture};
+use vulkano::sync::{AccessFlagBits, FenceSignalFuture, FlushError, GpuFuture, PipelineStages};
+use vulkano::VulkanObject;
 use vulkano_win::VkSurfaceBuild;
 use winit::{EventsLoop, Window, WindowBuilder};
 
 use crate::*;
 
+// live allocations are pruned as they're dropped
+#[derive(Default)]
+struct MemoryTracker {
+    buffers: Vec<(Weak<dyn BufferAccess + Send + Sync>, usize)>,
+    images: Vec<(Weak<dyn ImageViewAccess + Send + Sync>, usize)>,
+}
+
+impl MemoryTracker {
+    fn stats(&mut self) -> MemoryStats {
+        self.buffers
+            .retain(|(buffer, _)| buffer.upgrade().is_some());
+        self.images.retain(|(image, _)| image.upgrade().is_some());
+
+        MemoryStats {
+            buffer_count: self.buffers.len(),
+            buffer_bytes: self.buffers.iter().map(|(_, size)| size).sum(),
+            image_count: self.images.len(),
+            image_bytes: self.images.iter().map(|(_, size)| size).sum(),
+        }
+    }
+}
+
+const DEFAULT_FRAMES_IN_FLIGHT: usize = 2;
+
+// bufferless like vulkano's BufferlessDefinition, but also drawable
+// through the type-erased RenderPipeline, which only passes a buffer list
+struct FullscreenTriangleDefinition;
+
+unsafe impl VertexSource<Vec<Arc<dyn BufferAccess + Send + Sync>>>
+    for FullscreenTriangleDefinition
+{
+    fn decode(
+        &self,
+        buffers: Vec<Arc<dyn BufferAccess + Send + Sync>>,
+    ) -> (Vec<Box<dyn BufferAccess + Send + Sync>>, usize, usize) {
+        assert!(buffers.is_empty(), "Fullscreen triangle takes no buffers");
+        (Vec::new(), FULLSCREEN_TRIANGLE_VERTICES as usize, 1)
+    }
+}
+
+unsafe impl<I> VertexDefinition<I> for FullscreenTriangleDefinition {
+    type BuffersIter = std::iter::Empty<(u32, usize, InputRate)>;
+    type AttribsIter = std::iter::Empty<(u32, u32, AttributeInfo)>;
+
+    fn definition(
+        &self,
+        _: &I,
+    ) -> Result<(Self::BuffersIter, Self::AttribsIter), IncompatibleVertexDefinitionError> {
+        Ok((std::iter::empty(), std::iter::empty()))
+    }
+}
+
+// VK_SUBPASS_EXTERNAL
+const SUBPASS_EXTERNAL: usize = !0u32 as usize;
+
+// depth only, like single_pass_renderpass! but ending in a layout the scene can sample,
+// the external dependency makes the depth writes visible to the scene's fragment shaders
+// since vulkano doesn't track what secondary command buffers read
+struct ShadowRenderPassDesc;
+
+unsafe impl RenderPassDesc for ShadowRenderPassDesc {
+    fn num_attachments(&self) -> usize {
+        1
+    }
+
+    fn attachment_desc(&self, num: usize) -> Option<AttachmentDescription> {
+        if num != 0 {
+            return None;
+        }
+
+        Some(AttachmentDescription {
+            format: Format::D16Unorm,
+            samples: 1,
+            load: LoadOp::Clear,
+            store: StoreOp::Store,
+            stencil_load: LoadOp::DontCare,
+            stencil_store: StoreOp::DontCare,
+            // cleared, so the previous contents don't matter
+            initial_layout: ImageLayout::Undefined,
+            final_layout: ImageLayout::ShaderReadOnlyOptimal,
+        })
+    }
+
+    fn num_subpasses(&self) -> usize {
+        1
+    }
+
+    fn subpass_desc(&self, num: usize) -> Option<PassDescription> {
+        if num != 0 {
+            return None;
+        }
+
+        Some(PassDescription {
+            color_attachments: Vec::new(),
+            depth_stencil: Some((0, ImageLayout::DepthStencilAttachmentOptimal)),
+            input_attachments: Vec::new(),
+            resolve_attachments: Vec::new(),
+            preserve_attachments: Vec::new(),
+        })
+    }
+
+    fn num_dependencies(&self) -> usize {
+        1
+    }
+
+    fn dependency_desc(&self, num: usize) -> Option<PassDependencyDescription> {
+        if num != 0 {
+            return None;
+        }
+
+        Some(PassDependencyDescription {
+            source_subpass: 0,
+            destination_subpass: SUBPASS_EXTERNAL,
+            source_stages: PipelineStages {
+                late_fragment_tests: true,
+                ..PipelineStages::none()
+            },
+            destination_stages: PipelineStages {
+                fragment_shader: true,
+                ..PipelineStages::none()
+            },
+            source_access: AccessFlagBits {
+                depth_stencil_attachment_write: true,
+                ..AccessFlagBits::none()
+            },
+            destination_access: AccessFlagBits {
+                shader_read: true,
+                ..AccessFlagBits::none()
+            },
+            by_region: false,
+        })
+    }
+}
+
+unsafe impl RenderPassDescClearValues<Vec<ClearValue>> for ShadowRenderPassDesc {
+    fn convert_clear_values(
+        &self,
+        values: Vec<ClearValue>,
+    ) -> Box<dyn Iterator<Item = ClearValue>> {
+        Box::new(values.into_iter())
+    }
+}
+
+fn viewport_dynamic_state(dimensions: [u32; 2]) -> DynamicState {
+    DynamicState {
+        viewports: Some(vec![Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+            depth_range: 0.0..1.0,
+        }]),
+        ..DynamicState::none()
+    }
+}
+
+// smallest pooled buffer, in vertices
+const MIN_POOLED_BUFFER_SIZE: usize = 64;
+
+fn pooled_size_class(len: usize) -> usize {
+    len.max(MIN_POOLED_BUFFER_SIZE).next_power_of_two()
+}
+
+// free vertex buffers by size class (capacity in vertices)
+#[derive(Default)]
+struct VertexBufferPool {
+    free: HashMap<usize, Vec<Arc<CpuAccessibleBuffer<[Vertex]>>>>,
+}
+
+// identifies a draw item by the GPU objects it references
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct DrawItemKey {
+    render_pipeline: usize,
+    vertex_buffer: usize,
+    descriptor_set: usize,
+    instance_buffer: usize,
+
+    // push constants are recorded into the command buffer
+    push_constants: Option<[[u32; 4]; 4]>,
+}
+
+impl DrawItemKey {
+    fn new(draw_item: &DrawItem) -> anyhow::Result<Self> {
+        Ok(Self {
+            render_pipeline: match draw_item.render_pipeline {
+                RenderPipeline::Vulkan(p) => &**p as *const _ as *const () as usize,
+                RenderPipeline::None => {
+                    bail!("Invalid render pipeline type {}", draw_item.render_pipeline)
+                }
+            },
+            vertex_buffer: match draw_item.vertex_buffer {
+                VertexBuffer::Vulkan(v) => &**v as *const _ as *const () as usize,
+                VertexBuffer::None => {
+                    bail!("Invalid vertex buffer type {}", draw_item.vertex_buffer)
+                }
+            },
+            descriptor_set: match draw_item.descriptor_set {
+                DescriptorSet::Vulkan(s) => &**s as *const _ as *const () as usize,
+                DescriptorSet::None => 0,
+            },
+            instance_buffer: match draw_item.instance_buffer {
+                Some(VertexBuffer::Vulkan(v)) => &**v as *const _ as *const () as usize,
+                Some(VertexBuffer::None) => bail!("Invalid instance buffer type None"),
+                None => 0,
+            },
+            push_constants: draw_item.push_constants.map(|p| {
+                let mut bits = [[0; 4]; 4];
+                for (col, values) in p.transform.iter().enumerate() {
+                    for (row, value) in values.iter().enumerate() {
+                        bits[col][row] = value.to_bits();
+                    }
+                }
+                bits
+            }),
+        })
+    }
+}
+
+// applies the PipelineConfig state that's shared by every configurable pipeline,
+// the builder's type changes with each call so this can't be a function
+macro_rules! configure_pipeline {
+    ($builder:expr, $config:expr, $has_depth:expr) => {{
+        let builder = $builder;
+        let config: &PipelineConfig = $config;
+
+        let builder = if $has_depth {
+            builder.depth_stencil(DepthStencil {
+                depth_compare: config.depth_mode.into(),
+                ..DepthStencil::simple_depth_test()
+            })
+        } else {
+            builder
+        };
+
+        let builder = match config.polygon_mode {
+            PolygonMode::Fill => builder.polygon_mode_fill(),
+            PolygonMode::Line => builder.polygon_mode_line(),
+        };
+
+        let builder = match config.cull_mode {
+            CullMode::None => builder.cull_mode_disabled(),
+            CullMode::Back => builder.cull_mode_back(),
+            CullMode::Front => builder.cull_mode_front(),
+        };
+
+        let builder = match config.front_face {
+            FrontFace::CounterClockwise => builder.front_face_counter_clockwise(),
+            FrontFace::Clockwise => builder.front_face_clockwise(),
+        };
+
+        match config.blend_mode {
+            BlendMode::Opaque => builder.blend_pass_through(),
+            BlendMode::Alpha => builder.blend_alpha_blending(),
+            BlendMode::Additive => builder.blend_collective(AttachmentBlend {
+                color_destination: BlendFactor::One,
+                alpha_destination: BlendFactor::One,
+                ..AttachmentBlend::alpha_blending()
+            }),
+        }
+    }};
+}
+
+impl From<PrimitiveTopology> for VulkanPrimitiveTopology {
+    fn from(topology: PrimitiveTopology) -> Self {
+        match topology {
+            PrimitiveTopology::PointList => VulkanPrimitiveTopology::PointList,
+            PrimitiveTopology::LineList => VulkanPrimitiveTopology::LineList,
+            PrimitiveTopology::LineStrip => VulkanPrimitiveTopology::LineStrip,
+            PrimitiveTopology::TriangleList => VulkanPrimitiveTopology::TriangleList,
+            PrimitiveTopology::TriangleStrip => VulkanPrimitiveTopology::TriangleStrip,
+            PrimitiveTopology::TriangleFan => VulkanPrimitiveTopology::TriangleFan,
+        }
+    }
+}
+
+impl From<DepthMode> for Compare {
+    fn from(mode: DepthMode) -> Self {
+        match mode {
+            DepthMode::Standard => Compare::Less,
+            DepthMode::Reversed => Compare::Greater,
+        }
+    }
+}
+
+// the skybox sits exactly on the far plane, so equal has to pass
+fn skybox_depth_compare(mode: DepthMode) -> Compare {
+    match mode {
+        DepthMode::Standard => Compare::LessOrEqual,
+        DepthMode::Reversed => Compare::GreaterOrEqual,
+    }
+}
+
+//#region Debug Names
+
+const DEBUG_UTILS_EXTENSION: &str = "VK_EXT_debug_utils";
+
+// VkStructureType / VkObjectType values from the VK_EXT_debug_utils spec
+const STRUCTURE_TYPE_DEBUG_UTILS_OBJECT_NAME_INFO: u32 = 1_000_128_000;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(u32)]
+enum DebugObjectType {
+    Buffer = 9,
+    Image = 10,
+    Pipeline = 19,
+}
+
+#[repr(C)]
+struct DebugUtilsObjectNameInfo {
+    s_type: u32,
+    p_next: *const c_void,
+    object_type: u32,
+    object_handle: u64,
+    p_object_name: *const c_char,
+}
+
+type SetDebugUtilsObjectNameFn =
+    unsafe extern "system" fn(usize, *const DebugUtilsObjectNameInfo) -> i32;
+
+// None if the extension isn't enabled or the loader doesn't expose the entry point
+fn load_set_debug_name(instance: &Instance) -> Option<SetDebugUtilsObjectNameFn> {
+    let loader = loader::auto_loader().ok()?;
+    let name = CString::new("vkSetDebugUtilsObjectNameEXT").ok()?;
+
+    let address = loader.get_instance_proc_addr(instance.internal_object(), name.as_ptr()) as usize;
+    if address == 0 {
+        return None;
+    }
+
+    Some(unsafe { std::mem::transmute::<usize, SetDebugUtilsObjectNameFn>(address) })
+}
+
+//#endregion
+
+// one uniform buffer per frame in flight, so a frame never overwrites
+// uniforms that an earlier frame still has queued
+struct UniformRing<T> {
+    buffers: Vec<Arc<CpuAccessibleBuffer<T>>>,
+}
+
+impl<T> UniformRing<T>
+where
+    T: Content + Copy + Send + Sync + 'static,
+{
+    fn new(device: &Arc<Device>, frames_in_flight: usize, data: T) -> anyhow::Result<Self> {
+        let buffers = (0..frames_in_flight)
+            .map(|_| {
+                CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::uniform_buffer(), data)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { buffers })
+    }
+
+    // the frame's fence must have been waited on first
+    fn write(&self, frame: usize, data: T) -> anyhow::Result<Arc<CpuAccessibleBuffer<T>>> {
+        let buffer = &self.buffers[frame % self.buffers.len()];
+        *buffer.write()? = data;
+
+        Ok(buffer.clone())
+    }
+}
+
 #[derive(Derivative)]
 #[derivative(Debug)]
 pub struct VulkanRendererState {
@@ -33,10 +428,20 @@ pub struct VulkanRendererState {
     #[derivative(Debug = "ignore")]
     debug_callback: Option<DebugCallback>,
 
+    // only available with VK_EXT_debug_utils
+    #[derivative(Debug = "ignore")]
+    set_debug_name: Option<SetDebugUtilsObjectNameFn>,
+
     device: Arc<Device>,
 
     graphics_queue: Arc<Queue>,
 
+    // additional queues from the graphics family for parallel submission
+    extra_graphics_queues: Vec<Arc<Queue>>,
+
+    // falls back to the graphics queue if there's no dedicated transfer family
+    transfer_queue: Arc<Queue>,
+
     surface: Arc<Surface<Window>>,
 
     #[derivative(Debug = "ignore")]
@@ -47,49 +452,288 @@ pub struct VulkanRendererState {
 
     current_swapchain_image: usize,
 
+    swapchain_format: (Format, ColorSpace),
+
+    // frame slot that last rendered to each swapchain image
+    image_frames: Vec<Option<usize>>,
+
     dynamic_state: DynamicState,
 
+    // one fenced submission per frame in flight, waited on before the slot is reused
+    #[derivative(Debug = "ignore")]
+    frame_fences: Vec<Option<FenceSignalFuture<Box<dyn GpuFuture>>>>,
+    current_frame: usize,
+
+    #[derivative(Debug = "ignore")]
+    cached_command_buffers: HashMap<DrawItemKey, Arc<AutoCommandBuffer>>,
+    command_buffers_dirty: bool,
+    recorded_command_buffer_count: u64,
+
+    sampler_anisotropy: f32,
+    max_push_constants_size: u32,
+
+    #[derivative(Debug = "ignore")]
+    memory: Mutex<MemoryTracker>,
+
+    #[derivative(Debug = "ignore")]
+    vertex_buffer_pool: Mutex<VertexBufferPool>,
+
+    // scene subpass target of each frame buffer, read by the post-process subpass
+    #[derivative(Debug = "ignore")]
+    scene_color_images: Vec<Arc<AttachmentImage<R16G16B16A16Sfloat>>>,
+
     #[derivative(Debug = "ignore")]
-    frame_future: Option<Box<dyn GpuFuture>>,
+    skybox_uniforms: UniformRing<shaders::skybox::vs::ty::Data>,
+
+    #[derivative(Debug = "ignore")]
+    shadow_uniforms: UniformRing<shaders::instanced::vs::ty::Shadow>,
+}
+
+// drops (and warns about) any requested layers that aren't available
+fn filter_layers<'a>(requested: &'a [String], supported: &[&str]) -> Vec<&'a str> {
+    let mut layers: Vec<&str> = Vec::new();
+    for layer in requested {
+        if layers.contains(&layer.as_str()) {
+            continue;
+        }
+
+        if !supported.contains(&layer.as_str()) {
+            warn!("Layer {} is not available", layer);
+            continue;
+        }
+
+        layers.push(layer);
+    }
+    layers
+}
+
+fn to_cstrings(names: &[String]) -> anyhow::Result<Vec<CString>> {
+    Ok(names
+        .iter()
+        .map(|x| CString::new(x.as_str()))
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+fn supports_device_extensions(physical_device: PhysicalDevice, extensions: &[CString]) -> bool {
+    let supported = RawDeviceExtensions::supported_by_device(physical_device);
+    extensions
+        .iter()
+        .all(|x| supported.iter().any(|supported| supported == x))
+}
+
+const DEFAULT_QUEUE_PRIORITY: f32 = 0.5;
+
+fn graphics_queue_priorities(requested: &[f32], queues_count: usize) -> anyhow::Result<Vec<f32>> {
+    if requested.is_empty() {
+        return Ok(vec![DEFAULT_QUEUE_PRIORITY]);
+    }
+
+    if requested.len() > queues_count {
+        bail!(
+            "Requested {} graphics queues but the queue family only supports {}",
+            requested.len(),
+            queues_count
+        );
+    }
+
+    if let Some(priority) = requested.iter().find(|x| !(0.0..=1.0).contains(*x)) {
+        bail!("Queue priority {} is outside of 0..1", priority);
+    }
+
+    Ok(requested.to_vec())
+}
+
+// prefers a transfer-only family, then any non-graphics family that supports transfers
+fn find_transfer_queue_family(physical_device: PhysicalDevice) -> Option<QueueFamily> {
+    physical_device
+        .queue_families()
+        .find(|&q| {
+            q.explicitly_supports_transfers() && !q.supports_graphics() && !q.supports_compute()
+        })
+        .or_else(|| {
+            physical_device
+                .queue_families()
+                .find(|&q| q.explicitly_supports_transfers() && !q.supports_graphics())
+        })
+}
+
+fn debug_message_level(ty: &MessageTypes) -> log::Level {
+    if ty.error {
+        log::Level::Error
+    } else if ty.warning || ty.performance_warning {
+        log::Level::Warn
+    } else if ty.information {
+        log::Level::Info
+    } else {
+        log::Level::Debug
+    }
+}
+
+fn check_push_constants_size(size: usize, max: u32) -> anyhow::Result<()> {
+    if size > max as usize {
+        bail!(
+            "Push constants size {} bytes exceeds the device max of {} bytes",
+            size,
+            max
+        );
+    }
+
+    Ok(())
+}
+
+// falls back to 1 (plain trilinear) when unsupported
+fn clamp_anisotropy(requested: f32, supported: bool, max: f32) -> f32 {
+    if !supported || requested <= 1.0 {
+        return 1.0;
+    }
+
+    if requested > max {
+        warn!(
+            "Anisotropy {} exceeds the device max {}, clamping",
+            requested, max
+        );
+        return max;
+    }
+
+    requested
+}
+
+// high precision formats only used if HDR output is requested
+const HDR_SWAPCHAIN_FORMATS: [Format; 2] =
+    [Format::R16G16B16A16Sfloat, Format::A2B10G10R10UnormPack32];
+
+const SRGB_SWAPCHAIN_FORMATS: [Format; 2] = [Format::B8G8R8A8Srgb, Format::R8G8B8A8Srgb];
+
+// vulkano always creates the swapchain in this color space,
+// so formats the surface only reports with other color spaces can't be used
+const SWAPCHAIN_COLOR_SPACE: ColorSpace = ColorSpace::SrgbNonLinear;
+
+// falls back to the first usable format the surface lists
+fn select_swapchain_format(
+    supported: &[(Format, ColorSpace)],
+    prefer_hdr: bool,
+) -> Option<(Format, ColorSpace)> {
+    let supported = supported
+        .iter()
+        .filter(|(_, color_space)| *color_space == SWAPCHAIN_COLOR_SPACE)
+        .copied()
+        .collect::<Vec<_>>();
+
+    let find = |formats: &[Format]| {
+        formats
+            .iter()
+            .filter_map(|format| supported.iter().find(|(f, _)| f == format))
+            .next()
+            .copied()
+    };
+
+    if prefer_hdr {
+        if let Some(format) = find(&HDR_SWAPCHAIN_FORMATS) {
+            return Some(format);
+        }
+        warn!("No HDR swapchain format available, falling back to sRGB");
+    }
+
+    find(&SRGB_SWAPCHAIN_FORMATS).or_else(|| supported.first().copied())
+}
+
+// 0 requests one more than the minimum, max is None if unbounded
+fn clamp_swapchain_image_count(requested: u32, min: u32, max: Option<u32>) -> u32 {
+    let requested = if requested == 0 { min + 1 } else { requested };
+
+    let count = requested.max(min);
+    let count = match max {
+        Some(max) => count.min(max),
+        None => count,
+    };
+
+    if count != requested {
+        warn!(
+            "Swapchain image count {} not supported, using {}",
+            requested, count
+        );
+    }
+
+    count
+}
+
+fn app_info(appid: &str, app_version: [u16; 3]) -> ApplicationInfo<'_> {
+    ApplicationInfo {
+        application_name: Some(appid.into()),
+        application_version: Some(Version {
+            major: app_version[0],
+            minor: app_version[1],
+            patch: app_version[2],
+        }),
+        engine_name: None,
+        engine_version: None,
+    }
 }
 
 impl VulkanRendererState {
-    pub fn new(events_loop: &EventsLoop) -> anyhow::Result<Self> {
-        // TODO: pass in the values rather than pulling from cargo
-        let app_info = vulkano::app_info_from_cargo_toml!();
+    // checks for a usable Vulkan loader
+    pub fn is_available() -> bool {
+        InstanceExtensions::supported_by_core().is_ok()
+    }
+
+    pub fn new(
+        events_loop: &EventsLoop,
+        appid: &str,
+        app_version: [u16; 3],
+        config: &RendererConfig,
+    ) -> anyhow::Result<Self> {
+        let app_info = app_info(appid, app_version);
 
         let supported_instance_extensions = InstanceExtensions::supported_by_core()?;
 
+        let required_instance_extensions = to_cstrings(&config.instance_extensions)?;
+        let supported_raw_instance_extensions = RawInstanceExtensions::supported_by_core()?;
+        let missing_instance_extensions = required_instance_extensions
+            .iter()
+            .filter(|x| {
+                !supported_raw_instance_extensions
+                    .iter()
+                    .any(|supported| supported == *x)
+            })
+            .map(|x| x.to_string_lossy())
+            .collect::<Vec<_>>();
+        if !missing_instance_extensions.is_empty() {
+            bail!(
+                "Required instance extensions not supported: {:?}",
+                missing_instance_extensions
+            );
+        }
+
         let mut extensions = vulkano_win::required_extensions();
-        // TODO: what about application-required extensions?
 
-        if cfg!(feature = "validation") {
+        let validation = cfg!(feature = "validation") || config.validation;
+        if validation {
             warn!("Enabling debug reporting...");
             extensions.ext_debug_report = true;
         }
 
         let supported_layers: Vec<LayerProperties> = vulkano::instance::layers_list()?.collect();
+        let supported_layers: Vec<&str> = supported_layers.iter().map(|x| x.name()).collect();
 
-        // TODO: layers would be better dealt with using a combination
-        // of features and VK_INSTANCE_LAYERS to turn specific ones on or off
-
-        let mut layers = Vec::new();
-        if cfg!(feature = "validation") {
+        let mut requested_layers = config.layers.clone();
+        if validation {
             warn!("Enabling validation...");
-            //layers.push("VK_LAYER_LUNARG_core_validation");
-            layers.push("VK_LAYER_LUNARG_standard_validation");
+            //requested_layers.push("VK_LAYER_LUNARG_core_validation".to_owned());
+            requested_layers.push("VK_LAYER_LUNARG_standard_validation".to_owned());
         }
 
         if cfg!(feature = "vktrace") {
             warn!("Enabling vktrace...");
-            layers.push("VK_LAYER_LUNARG_vktrace");
+            requested_layers.push("VK_LAYER_LUNARG_vktrace".to_owned());
         }
 
         if cfg!(feature = "renderdoc") {
             warn!("Enabling renderdoc capture...");
-            layers.push("VK_LAYER_RENDERDOC_Capture");
+            requested_layers.push("VK_LAYER_RENDERDOC_Capture".to_owned());
         }
 
+        let layers = filter_layers(&requested_layers, &supported_layers);
+
         info!(
             "Initializing Vulkan renderer...
 \tApp Info: {:?}
@@ -97,20 +741,46 @@ impl VulkanRendererState {
 \tRequested Extensions: {:?}
 \tSupported Layers: {:?}
 \tRequested Layers: {:?}",
-            app_info,
-            supported_instance_extensions,
-            extensions,
-            supported_layers
-                .iter()
-                .map(|x| x.name())
-                .collect::<Vec<_>>(),
-            layers,
+            app_info, supported_instance_extensions, extensions, supported_layers, layers,
         );
-        let instance = Instance::new(Some(&app_info), &extensions, layers)?;
+        // object names show up in validation messages and capture tools
+        let debug_utils = CString::new(DEBUG_UTILS_EXTENSION)?;
+        let debug_utils = if supported_raw_instance_extensions
+            .iter()
+            .any(|supported| *supported == debug_utils)
+        {
+            info!("Enabling debug object names...");
+            vec![debug_utils]
+        } else {
+            Vec::new()
+        };
+        let debug_names = !debug_utils.is_empty();
+
+        let extensions = RawInstanceExtensions::from(&extensions)
+            .union(&RawInstanceExtensions::new(required_instance_extensions))
+            .union(&RawInstanceExtensions::new(debug_utils));
+        let instance = Instance::new(Some(&app_info), extensions, layers)?;
 
-        let debug_callback = if cfg!(feature = "validation") {
-            Some(DebugCallback::errors_and_warnings(&instance, |msg| {
-                warn!("Debug callback: {:?}", msg.description);
+        let set_debug_name = if debug_names {
+            load_set_debug_name(&instance)
+        } else {
+            None
+        };
+
+        let debug_callback = if validation {
+            let user_callback = config.debug_callback.clone();
+            Some(DebugCallback::errors_and_warnings(&instance, move |msg| {
+                let level = debug_message_level(&msg.ty);
+                log!(
+                    level,
+                    "Debug callback ({}): {}",
+                    msg.layer_prefix,
+                    msg.description
+                );
+
+                if let Some(user_callback) = &user_callback {
+                    user_callback(level, msg.layer_prefix, msg.description);
+                }
             })?)
         } else {
             None
@@ -119,12 +789,18 @@ impl VulkanRendererState {
         info!("Creating surface...");
         let surface = WindowBuilder::new().build_vk_surface(events_loop, instance.clone())?;
 
-        // TODO: need to do application requirement filtering here
-        // and should allow the application to select between
-        // all devices that fit within those constraints
+        let required_device_extensions = to_cstrings(&config.device_extensions)?;
+
+        // TODO: should allow the application to select between
+        // all devices that fit within the application requirements
         let physical_device = PhysicalDevice::enumerate(&instance)
-            .next()
-            .ok_or_else(|| anyhow!("No devices available!"))?;
+            .find(|&p| supports_device_extensions(p, &required_device_extensions))
+            .ok_or_else(|| {
+                anyhow!(
+                    "No devices available supporting required extensions {:?}!",
+                    config.device_extensions
+                )
+            })?;
 
         let supported_device_extensions = DeviceExtensions::supported_by_device(physical_device);
 
@@ -142,24 +818,67 @@ impl VulkanRendererState {
             supported_device_extensions,
         );
 
+        // prefer a family that can also run compute dispatches
         let graphics_queue_family = physical_device
             .queue_families()
-            .find(|&q| q.supports_graphics() && surface.is_supported(q).unwrap_or(false))
+            .filter(|&q| q.supports_graphics() && surface.is_supported(q).unwrap_or(false))
+            .min_by_key(|q| !q.supports_compute())
             .ok_or_else(|| anyhow!("No graphics queues available!"))?;
 
-        let device_ext = DeviceExtensions {
+        let device_ext = RawDeviceExtensions::from(&DeviceExtensions {
             khr_swapchain: true,
             ..DeviceExtensions::none()
-        };
+        })
+        .union(&RawDeviceExtensions::new(required_device_extensions));
+
+        let transfer_queue_family = find_transfer_queue_family(physical_device);
+        match transfer_queue_family {
+            Some(q) => info!("Using dedicated transfer queue family {}", q.id()),
+            None => info!("No dedicated transfer queue family, using graphics queue"),
+        }
+
+        let priorities = graphics_queue_priorities(
+            &config.graphics_queue_priorities,
+            graphics_queue_family.queues_count(),
+        )?;
+        info!("Requesting {} graphics queues", priorities.len());
+
+        let mut queue_families = priorities
+            .iter()
+            .map(|priority| (graphics_queue_family, *priority))
+            .collect::<Vec<_>>();
+        if let Some(transfer_queue_family) = transfer_queue_family {
+            queue_families.push((transfer_queue_family, DEFAULT_QUEUE_PRIORITY));
+        }
 
         info!("Creating logical device...");
-        let (device, mut graphics_queues) = Device::new(
+        let (device, mut queues) = Device::new(
             physical_device,
             physical_device.supported_features(),
-            &device_ext,
-            [(graphics_queue_family, 0.5)].iter().cloned(),
+            device_ext,
+            queue_families.iter().cloned(),
         )?;
-        let graphics_queue = graphics_queues.next().unwrap();
+        let graphics_queue = queues.next().unwrap();
+        let extra_graphics_queues = queues.by_ref().take(priorities.len() - 1).collect();
+        let transfer_queue = queues.next().unwrap_or_else(|| graphics_queue.clone());
+
+        let frames_in_flight = if config.frames_in_flight == 0 {
+            DEFAULT_FRAMES_IN_FLIGHT
+        } else {
+            config.frames_in_flight
+        };
+        info!("Using {} frames in flight", frames_in_flight);
+
+        let sampler_anisotropy = clamp_anisotropy(
+            config.anisotropy,
+            device.enabled_features().sampler_anisotropy,
+            physical_device.limits().max_sampler_anisotropy(),
+        );
+        if config.anisotropy > 1.0 && sampler_anisotropy <= 1.0 {
+            warn!("Anisotropic filtering not supported, using trilinear filtering");
+        }
+
+        let max_push_constants_size = physical_device.limits().max_push_constants_size();
 
         let capabilities = surface.capabilities(physical_device)?;
         let alpha = capabilities
@@ -167,7 +886,10 @@ impl VulkanRendererState {
             .iter()
             .next()
             .unwrap();
-        let format = capabilities.supported_formats[0].0;
+        let (format, color_space) =
+            select_swapchain_format(&capabilities.supported_formats, config.hdr)
+                .ok_or_else(|| anyhow!("Surface has no supported formats"))?;
+        info!("Using swapchain format {:?} ({:?})", format, color_space);
 
         // TODO: use Fifo if vsync
         let present_mode = if capabilities.present_modes.supports(PresentMode::Mailbox) {
@@ -178,11 +900,17 @@ impl VulkanRendererState {
 
         // TODO: comb over https://vulkan-tutorial.com/en/Drawing_a_triangle/Presentation/Swap_chain
 
-        info!("Creating swapchain...");
+        let image_count = clamp_swapchain_image_count(
+            config.swapchain_image_count,
+            capabilities.min_image_count,
+            capabilities.max_image_count,
+        );
+
+        info!("Creating swapchain with {} images...", image_count);
         let (swapchain, swapchain_images) = Swapchain::new(
             device.clone(),
             surface.clone(),
-            capabilities.min_image_count + 1,
+            image_count,
             format,
             crate::get_window_dimensions(surface.window())?,
             1,
@@ -195,17 +923,50 @@ impl VulkanRendererState {
             None,
         )?;
 
+        let skybox_uniforms = UniformRing::new(
+            &device,
+            frames_in_flight,
+            shaders::skybox::vs::ty::Data {
+                view_projection: [[0.0; 4]; 4],
+            },
+        )?;
+
+        let shadow_uniforms = UniformRing::new(
+            &device,
+            frames_in_flight,
+            shaders::instanced::vs::ty::Shadow {
+                view_projection: [[0.0; 4]; 4],
+                bias: 0.0,
+            },
+        )?;
+
         Ok(Self {
             instance,
             debug_callback,
+            set_debug_name,
             device,
             graphics_queue,
+            extra_graphics_queues,
+            transfer_queue,
             surface,
             swapchain,
+            image_frames: vec![None; swapchain_images.len()],
             swapchain_images,
             current_swapchain_image: 0,
+            swapchain_format: (format, color_space),
             dynamic_state: DynamicState::none(),
-            frame_future: None,
+            frame_fences: (0..frames_in_flight).map(|_| None).collect(),
+            current_frame: 0,
+            cached_command_buffers: HashMap::new(),
+            command_buffers_dirty: true,
+            recorded_command_buffer_count: 0,
+            sampler_anisotropy,
+            max_push_constants_size,
+            memory: Mutex::new(MemoryTracker::default()),
+            vertex_buffer_pool: Mutex::new(VertexBufferPool::default()),
+            scene_color_images: Vec::new(),
+            skybox_uniforms,
+            shadow_uniforms,
         })
     }
 
@@ -213,12 +974,16 @@ impl VulkanRendererState {
         &self.device
     }
 
+    pub(crate) fn graphics_queue_count(&self) -> usize {
+        1 + self.extra_graphics_queues.len()
+    }
+
     pub(crate) fn get_window(&self) -> &Window {
         self.surface.window()
     }
 
     pub(crate) fn init_viewport(&mut self) {
-        let dimensions = self.swapchain_images[0].dimensions();
+        let dimensions = SwapchainImage::dimensions(&self.swapchain_images[0]);
 
         let viewport = Viewport {
             origin: [0.0, 0.0],
@@ -226,14 +991,19 @@ impl VulkanRendererState {
             depth_range: 0.0..1.0,
         };
         self.dynamic_state.viewports = Some(vec![viewport]);
+
+        // cached command buffers have the old viewport baked in
+        self.command_buffers_dirty = true;
     }
 
     pub(crate) fn recreate_swapchain(&mut self) -> anyhow::Result<bool> {
+        let dimensions = crate::get_window_dimensions(self.surface.window())?;
+        if crate::is_minimized_dimensions(dimensions) {
+            return Ok(false);
+        }
+
         info!("Recreating swapchain...");
-        let (new_swapchain, new_images) = match self
-            .swapchain
-            .recreate_with_dimension(crate::get_window_dimensions(self.surface.window())?)
-        {
+        let (new_swapchain, new_images) = match self.swapchain.recreate_with_dimension(dimensions) {
             Ok(r) => r,
             // This error tends to happen when the user is manually resizing the window.
             // Simply restarting the loop is the easiest way to fix this issue.
@@ -242,37 +1012,241 @@ impl VulkanRendererState {
         };
 
         self.swapchain = new_swapchain;
+        self.image_frames = vec![None; new_images.len()];
         self.swapchain_images = new_images;
 
         Ok(true)
     }
 
-    //#region CPU Buffers
+    //#region Debug Names
 
-    pub fn create_cpu_buffer<T>(&self, data: T) -> anyhow::Result<Arc<CpuAccessibleBuffer<T>>>
-    where
-        T: Content + 'static,
-    {
-        Ok(CpuAccessibleBuffer::from_data(
-            self.device.clone(),
-            BufferUsage::all(),
-            data,
-        )?)
+    pub(crate) fn has_debug_names(&self) -> bool {
+        self.set_debug_name.is_some()
     }
 
-    pub fn create_cpu_buffer_iter<V, T>(
-        &self,
-        data: V,
-    ) -> anyhow::Result<Arc<CpuAccessibleBuffer<[T]>>>
-    where
-        V: Into<Vec<T>>,
-        T: Content + Clone + 'static,
-    {
-        Ok(CpuAccessibleBuffer::from_iter(
+    fn set_debug_name(&self, object_type: DebugObjectType, handle: u64, name: &str) {
+        let set_debug_name = match self.set_debug_name {
+            Some(set_debug_name) => set_debug_name,
+            None => return,
+        };
+
+        let name = match CString::new(name) {
+            Ok(name) => name,
+            Err(err) => {
+                warn!("Invalid debug name {:?}: {}", name, err);
+                return;
+            }
+        };
+
+        let info = DebugUtilsObjectNameInfo {
+            s_type: STRUCTURE_TYPE_DEBUG_UTILS_OBJECT_NAME_INFO,
+            p_next: std::ptr::null(),
+            object_type: object_type as u32,
+            object_handle: handle,
+            p_object_name: name.as_ptr(),
+        };
+
+        let result = unsafe { set_debug_name(self.device.internal_object(), &info) };
+        if result != 0 {
+            warn!("Failed to set debug name {:?}: {}", name, result);
+        }
+    }
+
+    pub(crate) fn set_buffer_debug_name(
+        &self,
+        buffer: &Arc<dyn BufferAccess + Send + Sync>,
+        name: &str,
+    ) {
+        let handle = buffer.inner().buffer.internal_object();
+        self.set_debug_name(DebugObjectType::Buffer, handle, name);
+    }
+
+    pub(crate) fn set_image_debug_name(
+        &self,
+        image: &Arc<dyn ImageViewAccess + Send + Sync>,
+        name: &str,
+    ) {
+        let handle = image.parent().inner().image.internal_object();
+        self.set_debug_name(DebugObjectType::Image, handle, name);
+    }
+
+    pub(crate) fn set_pipeline_debug_name(
+        &self,
+        pipeline: &Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+        name: &str,
+    ) {
+        let handle = GraphicsPipelineAbstract::inner(pipeline).internal_object();
+        self.set_debug_name(DebugObjectType::Pipeline, handle, name);
+    }
+
+    //#endregion
+
+    //#region Memory Stats
+
+    fn track_buffer(&self, buffer: Arc<dyn BufferAccess + Send + Sync>, size: usize) {
+        if let Ok(mut memory) = self.memory.lock() {
+            memory.buffers.push((Arc::downgrade(&buffer), size));
+        }
+    }
+
+    fn track_image(&self, image: Arc<dyn ImageViewAccess + Send + Sync>, size: usize) {
+        if let Ok(mut memory) = self.memory.lock() {
+            memory.images.push((Arc::downgrade(&image), size));
+        }
+    }
+
+    pub(crate) fn memory_stats(&self) -> MemoryStats {
+        self.memory
+            .lock()
+            .map(|mut memory| memory.stats())
+            .unwrap_or_default()
+    }
+
+    //#endregion
+
+    //#region CPU Buffers
+
+    pub fn create_cpu_buffer<T>(
+        &self,
+        data: T,
+        usage: BufferUsage,
+    ) -> anyhow::Result<Arc<CpuAccessibleBuffer<T>>>
+    where
+        T: Content + Send + Sync + 'static,
+    {
+        let buffer = CpuAccessibleBuffer::from_data(self.device.clone(), usage, data)?;
+        self.track_buffer(buffer.clone(), std::mem::size_of::<T>());
+
+        Ok(buffer)
+    }
+
+    pub fn create_cpu_buffer_iter<V, T>(
+        &self,
+        data: V,
+        usage: BufferUsage,
+    ) -> anyhow::Result<Arc<CpuAccessibleBuffer<[T]>>>
+    where
+        V: Into<Vec<T>>,
+        T: Content + Clone + Send + Sync + 'static,
+    {
+        let data = data.into();
+        let size = std::mem::size_of::<T>() * data.len();
+
+        let buffer = CpuAccessibleBuffer::from_iter(self.device.clone(), usage, data.into_iter())?;
+        self.track_buffer(buffer.clone(), size);
+
+        Ok(buffer)
+    }
+
+    //#endregion
+
+    //#region Buffer Pool
+
+    pub(crate) fn acquire_pooled_buffer(
+        &self,
+        vertices: &[Vertex],
+    ) -> anyhow::Result<Arc<CpuAccessibleBuffer<[Vertex]>>> {
+        let size_class = pooled_size_class(vertices.len());
+
+        let pooled = self
+            .vertex_buffer_pool
+            .lock()
+            .map_err(|_| anyhow!("Vertex buffer pool poisoned"))?
+            .free
+            .get_mut(&size_class)
+            .and_then(|free| free.pop());
+
+        let buffer = match pooled {
+            Some(buffer) => buffer,
+            None => self.create_cpu_buffer_iter(
+                vec![Vertex::default(); size_class],
+                BufferUsage::vertex_buffer(),
+            )?,
+        };
+
+        buffer.write()?[..vertices.len()].copy_from_slice(vertices);
+
+        Ok(buffer)
+    }
+
+    // only the first len vertices are drawn
+    pub(crate) fn pooled_buffer_slice(
+        buffer: &Arc<CpuAccessibleBuffer<[Vertex]>>,
+        len: usize,
+    ) -> anyhow::Result<Arc<dyn BufferAccess + Send + Sync>> {
+        let slice = BufferSlice::from_typed_buffer_access(buffer.clone())
+            .slice(0..len)
+            .ok_or_else(|| anyhow!("Pooled buffer too small for {} vertices", len))?;
+
+        Ok(Arc::new(slice))
+    }
+
+    pub(crate) fn release_pooled_buffer(&self, buffer: Arc<CpuAccessibleBuffer<[Vertex]>>) {
+        if let Ok(mut pool) = self.vertex_buffer_pool.lock() {
+            pool.free.entry(buffer.len()).or_default().push(buffer);
+        }
+    }
+
+    pub(crate) fn pooled_buffer_count(&self) -> usize {
+        self.vertex_buffer_pool
+            .lock()
+            .map(|pool| pool.free.values().map(Vec::len).sum())
+            .unwrap_or(0)
+    }
+
+    //#endregion
+
+    //#region GPU Buffers
+
+    fn buffer_queue_families(&self) -> Vec<QueueFamily<'_>> {
+        let mut queue_families = vec![self.graphics_queue.family()];
+        if self.transfer_queue.family().id() != self.graphics_queue.family().id() {
+            queue_families.push(self.transfer_queue.family());
+        }
+        queue_families
+    }
+
+    pub fn create_device_local_buffer_iter<V, T>(
+        &self,
+        data: V,
+        usage: BufferUsage,
+    ) -> anyhow::Result<Arc<DeviceLocalBuffer<[T]>>>
+    where
+        V: Into<Vec<T>>,
+        T: Content + Clone + Send + Sync + 'static,
+    {
+        let data = data.into();
+
+        let staging_buffer = CpuAccessibleBuffer::from_iter(
             self.device.clone(),
-            BufferUsage::all(),
-            data.into().iter().cloned(),
-        )?)
+            BufferUsage::transfer_source(),
+            data.iter().cloned(),
+        )?;
+
+        let buffer = DeviceLocalBuffer::array(
+            self.device.clone(),
+            data.len(),
+            BufferUsage {
+                transfer_destination: true,
+                ..usage
+            },
+            self.buffer_queue_families(),
+        )?;
+
+        let command_buffer = self
+            .create_transfer_command_buffer()?
+            .copy_buffer(staging_buffer, buffer.clone())?
+            .build()?;
+
+        // TODO: don't block on the upload
+        command_buffer
+            .execute(self.transfer_queue.clone())?
+            .then_signal_fence_and_flush()?
+            .wait(None)?;
+
+        self.track_buffer(buffer.clone(), std::mem::size_of::<T>() * data.len());
+
+        Ok(buffer)
     }
 
     //#endregion
@@ -286,13 +1260,99 @@ impl VulkanRendererState {
         format: F,
     ) -> anyhow::Result<Arc<StorageImage<F>>>
     where
-        F: FormatDesc,
+        F: FormatDesc + Send + Sync + 'static,
     {
-        Ok(StorageImage::new(
+        let image = StorageImage::new(
             self.device.clone(),
             Dimensions::Dim2d { width, height },
             format,
             Some(self.graphics_queue.family()),
+        )?;
+
+        // approximate, assumes 4 bytes per texel
+        self.track_image(image.clone(), width as usize * height as usize * 4);
+
+        Ok(image)
+    }
+
+    pub(crate) fn create_texture(
+        &self,
+        width: u32,
+        height: u32,
+        data: Vec<u8>,
+    ) -> anyhow::Result<Arc<dyn ImageViewAccess + Send + Sync>> {
+        let size_bytes = data.len();
+        let (image, future) = ImmutableImage::from_iter(
+            data.into_iter(),
+            Dimensions::Dim2d { width, height },
+            R8G8B8A8Srgb,
+            self.graphics_queue.clone(),
+        )?;
+
+        // TODO: don't block on the upload
+        future.then_signal_fence_and_flush()?.wait(None)?;
+
+        self.track_image(image.clone(), size_bytes);
+
+        Ok(image)
+    }
+
+    pub(crate) fn create_cubemap(
+        &self,
+        size: u32,
+        data: Vec<u8>,
+    ) -> anyhow::Result<Arc<dyn ImageViewAccess + Send + Sync>> {
+        let size_bytes = data.len();
+        let (image, future) = ImmutableImage::from_iter(
+            data.into_iter(),
+            Dimensions::Cubemap { size },
+            R8G8B8A8Srgb,
+            self.graphics_queue.clone(),
+        )?;
+
+        // TODO: don't block on the upload
+        future.then_signal_fence_and_flush()?.wait(None)?;
+
+        self.track_image(image.clone(), size_bytes);
+
+        Ok(image)
+    }
+
+    //#endregion
+
+    //#region Samplers
+
+    // trilinear, anisotropic if enabled
+    pub(crate) fn create_sampler(&self) -> anyhow::Result<Arc<Sampler>> {
+        Ok(Sampler::new(
+            self.device.clone(),
+            Filter::Linear,
+            Filter::Linear,
+            MipmapMode::Linear,
+            SamplerAddressMode::Repeat,
+            SamplerAddressMode::Repeat,
+            SamplerAddressMode::Repeat,
+            0.0,
+            self.sampler_anisotropy,
+            0.0,
+            1_000.0,
+        )?)
+    }
+
+    // unfiltered depth, nothing is shadowed past the edges
+    fn create_shadow_sampler(&self) -> anyhow::Result<Arc<Sampler>> {
+        Ok(Sampler::new(
+            self.device.clone(),
+            Filter::Nearest,
+            Filter::Nearest,
+            MipmapMode::Nearest,
+            SamplerAddressMode::ClampToEdge,
+            SamplerAddressMode::ClampToEdge,
+            SamplerAddressMode::ClampToEdge,
+            0.0,
+            1.0,
+            0.0,
+            0.0,
         )?)
     }
 
@@ -309,6 +1369,15 @@ impl VulkanRendererState {
         )?)
     }
 
+    pub fn create_transfer_command_buffer(
+        &self,
+    ) -> anyhow::Result<AutoCommandBufferBuilder<StandardCommandPoolBuilder>> {
+        Ok(AutoCommandBufferBuilder::primary_one_time_submit(
+            self.device.clone(),
+            self.transfer_queue.family(),
+        )?)
+    }
+
     pub(crate) fn create_primary_one_time_submit_command_buffer(
         &self,
     ) -> anyhow::Result<AutoCommandBufferBuilder<StandardCommandPoolBuilder>> {
@@ -318,28 +1387,257 @@ impl VulkanRendererState {
         )?)
     }
 
+    fn record_draw_item(
+        &mut self,
+        draw_item: &DrawItem,
+        dynamic_state: &DynamicState,
+    ) -> anyhow::Result<Arc<AutoCommandBuffer>> {
+        let render_pipeline = match draw_item.render_pipeline {
+            RenderPipeline::Vulkan(p) => p,
+            RenderPipeline::None => {
+                bail!("Invalid render pipeline type {}", draw_item.render_pipeline)
+            }
+        };
+
+        let mut vertex_buffers = vec![match draw_item.vertex_buffer {
+            VertexBuffer::Vulkan(v) => v.clone(),
+            VertexBuffer::None => bail!("Invalid vertex buffer type {}", draw_item.vertex_buffer),
+        }];
+        match draw_item.instance_buffer {
+            Some(VertexBuffer::Vulkan(v)) => vertex_buffers.push(v.clone()),
+            Some(VertexBuffer::None) => bail!("Invalid instance buffer type None"),
+            None => (),
+        }
+
+        let descriptor_sets = match draw_item.descriptor_set {
+            DescriptorSet::Vulkan(s) => vec![s.clone()],
+            DescriptorSet::None => Vec::new(),
+        };
+
+        let builder = AutoCommandBufferBuilder::secondary_graphics_simultaneous_use(
+            self.device.clone(),
+            self.graphics_queue.family(),
+            render_pipeline.clone().subpass(),
+        )?;
+
+        let builder = match draw_item.push_constants {
+            Some(push_constants) => builder.draw(
+                render_pipeline.clone(),
+                dynamic_state,
+                vertex_buffers,
+                descriptor_sets,
+                push_constants,
+            )?,
+            None => builder.draw(
+                render_pipeline.clone(),
+                dynamic_state,
+                vertex_buffers,
+                descriptor_sets,
+                (),
+            )?,
+        };
+        let command_buffer = builder.build()?;
+
+        self.recorded_command_buffer_count += 1;
+
+        Ok(Arc::new(command_buffer))
+    }
+
+    fn record_post_process(
+        &mut self,
+        post_process: &PostProcessItem,
+        frame_buffer: usize,
+    ) -> anyhow::Result<AutoCommandBuffer> {
+        let render_pipeline = match post_process.render_pipeline {
+            RenderPipeline::Vulkan(p) => p,
+            RenderPipeline::None => {
+                bail!(
+                    "Invalid render pipeline type {}",
+                    post_process.render_pipeline
+                )
+            }
+        };
+
+        let descriptor_sets = match post_process.descriptor_sets.get(frame_buffer) {
+            Some(DescriptorSet::Vulkan(s)) => vec![s.clone()],
+            Some(DescriptorSet::None) => Vec::new(),
+            None => bail!(
+                "No post-process descriptor set for frame buffer {}",
+                frame_buffer
+            ),
+        };
+
+        let command_buffer = AutoCommandBufferBuilder::secondary_graphics_one_time_submit(
+            self.device.clone(),
+            self.graphics_queue.family(),
+            render_pipeline.clone().subpass(),
+        )?
+        .draw(
+            render_pipeline.clone(),
+            &self.dynamic_state,
+            Vec::new(),
+            descriptor_sets,
+            (),
+        )?
+        .build()?;
+
+        self.recorded_command_buffer_count += 1;
+
+        Ok(command_buffer)
+    }
+
+    // secondary command buffers are reused for as long as the items are drawn every frame
+    fn execute_draw_items(
+        &mut self,
+        mut command_buffer: AutoCommandBufferBuilder<StandardCommandPoolBuilder>,
+        draw_items: &[DrawItem],
+        dynamic_state: &DynamicState,
+        used_keys: &mut HashSet<DrawItemKey>,
+    ) -> anyhow::Result<AutoCommandBufferBuilder<StandardCommandPoolBuilder>> {
+        for draw_item in draw_items {
+            let key = DrawItemKey::new(draw_item)?;
+            used_keys.insert(key);
+
+            let secondary = match self.cached_command_buffers.get(&key) {
+                Some(secondary) => secondary.clone(),
+                None => {
+                    let secondary = self.record_draw_item(draw_item, dynamic_state)?;
+                    self.cached_command_buffers.insert(key, secondary.clone());
+                    secondary
+                }
+            };
+
+            // vulkano doesn't validate secondary buffers yet, these were recorded
+            // against this subpass and keep their own resources alive
+            command_buffer = unsafe { command_buffer.execute_commands(secondary)? };
+        }
+
+        Ok(command_buffer)
+    }
+
+    pub(crate) fn set_command_buffers_dirty(&mut self) {
+        self.command_buffers_dirty = true;
+    }
+
+    pub(crate) fn recorded_command_buffer_count(&self) -> u64 {
+        self.recorded_command_buffer_count
+    }
+
     //#endregion
 
     //#region Render Pass
 
+    // the scene draws into an HDR target in the first subpass,
+    // the post-process subpass writes it to the swapchain
     pub(crate) fn create_simple_render_pass(&self) -> anyhow::Result<RenderPass> {
         Ok(RenderPass::Vulkan(Arc::new(
-            vulkano::single_pass_renderpass!(
+            vulkano::ordered_passes_renderpass!(
                self.device.clone(),
                 attachments: {
                     color: {
-                        load: Clear,
+                        load: DontCare,
                         store: Store,
                         format: self.swapchain.format(),
                         samples: 1,
+                    },
+                    scene_color: {
+                        load: Clear,
+                        store: DontCare,
+                        format: Format::R16G16B16A16Sfloat,
+                        samples: 1,
+                    },
+                    depth: {
+                        load: Clear,
+                        store: DontCare,
+                        format: Format::D16Unorm,
+                        samples: 1,
+                    }
+                },
+                passes: [
+                    {
+                        color: [scene_color],
+                        depth_stencil: {depth},
+                        input: []
+                    },
+                    {
+                        color: [color],
+                        depth_stencil: {},
+                        input: [scene_color]
+                    }
+                ]
+            )?,
+        )))
+    }
+
+    pub(crate) fn create_shadow_render_pass(&self) -> anyhow::Result<RenderPass> {
+        Ok(RenderPass::Vulkan(Arc::new(
+            ShadowRenderPassDesc.build_render_pass(self.device.clone())?,
+        )))
+    }
+
+    pub(crate) fn create_offscreen_render_pass(&self, samples: u32) -> anyhow::Result<RenderPass> {
+        Ok(RenderPass::Vulkan(
+            self.build_offscreen_render_pass(samples)?,
+        ))
+    }
+
+    // multisampled color is resolved into a single sampled attachment
+    fn build_offscreen_render_pass(
+        &self,
+        samples: u32,
+    ) -> anyhow::Result<Arc<dyn RenderPassAbstract + Send + Sync>> {
+        Ok(if samples > 1 {
+            Arc::new(vulkano::single_pass_renderpass!(
+                self.device.clone(),
+                attachments: {
+                    multisampled_color: {
+                        load: Clear,
+                        store: DontCare,
+                        format: Format::R8G8B8A8Unorm,
+                        samples: samples,
+                    },
+                    color: {
+                        load: DontCare,
+                        store: Store,
+                        format: Format::R8G8B8A8Unorm,
+                        samples: 1,
+                    },
+                    depth: {
+                        load: Clear,
+                        store: DontCare,
+                        format: Format::D16Unorm,
+                        samples: samples,
+                    }
+                },
+                pass: {
+                    color: [multisampled_color],
+                    depth_stencil: {depth},
+                    resolve: [color]
+                }
+            )?)
+        } else {
+            Arc::new(vulkano::single_pass_renderpass!(
+                self.device.clone(),
+                attachments: {
+                    color: {
+                        load: Clear,
+                        store: Store,
+                        format: Format::R8G8B8A8Unorm,
+                        samples: 1,
+                    },
+                    depth: {
+                        load: Clear,
+                        store: DontCare,
+                        format: Format::D16Unorm,
+                        samples: 1,
                     }
                 },
                 pass: {
                     color: [color],
-                    depth_stencil: {}
+                    depth_stencil: {depth}
                 }
-            )?,
-        )))
+            )?)
+        })
     }
 
     //#endregion
@@ -352,14 +1650,36 @@ impl VulkanRendererState {
     ) -> anyhow::Result<Vec<FrameBuffer>> {
         Ok(match render_pass {
             RenderPass::Vulkan(rp) => {
+                let dimensions = SwapchainImage::dimensions(&self.swapchain_images[0]);
+                let texels = dimensions[0] as usize * dimensions[1] as usize;
+
                 let mut frame_buffers = Vec::new();
+                let mut scene_color_images = Vec::new();
                 for image in &self.swapchain_images {
+                    // one set per framebuffer, frames in flight can't share them
+                    let scene_color = AttachmentImage::transient_input_attachment(
+                        self.device.clone(),
+                        dimensions,
+                        R16G16B16A16Sfloat,
+                    )?;
+                    self.track_image(scene_color.clone(), texels * 8);
+
+                    let depth =
+                        AttachmentImage::transient(self.device.clone(), dimensions, D16Unorm)?;
+                    self.track_image(depth.clone(), texels * 2);
+
                     let frame_buffer = FrameBuffer::Vulkan(Arc::new(
-                        Framebuffer::start(rp.clone()).add(image.clone())?.build()?,
+                        Framebuffer::start(rp.clone())
+                            .add(image.clone())?
+                            .add(scene_color.clone())?
+                            .add(depth)?
+                            .build()?,
                     )
                         as Arc<dyn FramebufferAbstract + Send + Sync>);
                     frame_buffers.push(frame_buffer);
+                    scene_color_images.push(scene_color);
                 }
+                self.scene_color_images = scene_color_images;
 
                 frame_buffers
             }
@@ -367,41 +1687,640 @@ impl VulkanRendererState {
         })
     }
 
+    pub(crate) fn create_shadow_map(
+        &self,
+        render_pass: &RenderPass,
+        size: u32,
+    ) -> anyhow::Result<ShadowMap> {
+        Ok(match render_pass {
+            RenderPass::Vulkan(rp) => {
+                let image = AttachmentImage::sampled(self.device.clone(), [size, size], D16Unorm)?;
+                self.track_image(image.clone(), size as usize * size as usize * 2);
+
+                let frame_buffer =
+                    Arc::new(Framebuffer::start(rp.clone()).add(image.clone())?.build()?);
+
+                ShadowMap {
+                    texture: Texture::Vulkan(image),
+                    frame_buffer: FrameBuffer::Vulkan(frame_buffer),
+                    size,
+                }
+            }
+            _ => bail!("Render pass type {} not supported", render_pass),
+        })
+    }
+
     //#endregion
 
     //#region Pipeline
 
-    pub(crate) fn create_simple_render_pipeline(
+    fn check_pipeline_config(&self, config: &PipelineConfig) -> anyhow::Result<()> {
+        if config.polygon_mode != PolygonMode::Fill
+            && !self.device.enabled_features().fill_mode_non_solid
+        {
+            bail!(
+                "Polygon mode {:?} requires the fill_mode_non_solid device feature",
+                config.polygon_mode
+            );
+        }
+
+        Ok(())
+    }
+
+    // push constant ranges are checked once here rather than on every draw
+    fn check_push_constants<P>(&self, pipeline: &P) -> anyhow::Result<()>
+    where
+        P: PipelineLayoutDesc,
+    {
+        for idx in 0..pipeline.num_push_constants_ranges() {
+            if let Some(range) = pipeline.push_constants_range(idx) {
+                check_push_constants_size(range.offset + range.size, self.max_push_constants_size)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn create_render_pipeline<V>(
         &self,
         render_pass: &RenderPass,
         vs: shaders::simple::vs::Shader,
         fs: shaders::simple::fs::Shader,
-    ) -> anyhow::Result<RenderPipeline> {
+        config: &PipelineConfig,
+    ) -> anyhow::Result<RenderPipeline>
+    where
+        V: VertexLayout,
+    {
+        self.check_pipeline_config(config)?;
+
         Ok(match render_pass {
-            RenderPass::Vulkan(rp) => RenderPipeline::Vulkan(Arc::new(
-                GraphicsPipeline::start()
-                    .vertex_input_single_buffer::<Vertex>()
+            RenderPass::Vulkan(rp) => {
+                let subpass = Subpass::from(rp.clone(), 0).unwrap();
+                let has_depth = subpass.has_depth();
+
+                let builder = GraphicsPipeline::start()
+                    .vertex_input_single_buffer::<V>()
                     .vertex_shader(vs.main_entry_point(), ())
-                    .triangle_list()
+                    .primitive_topology(config.topology.into())
                     .viewports_dynamic_scissors_irrelevant(1)
                     .fragment_shader(fs.main_entry_point(), ())
-                    .render_pass(Subpass::from(rp.clone(), 0).unwrap())
-                    .build(self.device.clone())?,
-            )),
-            _ => bail!("Render pass type {} not supported", render_pass),
-        })
-    }
-
-    //#endregion
+                    .render_pass(subpass);
+                let builder = configure_pipeline!(builder, config, has_depth);
 
-    pub(crate) fn begin_frame(&mut self) {
-        match &mut self.frame_future {
-            Some(ref mut frame_future) => frame_future.cleanup_finished(),
-            None => {
-                self.frame_future =
-                    Some(Box::new(vulkano::sync::now(self.device.clone())) as Box<dyn GpuFuture>)
+                let pipeline = builder.build(self.device.clone())?;
+                self.check_push_constants(&pipeline)?;
+                RenderPipeline::Vulkan(Arc::new(pipeline))
             }
-        }
+            _ => bail!("Render pass type {} not supported", render_pass),
+        })
+    }
+
+    pub(crate) fn create_line_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::line::vs::Shader,
+        fs: shaders::line::fs::Shader,
+    ) -> anyhow::Result<RenderPipeline> {
+        Ok(match render_pass {
+            RenderPass::Vulkan(rp) => {
+                let pipeline = GraphicsPipeline::start()
+                    .vertex_input_single_buffer::<LineVertex>()
+                    .vertex_shader(vs.main_entry_point(), ())
+                    .line_list()
+                    .viewports_dynamic_scissors_irrelevant(1)
+                    .fragment_shader(fs.main_entry_point(), ())
+                    .render_pass(Subpass::from(rp.clone(), 0).unwrap())
+                    .build(self.device.clone())?;
+                self.check_push_constants(&pipeline)?;
+                RenderPipeline::Vulkan(Arc::new(pipeline))
+            }
+            _ => bail!("Render pass type {} not supported", render_pass),
+        })
+    }
+
+    // writes the far plane so it passes against the cleared depth
+    pub(crate) fn create_skybox_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::skybox::vs::Shader,
+        fs: shaders::skybox::fs::Shader,
+        depth_mode: DepthMode,
+    ) -> anyhow::Result<RenderPipeline> {
+        Ok(match render_pass {
+            RenderPass::Vulkan(rp) => {
+                let pipeline = GraphicsPipeline::start()
+                    .vertex_input_single_buffer::<Vertex>()
+                    .vertex_shader(
+                        vs.main_entry_point(),
+                        shaders::skybox::vs::SpecializationConstants {
+                            far_depth: depth_mode.clear_value(),
+                        },
+                    )
+                    .triangle_list()
+                    .viewports_dynamic_scissors_irrelevant(1)
+                    .fragment_shader(fs.main_entry_point(), ())
+                    .depth_stencil(DepthStencil {
+                        depth_compare: skybox_depth_compare(depth_mode),
+                        ..DepthStencil::simple_depth_test()
+                    })
+                    .render_pass(Subpass::from(rp.clone(), 0).unwrap())
+                    .build(self.device.clone())?;
+                self.check_push_constants(&pipeline)?;
+                RenderPipeline::Vulkan(Arc::new(pipeline))
+            }
+            _ => bail!("Render pass type {} not supported", render_pass),
+        })
+    }
+
+    pub(crate) fn create_sprite_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::sprite::vs::Shader,
+        fs: shaders::sprite::fs::Shader,
+    ) -> anyhow::Result<RenderPipeline> {
+        Ok(match render_pass {
+            RenderPass::Vulkan(rp) => {
+                let pipeline = GraphicsPipeline::start()
+                    .vertex_input_single_buffer::<MeshVertex>()
+                    .vertex_shader(vs.main_entry_point(), ())
+                    .triangle_list()
+                    .viewports_dynamic_scissors_irrelevant(1)
+                    .fragment_shader(fs.main_entry_point(), ())
+                    .blend_alpha_blending()
+                    .render_pass(Subpass::from(rp.clone(), 0).unwrap())
+                    .build(self.device.clone())?;
+                self.check_push_constants(&pipeline)?;
+                RenderPipeline::Vulkan(Arc::new(pipeline))
+            }
+            _ => bail!("Render pass type {} not supported", render_pass),
+        })
+    }
+
+    pub(crate) fn create_text_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::text::vs::Shader,
+        fs: shaders::text::fs::Shader,
+    ) -> anyhow::Result<RenderPipeline> {
+        Ok(match render_pass {
+            RenderPass::Vulkan(rp) => {
+                let pipeline = GraphicsPipeline::start()
+                    .vertex_input_single_buffer::<TextVertex>()
+                    .vertex_shader(vs.main_entry_point(), ())
+                    .triangle_list()
+                    .viewports_dynamic_scissors_irrelevant(1)
+                    .fragment_shader(fs.main_entry_point(), ())
+                    .blend_alpha_blending()
+                    .render_pass(Subpass::from(rp.clone(), 0).unwrap())
+                    .build(self.device.clone())?;
+                self.check_push_constants(&pipeline)?;
+                RenderPipeline::Vulkan(Arc::new(pipeline))
+            }
+            _ => bail!("Render pass type {} not supported", render_pass),
+        })
+    }
+
+    // runs in the post-process subpass
+    pub(crate) fn create_tonemap_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::tonemap::vs::Shader,
+        fs: shaders::tonemap::fs::Shader,
+    ) -> anyhow::Result<RenderPipeline> {
+        Ok(match render_pass {
+            RenderPass::Vulkan(rp) => {
+                let subpass = Subpass::from(rp.clone(), 1)
+                    .ok_or_else(|| anyhow!("Render pass has no post-process subpass"))?;
+
+                let pipeline = GraphicsPipeline::start()
+                    .vertex_input(FullscreenTriangleDefinition)
+                    .vertex_shader(vs.main_entry_point(), ())
+                    .triangle_list()
+                    .viewports_dynamic_scissors_irrelevant(1)
+                    .fragment_shader(fs.main_entry_point(), ())
+                    .render_pass(subpass)
+                    .build(self.device.clone())?;
+                self.check_push_constants(&pipeline)?;
+                RenderPipeline::Vulkan(Arc::new(pipeline))
+            }
+            _ => bail!("Render pass type {} not supported", render_pass),
+        })
+    }
+
+    // one per frame buffer, in the same order
+    pub(crate) fn create_tonemap_descriptor_sets(
+        &self,
+        render_pipeline: &RenderPipeline,
+        tonemap: Tonemap,
+    ) -> anyhow::Result<Vec<DescriptorSet>> {
+        let pipeline = match render_pipeline {
+            RenderPipeline::Vulkan(p) => p,
+            RenderPipeline::None => bail!("Invalid render pipeline type {}", render_pipeline),
+        };
+
+        let uniforms = self.create_cpu_buffer(
+            shaders::tonemap::fs::ty::Data {
+                operator: tonemap.shader_operator(),
+            },
+            BufferUsage::uniform_buffer(),
+        )?;
+
+        self.scene_color_images
+            .iter()
+            .map(|scene_color| {
+                Ok(DescriptorSet::Vulkan(Arc::new(
+                    PersistentDescriptorSet::start(pipeline.clone(), 0)
+                        .add_image(scene_color.clone())?
+                        .add_buffer(uniforms.clone())?
+                        .build()?,
+                )
+                    as Arc<dyn VulkanDescriptorSet + Send + Sync>))
+            })
+            .collect()
+    }
+
+    pub(crate) fn create_skybox_descriptor_set(
+        &mut self,
+        render_pipeline: &RenderPipeline,
+        cubemap: &Texture,
+        view_projection: [[f32; 4]; 4],
+    ) -> anyhow::Result<DescriptorSet> {
+        let pipeline = match render_pipeline {
+            RenderPipeline::Vulkan(p) => p,
+            RenderPipeline::None => bail!("Invalid render pipeline type {}", render_pipeline),
+        };
+
+        let cubemap = match cubemap {
+            Texture::Vulkan(t) => t,
+            Texture::None => bail!("Invalid texture type {}", cubemap),
+        };
+
+        // the frame this is drawn in may still be queued from the last time around
+        self.wait_frame_fence(self.current_frame)?;
+        let uniforms = self.skybox_uniforms.write(
+            self.current_frame,
+            shaders::skybox::vs::ty::Data { view_projection },
+        )?;
+
+        Ok(DescriptorSet::Vulkan(Arc::new(
+            PersistentDescriptorSet::start(pipeline.clone(), 0)
+                .add_buffer(uniforms)?
+                .add_sampled_image(cubemap.clone(), self.create_sampler()?)?
+                .build()?,
+        )))
+    }
+
+    pub(crate) fn create_texture_descriptor_set(
+        &self,
+        render_pipeline: &RenderPipeline,
+        texture: &Texture,
+    ) -> anyhow::Result<DescriptorSet> {
+        let pipeline = match render_pipeline {
+            RenderPipeline::Vulkan(p) => p,
+            RenderPipeline::None => bail!("Invalid render pipeline type {}", render_pipeline),
+        };
+
+        let texture = match texture {
+            Texture::Vulkan(t) => t,
+            Texture::None => bail!("Invalid texture type {}", texture),
+        };
+
+        Ok(DescriptorSet::Vulkan(Arc::new(
+            PersistentDescriptorSet::start(pipeline.clone(), 0)
+                .add_sampled_image(texture.clone(), self.create_sampler()?)?
+                .build()?,
+        )))
+    }
+
+    // vertices from the first buffer, model matrices per instance from the second
+    pub(crate) fn create_instanced_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::instanced::vs::Shader,
+        fs: shaders::instanced::fs::Shader,
+        config: &PipelineConfig,
+    ) -> anyhow::Result<RenderPipeline> {
+        self.check_pipeline_config(config)?;
+
+        Ok(match render_pass {
+            RenderPass::Vulkan(rp) => {
+                let subpass = Subpass::from(rp.clone(), 0).unwrap();
+                let has_depth = subpass.has_depth();
+
+                let builder = GraphicsPipeline::start()
+                    .vertex_input(OneVertexOneInstanceDefinition::<Vertex, InstanceData>::new())
+                    .vertex_shader(vs.main_entry_point(), ())
+                    .primitive_topology(config.topology.into())
+                    .viewports_dynamic_scissors_irrelevant(1)
+                    .fragment_shader(fs.main_entry_point(), ())
+                    .render_pass(subpass);
+                let builder = configure_pipeline!(builder, config, has_depth);
+
+                let pipeline = builder.build(self.device.clone())?;
+                self.check_push_constants(&pipeline)?;
+                RenderPipeline::Vulkan(Arc::new(pipeline))
+            }
+            _ => bail!("Render pass type {} not supported", render_pass),
+        })
+    }
+
+    // depth only, from the light's point of view
+    pub(crate) fn create_shadow_render_pipeline(
+        &self,
+        render_pass: &RenderPass,
+        vs: shaders::shadow::vs::Shader,
+        fs: shaders::shadow::fs::Shader,
+    ) -> anyhow::Result<RenderPipeline> {
+        Ok(match render_pass {
+            RenderPass::Vulkan(rp) => {
+                let pipeline = GraphicsPipeline::start()
+                    .vertex_input(OneVertexOneInstanceDefinition::<Vertex, InstanceData>::new())
+                    .vertex_shader(vs.main_entry_point(), ())
+                    .triangle_list()
+                    .viewports_dynamic_scissors_irrelevant(1)
+                    .fragment_shader(fs.main_entry_point(), ())
+                    .depth_stencil_simple_depth()
+                    .render_pass(Subpass::from(rp.clone(), 0).unwrap())
+                    .build(self.device.clone())?;
+                self.check_push_constants(&pipeline)?;
+                RenderPipeline::Vulkan(Arc::new(pipeline))
+            }
+            _ => bail!("Render pass type {} not supported", render_pass),
+        })
+    }
+
+    pub(crate) fn create_shadow_descriptor_set(
+        &mut self,
+        render_pipeline: &RenderPipeline,
+        shadow_map: &ShadowMap,
+        view_projection: [[f32; 4]; 4],
+        bias: f32,
+    ) -> anyhow::Result<DescriptorSet> {
+        let pipeline = match render_pipeline {
+            RenderPipeline::Vulkan(p) => p,
+            RenderPipeline::None => bail!("Invalid render pipeline type {}", render_pipeline),
+        };
+
+        let texture = match &shadow_map.texture {
+            Texture::Vulkan(t) => t,
+            Texture::None => bail!("Invalid texture type {}", shadow_map.texture),
+        };
+
+        // the frame this is drawn in may still be queued from the last time around
+        self.wait_frame_fence(self.current_frame)?;
+        let uniforms = self.shadow_uniforms.write(
+            self.current_frame,
+            shaders::instanced::vs::ty::Shadow {
+                view_projection,
+                bias,
+            },
+        )?;
+
+        Ok(DescriptorSet::Vulkan(Arc::new(
+            PersistentDescriptorSet::start(pipeline.clone(), 0)
+                .add_buffer(uniforms)?
+                .add_sampled_image(texture.clone(), self.create_shadow_sampler()?)?
+                .build()?,
+        )))
+    }
+
+    //#endregion
+
+    //#region Compute
+
+    // compute work is submitted to the graphics queue,
+    // which isn't guaranteed to support it
+    fn check_compute_support(&self) -> anyhow::Result<()> {
+        if !self.graphics_queue.family().supports_compute() {
+            bail!("The graphics queue family doesn't support compute");
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn create_compute_pipeline<Cs>(&self, shader: &Cs) -> anyhow::Result<ComputePipeline>
+    where
+        Cs: EntryPointAbstract<SpecializationConstants = ()>,
+        Cs::PipelineLayout: Clone + Send + Sync + 'static,
+    {
+        self.check_compute_support()?;
+
+        Ok(ComputePipeline::Vulkan(Arc::new(
+            VulkanComputePipeline::new(self.device.clone(), shader, &())?,
+        )))
+    }
+
+    pub(crate) fn create_storage_descriptor_set<T>(
+        &self,
+        compute_pipeline: &ComputePipeline,
+        buffer: &StorageBuffer<T>,
+    ) -> anyhow::Result<DescriptorSet>
+    where
+        T: Content + Send + Sync + 'static,
+    {
+        let pipeline = match compute_pipeline {
+            ComputePipeline::Vulkan(p) => p,
+            ComputePipeline::None => bail!("Invalid compute pipeline type {}", compute_pipeline),
+        };
+
+        let buffer = match buffer {
+            StorageBuffer::Vulkan(b) => b,
+            StorageBuffer::None => bail!("Invalid storage buffer type None"),
+        };
+
+        Ok(DescriptorSet::Vulkan(Arc::new(
+            PersistentDescriptorSet::start(pipeline.clone(), 0)
+                .add_buffer(buffer.clone())?
+                .build()?,
+        )))
+    }
+
+    pub(crate) fn dispatch(
+        &self,
+        compute_pipeline: &ComputePipeline,
+        descriptor_set: &DescriptorSet,
+        groups: [u32; 3],
+    ) -> anyhow::Result<()> {
+        let pipeline = match compute_pipeline {
+            ComputePipeline::Vulkan(p) => p,
+            ComputePipeline::None => bail!("Invalid compute pipeline type {}", compute_pipeline),
+        };
+
+        let descriptor_sets = match descriptor_set {
+            DescriptorSet::Vulkan(s) => vec![s.clone()],
+            DescriptorSet::None => Vec::new(),
+        };
+
+        self.check_compute_support()?;
+
+        let command_buffer = self
+            .create_primary_one_time_submit_command_buffer()?
+            .dispatch(groups, pipeline.clone(), descriptor_sets, ())?
+            .build()?;
+
+        // TODO: don't block on the dispatch
+        command_buffer
+            .execute(self.graphics_queue.clone())?
+            .then_signal_fence_and_flush()?
+            .wait(None)?;
+
+        Ok(())
+    }
+
+    //#endregion
+
+    //#region Offscreen
+
+    // sample counts (VkSampleCountFlags) both color and depth attachments can use
+    pub(crate) fn supported_sample_counts(&self) -> u32 {
+        let limits = self.device.physical_device().limits();
+        limits.framebuffer_color_sample_counts() & limits.framebuffer_depth_sample_counts()
+    }
+
+    // blocks until the image has been read back
+    pub(crate) fn render_to_image(
+        &mut self,
+        dimensions: [u32; 2],
+        samples: u32,
+        clear_color: [f32; 4],
+        depth_mode: DepthMode,
+        draw_items: &[DrawItem],
+    ) -> anyhow::Result<image::RgbaImage> {
+        // compatible with pipelines created against create_offscreen_render_pass()
+        let render_pass = self.build_offscreen_render_pass(samples)?;
+        let texels = dimensions[0] as usize * dimensions[1] as usize;
+
+        let color = AttachmentImage::with_usage(
+            self.device.clone(),
+            dimensions,
+            R8G8B8A8Unorm,
+            ImageUsage {
+                color_attachment: true,
+                transfer_source: true,
+                ..ImageUsage::none()
+            },
+        )?;
+        self.track_image(color.clone(), texels * 4);
+
+        let depth = AttachmentImage::transient_multisampled(
+            self.device.clone(),
+            dimensions,
+            samples,
+            D16Unorm,
+        )?;
+        self.track_image(depth.clone(), texels * samples as usize * 2);
+
+        let (frame_buffer, clear_values): (Arc<dyn FramebufferAbstract + Send + Sync>, _) =
+            if samples > 1 {
+                let multisampled_color = AttachmentImage::transient_multisampled(
+                    self.device.clone(),
+                    dimensions,
+                    samples,
+                    R8G8B8A8Unorm,
+                )?;
+                self.track_image(multisampled_color.clone(), texels * samples as usize * 4);
+
+                (
+                    Arc::new(
+                        Framebuffer::start(render_pass)
+                            .add(multisampled_color)?
+                            .add(color.clone())?
+                            .add(depth)?
+                            .build()?,
+                    ),
+                    vec![
+                        clear_color.into(),
+                        ClearValue::None,
+                        depth_mode.clear_value().into(),
+                    ],
+                )
+            } else {
+                (
+                    Arc::new(
+                        Framebuffer::start(render_pass)
+                            .add(color.clone())?
+                            .add(depth)?
+                            .build()?,
+                    ),
+                    vec![clear_color.into(), depth_mode.clear_value().into()],
+                )
+            };
+
+        let dynamic_state = viewport_dynamic_state(dimensions);
+        let mut command_buffer = self
+            .create_primary_one_time_submit_command_buffer()?
+            .begin_render_pass(frame_buffer, true, clear_values)?;
+
+        // not cached, these pipelines belong to the offscreen render pass
+        for draw_item in draw_items {
+            let secondary = self.record_draw_item(draw_item, &dynamic_state)?;
+            command_buffer = unsafe { command_buffer.execute_commands(secondary)? };
+        }
+
+        let buffer = CpuAccessibleBuffer::from_iter(
+            self.device.clone(),
+            BufferUsage::transfer_destination(),
+            (0..texels * 4).map(|_| 0u8),
+        )?;
+
+        let command_buffer = command_buffer
+            .end_render_pass()?
+            .copy_image_to_buffer(color, buffer.clone())?
+            .build()?;
+
+        command_buffer
+            .execute(self.graphics_queue.clone())?
+            .then_signal_fence_and_flush()?
+            .wait(None)?;
+
+        let data = buffer.read()?.to_vec();
+        image::RgbaImage::from_raw(dimensions[0], dimensions[1], data)
+            .ok_or_else(|| anyhow!("Offscreen image size mismatch"))
+    }
+
+    //#endregion
+
+    // blocks until the device has finished all submitted work
+    pub(crate) fn wait_idle(&mut self) -> anyhow::Result<()> {
+        // the device must not be used by anything else while waiting
+        unsafe { self.device.wait()? };
+
+        for fence in self.frame_fences.iter_mut() {
+            fence.take();
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn frames_in_flight(&self) -> usize {
+        self.frame_fences.len()
+    }
+
+    pub(crate) fn begin_frame(&mut self) {
+        for fence in self.frame_fences.iter_mut().flatten() {
+            fence.cleanup_finished();
+        }
+    }
+
+    pub fn get_swapchain_format(&self) -> (Format, ColorSpace) {
+        self.swapchain_format
+    }
+
+    pub(crate) fn current_swapchain_image(&self) -> usize {
+        self.current_swapchain_image
+    }
+
+    // blocks until the submission that last used the frame slot is done
+    fn wait_frame_fence(&mut self, frame: usize) -> anyhow::Result<()> {
+        if let Some(fence) = self.frame_fences[frame].take() {
+            match fence.wait(None) {
+                Ok(()) => (),
+                Err(FlushError::DeviceLost) => bail!(RendererError::DeviceLost),
+                Err(e) => warn!("{:?}", e),
+            }
+        }
+
+        Ok(())
     }
 
     fn acquire_swapchain(&mut self) -> anyhow::Result<Option<SwapchainAcquireFuture<Window>>> {
@@ -411,6 +2330,7 @@ impl VulkanRendererState {
                 Err(AcquireError::OutOfDate) => {
                     return Ok(None);
                 }
+                Err(AcquireError::DeviceLost) => bail!(RendererError::DeviceLost),
                 Err(e) => bail!(e),
             };
 
@@ -421,86 +2341,239 @@ impl VulkanRendererState {
 
     pub(crate) fn draw_data<F>(
         &mut self,
-        render_pipeline: &RenderPipeline,
         clear_values: [f32; 4],
-        draw_data: &VertexBuffer,
+        depth_mode: DepthMode,
+        shadow_pass: Option<&ShadowPass>,
+        draw_items: &[DrawItem],
+        post_process: Option<&PostProcessItem>,
         frame_buffers: F,
     ) -> anyhow::Result<bool>
     where
         F: AsRef<Vec<FrameBuffer>>,
     {
+        self.wait_frame_fence(self.current_frame)?;
+
         let acquire_future = self.acquire_swapchain()?;
         if acquire_future.is_none() {
             return Ok(false);
         }
         let acquire_future = acquire_future.unwrap();
 
+        // the swapchain can hand back an image that an older frame
+        // (in a different slot) is still rendering to
+        if let Some(frame) = self.image_frames[self.current_swapchain_image] {
+            if frame != self.current_frame {
+                self.wait_frame_fence(frame)?;
+            }
+        }
+        self.image_frames[self.current_swapchain_image] = Some(self.current_frame);
+
         let frame_buffers = frame_buffers.as_ref();
         let frame_buffer = &frame_buffers[self.current_swapchain_image];
 
-        let clear_values = vec![clear_values.into()];
+        // the swapchain image is entirely overwritten by the post-process subpass
+        let clear_values = vec![
+            ClearValue::None,
+            clear_values.into(),
+            depth_mode.clear_value().into(),
+        ];
 
-        let command_buffer = self
-            .create_primary_one_time_submit_command_buffer()?
-            .begin_render_pass(
-                match frame_buffer {
-                    FrameBuffer::Vulkan(f) => f,
-                    FrameBuffer::None => bail!("Invalid framebuffer type {}", frame_buffer),
-                }
-                .clone(),
-                false,
-                clear_values,
-            )?
-            .draw(
-                match render_pipeline {
-                    RenderPipeline::Vulkan(p) => p,
-                    RenderPipeline::None => {
-                        bail!("Invalid render pipeline type {}", render_pipeline)
-                    }
-                }
-                .clone(),
-                &self.dynamic_state,
-                vec![match draw_data {
-                    VertexBuffer::Vulkan(v) => v,
-                    VertexBuffer::None => bail!("Invalid vertex buffer type {}", draw_data),
-                }
-                .clone()],
-                (),
-                (),
-            )?
-            .end_render_pass()?
-            .build()?;
+        if self.command_buffers_dirty {
+            self.cached_command_buffers.clear();
+            self.command_buffers_dirty = false;
+        }
+
+        let frame_buffer = match frame_buffer {
+            FrameBuffer::Vulkan(f) => f,
+            FrameBuffer::None => bail!("Invalid framebuffer type {}", frame_buffer),
+        };
+
+        let mut command_buffer = self.create_primary_one_time_submit_command_buffer()?;
+        let mut used_keys = HashSet::with_capacity(draw_items.len());
+
+        if let Some(shadow_pass) = shadow_pass {
+            let shadow_frame_buffer = match &shadow_pass.shadow_map.frame_buffer {
+                FrameBuffer::Vulkan(f) => f,
+                FrameBuffer::None => bail!(
+                    "Invalid shadow framebuffer type {}",
+                    shadow_pass.shadow_map.frame_buffer
+                ),
+            };
+
+            command_buffer = command_buffer.begin_render_pass(
+                shadow_frame_buffer.clone(),
+                true,
+                vec![ClearValue::Depth(1.0)],
+            )?;
+            command_buffer = self.execute_draw_items(
+                command_buffer,
+                shadow_pass.draw_items,
+                &viewport_dynamic_state([shadow_pass.shadow_map.size; 2]),
+                &mut used_keys,
+            )?;
+            command_buffer = command_buffer.end_render_pass()?;
+        }
+
+        let dynamic_state = self.dynamic_state.clone();
+        let mut command_buffer = self.execute_draw_items(
+            command_buffer.begin_render_pass(frame_buffer.clone(), true, clear_values)?,
+            draw_items,
+            &dynamic_state,
+            &mut used_keys,
+        )?;
+
+        // drop anything that wasn't drawn this frame (transient buffers, removed items)
+        self.cached_command_buffers
+            .retain(|key, _| used_keys.contains(key));
+
+        if frame_buffer.num_subpasses() > 1 {
+            let post_process = match post_process {
+                Some(post_process) => post_process,
+                None => bail!("Render pass requires a post-process item"),
+            };
 
-        let future = self
-            .frame_future
-            .take()
-            .unwrap()
-            .join(acquire_future)
+            let secondary = self.record_post_process(post_process, self.current_swapchain_image)?;
+            command_buffer = command_buffer.next_subpass(true)?;
+            command_buffer = unsafe { command_buffer.execute_commands(secondary)? };
+        }
+
+        let command_buffer = command_buffer.end_render_pass()?.build()?;
+
+        let present = acquire_future
             .then_execute(self.graphics_queue.clone(), command_buffer)?
             .then_swapchain_present(
                 self.graphics_queue.clone(),
                 self.swapchain.clone(),
                 self.current_swapchain_image,
-            )
-            .then_signal_fence_and_flush();
+            );
+        let future = (Box::new(present) as Box<dyn GpuFuture>).then_signal_fence_and_flush();
 
         let mut recreate_swapchain = false;
         match future {
             Ok(future) => {
-                self.frame_future = Some(Box::new(future) as Box<_>);
+                self.frame_fences[self.current_frame] = Some(future);
             }
             Err(FlushError::OutOfDate) => {
                 recreate_swapchain = true;
-                self.frame_future =
-                    Some(Box::new(vulkano::sync::now(self.device.clone())) as Box<_>);
             }
+            // retrying would just fail again every frame
+            Err(FlushError::DeviceLost) => bail!(RendererError::DeviceLost),
             Err(e) => {
                 warn!("{:?}", e);
-                self.frame_future =
-                    Some(Box::new(vulkano::sync::now(self.device.clone())) as Box<_>);
             }
         }
 
+        self.current_frame = (self.current_frame + 1) % self.frame_fences.len();
+
         Ok(!recreate_swapchain)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skybox_passes_at_the_far_plane() {
+        assert_eq!(
+            skybox_depth_compare(DepthMode::Standard),
+            Compare::LessOrEqual
+        );
+        assert_eq!(
+            skybox_depth_compare(DepthMode::Reversed),
+            Compare::GreaterOrEqual
+        );
+    }
+
+    #[test]
+    fn unsupported_and_duplicate_layers_are_dropped() {
+        let requested = vec![
+            "VK_LAYER_KHRONOS_validation".to_owned(),
+            "VK_LAYER_MISSING".to_owned(),
+            "VK_LAYER_KHRONOS_validation".to_owned(),
+        ];
+
+        assert_eq!(
+            filter_layers(&requested, &["VK_LAYER_KHRONOS_validation"]),
+            vec!["VK_LAYER_KHRONOS_validation"]
+        );
+        assert!(filter_layers(&requested, &[]).is_empty());
+    }
+
+    #[test]
+    fn queue_priorities_are_validated() {
+        assert_eq!(
+            graphics_queue_priorities(&[], 4).unwrap(),
+            vec![DEFAULT_QUEUE_PRIORITY]
+        );
+        assert_eq!(
+            graphics_queue_priorities(&[1.0, 0.5], 4).unwrap(),
+            vec![1.0, 0.5]
+        );
+
+        // more queues than the family has
+        assert!(graphics_queue_priorities(&[1.0, 1.0], 1).is_err());
+
+        assert!(graphics_queue_priorities(&[1.5], 4).is_err());
+        assert!(graphics_queue_priorities(&[-0.1], 4).is_err());
+    }
+
+    #[test]
+    fn swapchain_image_count_is_clamped_to_the_surface() {
+        // 0 is one more than the minimum
+        assert_eq!(clamp_swapchain_image_count(0, 2, Some(8)), 3);
+
+        assert_eq!(clamp_swapchain_image_count(3, 2, Some(8)), 3);
+        assert_eq!(clamp_swapchain_image_count(1, 2, Some(8)), 2);
+        assert_eq!(clamp_swapchain_image_count(16, 2, Some(8)), 8);
+
+        // unbounded
+        assert_eq!(clamp_swapchain_image_count(16, 2, None), 16);
+    }
+
+    #[test]
+    fn srgb_swapchain_format_is_preferred() {
+        let supported = vec![
+            (Format::B8G8R8A8Unorm, ColorSpace::SrgbNonLinear),
+            (Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear),
+            (Format::R16G16B16A16Sfloat, ColorSpace::ExtendedSrgbLinear),
+            (Format::A2B10G10R10UnormPack32, ColorSpace::SrgbNonLinear),
+        ];
+
+        assert_eq!(
+            select_swapchain_format(&supported, false),
+            Some((Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear))
+        );
+
+        // the float format is only listed with a color space vulkano can't create
+        assert_eq!(
+            select_swapchain_format(&supported, true),
+            Some((Format::A2B10G10R10UnormPack32, ColorSpace::SrgbNonLinear))
+        );
+
+        // no sRGB format, first usable one
+        assert_eq!(
+            select_swapchain_format(&supported[..1], true),
+            Some((Format::B8G8R8A8Unorm, ColorSpace::SrgbNonLinear))
+        );
+
+        assert_eq!(select_swapchain_format(&supported[2..3], false), None);
+    }
+
+    #[test]
+    fn anisotropy_is_clamped_to_the_device() {
+        assert_eq!(clamp_anisotropy(8.0, true, 16.0), 8.0);
+        assert_eq!(clamp_anisotropy(32.0, true, 16.0), 16.0);
+
+        // unsupported or disabled
+        assert_eq!(clamp_anisotropy(8.0, false, 16.0), 1.0);
+        assert_eq!(clamp_anisotropy(0.0, true, 16.0), 1.0);
+    }
+
+    #[test]
+    fn push_constants_must_fit_the_device_limit() {
+        assert!(check_push_constants_size(128, 128).is_ok());
+        assert!(check_push_constants_size(std::mem::size_of::<PushConstants>(), 128).is_ok());
+        assert!(check_push_constants_size(132, 128).is_err());
+    }
+}
@@ -17,9 +17,26 @@ fn main() -> anyhow::Result<()> {
         .start()
         .unwrap();
 
-    let mut engine = Engine::new("viewer", RendererType::Vulkan, &options.get_window_config())
-        .unwrap_or_else(|e| panic!("Error initializing engine: {}", e));
-
+    let app_version = [
+        env!("CARGO_PKG_VERSION_MAJOR").parse()?,
+        env!("CARGO_PKG_VERSION_MINOR").parse()?,
+        env!("CARGO_PKG_VERSION_PATCH").parse()?,
+    ];
+
+    let mut engine = Engine::new(
+        "viewer",
+        app_version,
+        RendererType::Auto,
+        &options.get_window_config(),
+        &options.get_renderer_config(),
+    )
+    .unwrap_or_else(|e| panic!("Error initializing engine: {}", e));
+
+    if let Some(asset_root) = options.get_asset_root() {
+        engine.set_asset_root(asset_root);
+    }
+
+    // resolved relative to the engine asset root
     let mut scene = PathBuf::from(SCENE_DIR);
     scene.push(options.get_scene());
 
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
@@ -6,14 +8,50 @@ pub struct Options {
     #[structopt(short, long)]
     scene: String,
 
+    #[structopt(long, parse(from_os_str))]
+    asset_root: Option<PathBuf>,
+
     #[structopt(short, long, default_value = "1024")]
     width: u32,
 
     #[structopt(short, long, default_value = "768")]
     height: u32,
 
+    #[structopt(long, default_value = "320")]
+    min_width: u32,
+
+    #[structopt(long, default_value = "240")]
+    min_height: u32,
+
     #[structopt(short, long)]
     fullscreen: bool,
+
+    #[structopt(long)]
+    physical_pixels: bool,
+
+    #[structopt(long)]
+    pause_when_unfocused: bool,
+
+    #[structopt(long)]
+    validation: bool,
+
+    #[structopt(long = "layer")]
+    layers: Vec<String>,
+
+    #[structopt(long, default_value = "16")]
+    anisotropy: f32,
+
+    #[structopt(long, default_value = "2")]
+    frames_in_flight: usize,
+
+    #[structopt(long, default_value = "0")]
+    swapchain_images: u32,
+
+    #[structopt(long)]
+    hdr: bool,
+
+    #[structopt(long, default_value = "0")]
+    shadow_map_size: u32,
 }
 
 impl Options {
@@ -21,11 +59,32 @@ impl Options {
         &self.scene
     }
 
+    pub fn get_asset_root(&self) -> Option<&PathBuf> {
+        self.asset_root.as_ref()
+    }
+
     pub fn get_window_config(&self) -> engine::config::WindowConfig {
         engine::config::WindowConfig {
             width: self.width,
             height: self.height,
+            min_width: self.min_width,
+            min_height: self.min_height,
             fullscreen: self.fullscreen,
+            size_in_physical_pixels: self.physical_pixels,
+            pause_when_unfocused: self.pause_when_unfocused,
+        }
+    }
+
+    pub fn get_renderer_config(&self) -> engine::config::RendererConfig {
+        engine::config::RendererConfig {
+            validation: self.validation,
+            layers: self.layers.clone(),
+            anisotropy: self.anisotropy,
+            frames_in_flight: self.frames_in_flight,
+            swapchain_image_count: self.swapchain_images,
+            hdr: self.hdr,
+            shadow_map_size: self.shadow_map_size,
+            ..Default::default()
         }
     }
 }